@@ -0,0 +1,87 @@
+use eframe::egui;
+
+use crate::Viewport;
+
+/// One shape of an [`Annotations`] overlay. Positions are in world units, the sim's own
+/// cell coordinates; sizes of text and strokes are in screen points so they stay
+/// readable at any zoom.
+#[derive(Clone, Debug)]
+pub enum Annotation {
+    Text { pos: egui::Pos2, text: String, color: egui::Color32 },
+    Line { from: egui::Pos2, to: egui::Pos2, stroke: egui::Stroke },
+    /// `radius` is in world units, so the circle grows with the zoom.
+    Circle { center: egui::Pos2, radius: f32, stroke: egui::Stroke },
+    Arrow { from: egui::Pos2, to: egui::Pos2, stroke: egui::Stroke },
+}
+
+/// Labels and shapes a sim wants drawn over its raster view, filled in by
+/// `Simulation::annotate` each frame and painted by the App with egui on top of the texture.
+///
+/// ```
+/// use eframe::egui::{pos2, Color32};
+/// use simulation_station::Annotations;
+///
+/// let mut annotations = Annotations::default();
+/// annotations.text(pos2(10.0, 20.0), "record", Color32::WHITE);
+/// annotations.arrow(pos2(10.0, 22.0), pos2(10.0, 40.0), (1.5, Color32::WHITE));
+/// assert_eq!(annotations.shapes().len(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Annotations {
+    shapes: Vec<Annotation>,
+}
+
+impl Annotations {
+    /// Text centered above `pos`.
+    pub fn text(&mut self, pos: egui::Pos2, text: impl Into<String>, color: egui::Color32) {
+        self.shapes.push(Annotation::Text { pos, text: text.into(), color });
+    }
+
+    pub fn line(&mut self, from: egui::Pos2, to: egui::Pos2, stroke: impl Into<egui::Stroke>) {
+        self.shapes.push(Annotation::Line { from, to, stroke: stroke.into() });
+    }
+
+    pub fn circle(&mut self, center: egui::Pos2, radius: f32, stroke: impl Into<egui::Stroke>) {
+        self.shapes.push(Annotation::Circle { center, radius, stroke: stroke.into() });
+    }
+
+    /// A line from `from` with an arrowhead at `to`.
+    pub fn arrow(&mut self, from: egui::Pos2, to: egui::Pos2, stroke: impl Into<egui::Stroke>) {
+        self.shapes.push(Annotation::Arrow { from, to, stroke: stroke.into() });
+    }
+
+    pub fn shapes(&self) -> &[Annotation] {
+        &self.shapes
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    /// Paints every shape where `viewport` puts its world positions on screen.
+    pub(crate) fn paint(&self, painter: &egui::Painter, viewport: &Viewport) {
+        let to_screen = |pos: egui::Pos2| viewport.to_screen(pos);
+        for shape in &self.shapes {
+            match shape {
+                Annotation::Text { pos, text, color } => {
+                    let font = egui::FontId::proportional(13.0);
+                    let galley = painter.layout_no_wrap(text.clone(), font, *color);
+                    let pos = to_screen(*pos) - egui::vec2(galley.size().x / 2.0, galley.size().y + 2.0);
+                    let backdrop = egui::Rect::from_min_size(pos, galley.size()).expand(2.0);
+                    painter.rect_filled(backdrop, 2.0, egui::Color32::from_black_alpha(150));
+                    painter.galley(pos, galley, *color);
+                }
+                Annotation::Line { from, to, stroke } => {
+                    painter.line_segment([to_screen(*from), to_screen(*to)], *stroke);
+                }
+                Annotation::Circle { center, radius, stroke } => {
+                    painter.circle_stroke(to_screen(*center), radius * viewport.scale(), *stroke);
+                }
+                Annotation::Arrow { from, to, stroke } => {
+                    let from = to_screen(*from);
+                    painter.arrow(from, to_screen(*to) - from, *stroke);
+                }
+            }
+        }
+    }
+}
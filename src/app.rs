@@ -0,0 +1,3020 @@
+use eframe::egui;
+use egui::emath::GuiRounding;
+use serde::{Deserialize, Serialize};
+use egui_dock::{DockArea, DockState, TabViewer};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use web_time::{Duration, Instant};
+use crate::browser::SimBrowser;
+use crate::dock::{self, Panel};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::export;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::svg;
+use crate::effects::PostProcessor;
+use crate::history::FrameHistory;
+use crate::hud::{FrameTimings, PerfHud};
+use crate::logger::LogConsole;
+use crate::palette::{Command, CommandPalette};
+use crate::pool::{self, FramePool};
+use crate::rewind::Rewind;
+use crate::session::{SessionAction, SessionFile, SessionPlayback, SessionRecorder};
+use crate::workspace::{SlotState, TabState, Workspace};
+#[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+use crate::video::{Container, VideoRecorder};
+use crate::{
+    Annotations, Camera2D, CanvasFit, Capabilities, Effects, Metrics, NoSim, Palette, PixelFormat,
+    PresetStore, Region, RenderMode, SimConfig, SimError, SimEntry, SimInfo, SimInput, SimLogger,
+    SimRegistry, Simulation, SpeedCurve, StepMode, TextureFilter, Theme, ThemeKind, Viewport,
+    WorkerSettings,
+};
+
+/// Blends a straight-alpha RGBA buffer over a solid background color.
+pub(crate) fn composite_over(size: [usize; 2], rgba: &[u8], background: egui::Color32) -> egui::ColorImage {
+    let mut image = egui::ColorImage::filled(size, egui::Color32::TRANSPARENT);
+    pool::copy_rgba_over(&mut image, rgba, background);
+    image
+}
+
+/// Blends a straight-alpha RGBA `layer` over `image`, scaling its alpha by `opacity`.
+fn composite_layer(image: &mut egui::ColorImage, layer: &[u8], opacity: f32) {
+    for (pixel, px) in image.pixels.iter_mut().zip(layer.chunks_exact(4)) {
+        let a = (px[3] as f32 * opacity) as u32;
+        if a == 0 {
+            continue;
+        }
+        let blend = |c: u8, b: u8| ((c as u32 * a + b as u32 * (255 - a)) / 255) as u8;
+        *pixel = egui::Color32::from_rgb(
+            blend(px[0], pixel.r()),
+            blend(px[1], pixel.g()),
+            blend(px[2], pixel.b()),
+        );
+    }
+}
+
+/// The user's settings for one of a sim's layers.
+#[derive(Clone, Copy)]
+struct LayerState {
+    visible: bool,
+    opacity: f32,
+}
+
+/// Framework-measured statistics for the running simulation.
+#[derive(Clone, Copy, Default)]
+pub struct SimStats {
+    /// Number of `update()` calls since the last reset.
+    pub steps: u64,
+    /// Seconds spent unpaused since the last reset.
+    pub wall_time: f64,
+    /// Steps per second achieved over the most recent measurement window.
+    pub steps_per_sec: f64,
+}
+
+#[derive(Default)]
+struct StatsTracker {
+    stats: SimStats,
+    window_steps: u64,
+    window_time: f64,
+}
+
+impl StatsTracker {
+    const WINDOW: f64 = 0.5;
+
+    fn record(&mut self, steps: u64, dt: f64) {
+        self.stats.steps += steps;
+        self.stats.wall_time += dt;
+        self.window_steps += steps;
+        self.window_time += dt;
+        if self.window_time >= Self::WINDOW {
+            self.stats.steps_per_sec = self.window_steps as f64 / self.window_time;
+            self.window_steps = 0;
+            self.window_time = 0.0;
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+fn speed_slider(ui: &mut egui::Ui, speed: &mut f32, config: &SimConfig) {
+    match config.speed_curve {
+        SpeedCurve::Steps(steps) if !steps.is_empty() => {
+            let mut index = steps
+                .iter()
+                .position(|&step| step >= *speed)
+                .unwrap_or(steps.len() - 1);
+            ui.add(
+                egui::Slider::new(&mut index, 0..=steps.len() - 1)
+                    .custom_formatter(|i, _| format!("{}", steps[i as usize]))
+                    .text(config.speed_unit),
+            );
+            *speed = steps[index];
+        }
+        curve => {
+            ui.add(
+                egui::Slider::new(speed, config.min_speed..=config.max_speed)
+                    .text(config.speed_unit)
+                    .logarithmic(curve == SpeedCurve::Logarithmic),
+            );
+        }
+    }
+}
+
+/// Quick picks at multiples of the sim's default speed, plus its maximum.
+fn speed_presets(ui: &mut egui::Ui, speed: &mut f32, config: &SimConfig) {
+    let default = default_speed(config);
+    let presets = [("0.25×", 0.25), ("1×", 1.0), ("10×", 10.0), ("100×", 100.0)]
+        .map(|(label, multiple)| (label, (default * multiple).clamp(config.min_speed, config.max_speed)));
+    ui.horizontal(|ui| {
+        for (label, preset) in presets.into_iter().chain([("Max", config.max_speed)]) {
+            let button = ui
+                .selectable_label(*speed == preset, label)
+                .on_hover_text(format!("{preset} {}", config.speed_unit));
+            if button.clicked() {
+                *speed = preset;
+            }
+        }
+    });
+}
+
+fn info_ui(ui: &mut egui::Ui, info: &SimInfo) {
+    ui.label(info.description);
+    ui.horizontal_wrapped(|ui| {
+        ui.small(info.category.label());
+        for tag in info.tags {
+            ui.small(format!("#{tag}"));
+        }
+    });
+}
+
+/// One loaded simulation together with the view state the App keeps for it.
+struct SimSlot {
+    sim: Box<dyn Simulation>,
+    texture: Option<egui::TextureHandle>,
+    camera: Camera2D,
+    /// Where the world sits in the pane at zoom 1, from the App's `CanvasFit`.
+    frame_rect: egui::Rect,
+    stats: StatsTracker,
+    error: Option<SimError>,
+    view_index: usize,
+    rewind: Rewind,
+    history: FrameHistory,
+    /// The raster frame painted most recently, for recordings.
+    frame: Option<Arc<egui::ColorImage>>,
+    /// Records what happens to the sim so the run can be replayed.
+    session: Option<SessionRecorder>,
+    /// Drives the sim from a recorded session instead of the user.
+    playback: Option<SessionPlayback>,
+    /// A speed change the playback made, for the tab to pick up.
+    replayed_speed: Option<f32>,
+    /// Filtering the user picked over the sim's own `texture_filter`.
+    filter: Option<TextureFilter>,
+    /// What the texture was last rendered from; `None` renders on the next paint.
+    rendered: Option<RenderKey>,
+    /// Post-processing for the next paint, from the App's per-sim settings.
+    effects: Effects,
+    post: PostProcessor,
+    /// Colors for an `Indexed` sim's pixels, and how far they've cycled, set like `effects`.
+    palette: Palette,
+    palette_shift: u8,
+    pool: FramePool,
+    /// Visibility and opacity of the sim's `layers()`, matched up by index.
+    layers: Vec<LayerState>,
+    metrics: Metrics,
+    /// Refilled from `annotate()` every paint.
+    annotations: Annotations,
+}
+
+/// Everything a raster frame depends on, so an unchanged frame can be drawn from the
+/// texture as it is.
+#[derive(Clone, Copy, PartialEq)]
+struct RenderKey {
+    version: u64,
+    view: usize,
+    background: egui::Color32,
+    filter: TextureFilter,
+    effects: Effects,
+    region: Option<Region>,
+    palette_shift: u8,
+}
+
+impl SimSlot {
+    fn new(sim: Box<dyn Simulation>) -> Self {
+        Self {
+            sim,
+            texture: None,
+            camera: Camera2D::default(),
+            frame_rect: egui::Rect::NOTHING,
+            stats: StatsTracker::default(),
+            error: None,
+            view_index: 0,
+            rewind: Rewind::default(),
+            history: FrameHistory::default(),
+            frame: None,
+            session: None,
+            playback: None,
+            replayed_speed: None,
+            filter: None,
+            rendered: None,
+            effects: Effects::NONE,
+            post: PostProcessor::default(),
+            palette: Palette::default(),
+            palette_shift: 0,
+            pool: FramePool::default(),
+            layers: Vec::new(),
+            metrics: Metrics::default(),
+            annotations: Annotations::default(),
+        }
+    }
+
+    fn texture_filter(&self) -> TextureFilter {
+        self.filter.unwrap_or(self.sim.config().texture_filter)
+    }
+
+    fn render_mode(&self) -> RenderMode {
+        self.sim
+            .views()
+            .get(self.view_index)
+            .map_or(self.sim.config().render_mode, |view| view.render_mode)
+    }
+
+    /// How the App's clock advances this slot, or `None` if it shouldn't be advanced at all.
+    fn pace(&self) -> Option<StepMode> {
+        let mode = self.sim.config().step_mode;
+        (self.error.is_none() && !self.sim.is_finished() && mode != StepMode::Manual).then_some(mode)
+    }
+
+    /// Restarts on the user's request, ending any replay in progress.
+    fn reset(&mut self, seed: u64) {
+        if let Some(session) = &mut self.session {
+            session.record(self.stats.stats.steps, SessionAction::Reset(seed));
+        }
+        self.playback = None;
+        self.restart(seed);
+    }
+
+    fn restart(&mut self, seed: u64) {
+        self.sim.reset(seed);
+        self.post.clear();
+        self.metrics.clear();
+        self.stats.reset();
+        self.error = None;
+        self.rewind.clear();
+        self.history.clear();
+    }
+
+    /// Switches the sim to a `width` × `height` resolution and starts it over.
+    fn resize(&mut self, width: usize, height: usize, seed: u64) {
+        if let Err(e) = self.sim.resize(width, height) {
+            self.fail(e);
+            return;
+        }
+        // A recording can't describe the change, so it ends here
+        self.session = None;
+        self.playback = None;
+        self.restart(seed);
+    }
+
+    fn record_checkpoint(&mut self) {
+        if self.sim.capabilities().contains(Capabilities::SNAPSHOTS) {
+            self.rewind.record(&*self.sim, self.stats.stats);
+        }
+    }
+
+    /// Restores checkpoint `index` along with the stats it was taken at.
+    fn rewind_to(&mut self, index: usize) {
+        let Some(checkpoint) = self.rewind.get(index) else { return };
+        match self.sim.load_state(&checkpoint.data) {
+            Ok(()) => {
+                // Neither a recording nor a replay can follow the jump
+                self.session = None;
+                self.playback = None;
+                self.stats.stats = checkpoint.stats;
+                self.rewind.cursor = Some(index);
+                self.error = None;
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Shows `error` over the pane and logs it to the console.
+    fn fail(&mut self, error: SimError) {
+        SimLogger::new(self.sim.name()).error(error.to_string());
+        self.error = Some(error);
+    }
+
+    /// Advances by up to `n` steps and records them against `dt` seconds.
+    fn run_steps(&mut self, n: usize, dt: f32) -> Result<(), SimError> {
+        if self.playback.is_some() {
+            return self.replay_steps(n, dt);
+        }
+        let result = if n == 0 { Ok(0) } else { self.sim.update_n(n) };
+        self.stats.record(result.as_ref().map_or(0, |&done| done as u64), dt as f64);
+        self.collect_metrics();
+        result.map(drop)
+    }
+
+    /// Asks the sim for its metrics if it moved on since they were last collected.
+    fn collect_metrics(&mut self) {
+        let steps = self.stats.stats.steps;
+        if steps != self.metrics.step() || self.metrics.is_empty() {
+            self.metrics.set_step(steps);
+            self.sim.record_metrics(&mut self.metrics);
+        }
+    }
+
+    /// Like `run_steps`, but stops at every step a replayed action is due at to carry
+    /// it out, and doesn't run past the end of the recording.
+    fn replay_steps(&mut self, n: usize, dt: f32) -> Result<(), SimError> {
+        let mut remaining = n;
+        let mut dt = dt as f64;
+        loop {
+            self.apply_playback();
+            let Some(playback) = &self.playback else { break };
+            let chunk = (playback.steps_until_next(self.stats.stats.steps) as usize).min(remaining);
+            if chunk == 0 {
+                break;
+            }
+            let done = self.sim.update_n(chunk)?;
+            self.stats.record(done as u64, dt);
+            dt = 0.0;
+            remaining -= done;
+            if done < chunk {
+                break;
+            }
+        }
+        self.stats.record(0, dt);
+        self.collect_metrics();
+        Ok(())
+    }
+
+    /// Carries out the replayed actions due at the current step.
+    fn apply_playback(&mut self) {
+        let Some(mut playback) = self.playback.take() else { return };
+        while let Some(action) = playback.pop_due(self.stats.stats.steps) {
+            match action {
+                SessionAction::Params(values) => {
+                    if let Some(params) = self.sim.params() {
+                        let _ = params.set_json(values);
+                    }
+                    self.invalidate();
+                }
+                SessionAction::Speed(speed) => self.replayed_speed = Some(speed),
+                SessionAction::Input(input) => self.sim.handle_input(input),
+                SessionAction::Reset(seed) => self.restart(seed),
+            }
+        }
+        self.playback = Some(playback);
+    }
+
+    /// Steps on user request. Realtime sims advance by one nominal 60 Hz frame per step.
+    fn step_manually(&mut self, n: usize, time_scale: f32) -> Result<(), SimError> {
+        if self.sim.config().step_mode != StepMode::Realtime {
+            return self.run_steps(n, 0.0);
+        }
+        for done in 0..n {
+            if let Err(e) = self.sim.update_dt(time_scale / 60.0) {
+                self.stats.record(done as u64, 0.0);
+                return Err(e);
+            }
+        }
+        self.stats.record(n as u64, 0.0);
+        self.collect_metrics();
+        Ok(())
+    }
+
+    /// Forwards pointer events on the canvas, and unclaimed key events if `keys` is set.
+    fn route_input(&mut self, ctx: &egui::Context, response: &egui::Response, keys: bool) {
+        // A replay's input comes from the recording
+        if !self.sim.capabilities().contains(Capabilities::INPUT) || self.playback.is_some() {
+            return;
+        }
+        let viewport = self.viewport(response.rect);
+        let to_cell = |pos: egui::Pos2| {
+            viewport.cell_at(pos).map(|(x, y)| (x, y, viewport.to_world(pos)))
+        };
+        let keyboard_free = keys && !ctx.wants_keyboard_input();
+        let (events, held) = ctx.input(|i| {
+            let held = if i.pointer.primary_down() {
+                Some(egui::PointerButton::Primary)
+            } else if i.pointer.secondary_down() {
+                Some(egui::PointerButton::Secondary)
+            } else {
+                None
+            };
+            (i.events.clone(), held)
+        });
+
+        for event in events {
+            let input = match event {
+                // The middle button belongs to camera panning
+                egui::Event::PointerButton { button: egui::PointerButton::Middle, .. } => continue,
+                egui::Event::PointerButton { pos, button, pressed, .. } => {
+                    let Some((x, y, pos)) = to_cell(pos) else { continue };
+                    if pressed && response.hovered() {
+                        SimInput::PointerDown { x, y, button, pos }
+                    } else if !pressed {
+                        SimInput::PointerUp { x, y, button, pos }
+                    } else {
+                        continue;
+                    }
+                }
+                egui::Event::PointerMoved(pos) if response.is_pointer_button_down_on() => {
+                    let (Some((x, y, pos)), Some(button)) = (to_cell(pos), held) else { continue };
+                    SimInput::PointerDrag { x, y, button, pos }
+                }
+                egui::Event::Key { key, pressed, repeat: false, .. } if keyboard_free => {
+                    SimInput::Key { key, pressed }
+                }
+                _ => continue,
+            };
+            if let Some(session) = &mut self.session {
+                session.record(self.stats.stats.steps, SessionAction::Input(input));
+            }
+            self.sim.handle_input(input);
+        }
+    }
+
+    /// Once the world no longer fits in the canvas, draws the whole of it in the top-right
+    /// corner with the visible part outlined. Clicking or dragging on it recenters the view.
+    fn minimap_ui(&mut self, ui: &egui::Ui, painter: &egui::Painter, canvas: egui::Rect, index: usize) {
+        const MAX_SIZE: f32 = 160.0;
+        let world = self.camera.world_rect(self.frame_rect);
+        if canvas.contains_rect(world) || !canvas.intersects(world) {
+            return;
+        }
+        let (w, h) = self.sim.dimensions();
+        let size = egui::vec2(w as f32, h as f32) * (MAX_SIZE / w.max(h).max(1) as f32);
+        let map = egui::Rect::from_min_size(canvas.right_top() + egui::vec2(-size.x - 8.0, 8.0), size);
+
+        painter.rect_filled(map.expand(2.0), 2.0, egui::Color32::from_black_alpha(180));
+        match (&self.texture, self.render_mode()) {
+            (Some(texture), RenderMode::Raster) => {
+                let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                painter.image(texture.id(), map, uv, egui::Color32::WHITE);
+            }
+            _ => {
+                let viewport = Viewport::fixed(map, (w, h));
+                self.sim.render_world(&painter.with_clip_rect(map), &viewport);
+            }
+        }
+        let to_map = |pos: egui::Pos2| map.min + (pos - world.min) / world.size() * map.size();
+        if let Some(focus) = self.focus() {
+            let focus = egui::Rect::from_min_max(
+                map.min + focus.min.to_vec2() * map.size(),
+                map.min + focus.max.to_vec2() * map.size(),
+            );
+            painter.rect_stroke(focus, 0.0, (1.0, Theme::current().accent), egui::StrokeKind::Outside);
+        }
+        let visible = canvas.intersect(world);
+        let viewport = egui::Rect::from_min_max(to_map(visible.min), to_map(visible.max));
+        painter.rect_stroke(viewport, 0.0, (1.5, egui::Color32::WHITE), egui::StrokeKind::Middle);
+
+        let response = ui
+            .interact(map, ui.id().with(("minimap", index)), egui::Sense::click_and_drag())
+            .on_hover_text("Click or drag to move the view");
+        if let Some(pos) = response.interact_pointer_pos()
+            && (response.clicked() || response.dragged())
+        {
+            let fraction = ((pos - map.min) / map.size()).clamp(egui::Vec2::ZERO, egui::vec2(1.0, 1.0));
+            self.camera.look_at(fraction, self.frame_rect, canvas);
+        }
+    }
+
+    /// Draws the sim's `annotate()` shapes over a raster view.
+    fn annotations_ui(&mut self, painter: &egui::Painter, canvas: egui::Rect) {
+        if self.render_mode() != RenderMode::Raster {
+            return;
+        }
+        self.annotations.clear();
+        self.sim.annotate(&mut self.annotations);
+        self.annotations.paint(painter, &self.viewport(canvas));
+    }
+
+    /// The color bar for a raster view the sim reports a `color_scale()` for.
+    fn color_bar_ui(&self, painter: &egui::Painter, canvas: egui::Rect) {
+        if self.render_mode() == RenderMode::Raster
+            && let Some(scale) = self.sim.color_scale()
+        {
+            scale.paint(painter, canvas);
+        }
+    }
+
+    /// Shows the coordinates and `probe()` description of the cell under the pointer in
+    /// the canvas's bottom-left corner. Only raster views have cells to inspect.
+    fn probe_ui(&self, painter: &egui::Painter, response: &egui::Response) {
+        if self.render_mode() != RenderMode::Raster {
+            return;
+        }
+        let viewport = self.viewport(response.rect);
+        let Some((x, y)) = response.hover_pos().and_then(|pos| viewport.cell_at(pos)) else {
+            return;
+        };
+        let text = match self.sim.probe(x, y) {
+            Some(value) => format!("({x}, {y})  {value}"),
+            None => format!("({x}, {y})"),
+        };
+        let font = egui::FontId::monospace(12.0);
+        let galley = painter.layout_no_wrap(text, font, egui::Color32::WHITE);
+        let pos = response.rect.left_bottom() + egui::vec2(6.0, -6.0 - galley.size().y);
+        let backdrop = egui::Rect::from_min_size(pos, galley.size()).expand(3.0);
+        painter.rect_filled(backdrop, 3.0, egui::Color32::from_black_alpha(180));
+        painter.galley(pos, galley, egui::Color32::WHITE);
+    }
+
+    /// The sim's `focus()` in fractions of its world, as the camera and minimap take it.
+    fn focus(&self) -> Option<egui::Rect> {
+        let focus = self.sim.focus()?;
+        let (w, h) = self.sim.dimensions();
+        let (w, h) = (w.max(1) as f32, h.max(1) as f32);
+        Some(egui::Rect::from_min_max(
+            egui::pos2(focus.x as f32 / w, focus.y as f32 / h),
+            egui::pos2((focus.x + focus.width) as f32 / w, (focus.y + focus.height) as f32 / h),
+        ))
+    }
+
+    /// How the sim's world is shown in `canvas`.
+    fn viewport(&self, canvas: egui::Rect) -> Viewport {
+        Viewport { camera: self.camera, frame: self.frame_rect, canvas, dims: self.sim.dimensions() }
+    }
+
+    /// The cells of a culling sim's world that show in `canvas`, or `None` when it's all
+    /// on screen (or the sim always renders everything).
+    fn visible_region(&self, canvas: egui::Rect) -> Option<Region> {
+        if !self.sim.capabilities().contains(Capabilities::CULLING) {
+            return None;
+        }
+        let viewport = self.viewport(canvas);
+        if canvas.contains_rect(viewport.world_rect()) {
+            return None;
+        }
+        let (dims, visible) = (viewport.dims, viewport.visible());
+        let (min, max) = (visible.min, visible.max);
+        let clamp = |v: f32, len: usize| (v.max(0.0) as usize).min(len);
+        let (x0, y0) = (clamp(min.x.floor(), dims.0), clamp(min.y.floor(), dims.1));
+        let (x1, y1) = (clamp(max.x.ceil(), dims.0), clamp(max.y.ceil(), dims.1));
+        Some(Region { x: x0, y: y0, width: x1 - x0, height: y1 - y0 })
+    }
+
+    /// Draws the sim onto `painter`, filling `canvas` with `background` first. Raster
+    /// frames are kept in the history while `record_cap` gives it a byte budget, and the
+    /// history's frame is shown instead of the sim while replaying. Culling sims only draw
+    /// the cells in view, leaving the rest of the frame blank.
+    fn paint(
+        &mut self,
+        ctx: &egui::Context,
+        painter: &egui::Painter,
+        canvas: egui::Rect,
+        background: egui::Color32,
+        record_cap: Option<usize>,
+        timings: &mut FrameTimings,
+    ) {
+        let (w, h) = self.sim.dimensions();
+        let world_rect = self.camera.world_rect(self.frame_rect).round_to_pixels(ctx.pixels_per_point());
+        painter.rect_filled(canvas, 0.0, background);
+
+        let start = Instant::now();
+        if self.render_mode() == RenderMode::Vector {
+            self.frame = None;
+            self.sim.render_world(painter, &self.viewport(canvas));
+            timings.render += start.elapsed();
+            return;
+        }
+
+        let region = self.visible_region(canvas);
+        let key = self.sim.version().map(|version| RenderKey {
+            version,
+            view: self.view_index,
+            background,
+            filter: self.texture_filter(),
+            effects: self.effects,
+            region,
+            palette_shift: self.palette_shift,
+        });
+        let image = match self.history.shown() {
+            Some(frame) => {
+                self.rendered = None;
+                Some(frame.image.clone())
+            }
+            // The texture already holds this frame
+            None if key.is_some() && key == self.rendered && self.texture.is_some() => None,
+            None => {
+                // Let go of the last frame so its pixels can be written over
+                self.frame = None;
+                let mut image = self.pool.image([w, h]);
+                let format = self.sim.config().pixel_format;
+                let sim = &self.sim;
+                let render = |buffer: &mut [u8]| match region {
+                    Some(region) => sim.render_region(region, buffer),
+                    None => sim.render(buffer),
+                };
+                if format == PixelFormat::Rgba {
+                    // RGBA bytes are laid out like the image's pixels, so the sim draws
+                    // straight into them and only the translucent ones need touching after
+                    let raw = image.as_raw_mut();
+                    raw.fill(0);
+                    render(raw);
+                    timings.render += start.elapsed();
+                    pool::blend_over(&mut image, background);
+                } else {
+                    let pixel_buffer = self.pool.pixels(w * h * format.channels());
+                    render(pixel_buffer);
+                    timings.render += start.elapsed();
+                    match format {
+                        PixelFormat::Indexed => {
+                            let lut = self.palette.lut(self.palette_shift);
+                            pool::copy_indexed(&mut image, pixel_buffer, &lut);
+                        }
+                        _ => pool::copy_rgb(&mut image, pixel_buffer),
+                    }
+                }
+                self.sync_layers();
+                for (index, layer) in self.layers.iter().enumerate() {
+                    if layer.visible && layer.opacity > 0.0 {
+                        let layer_buffer = self.pool.layer(w * h * 4);
+                        self.sim.render_layer(index, layer_buffer);
+                        composite_layer(&mut image, layer_buffer, layer.opacity);
+                    }
+                }
+                let image = Arc::new(image);
+                self.pool.recycle(image.clone());
+                if let Some(cap) = record_cap {
+                    for evicted in self.history.record(self.stats.stats.steps, image.clone(), cap) {
+                        self.pool.recycle(evicted);
+                    }
+                }
+                self.rendered = key;
+                Some(image)
+            }
+        };
+
+        if let Some(image) = image {
+            self.frame = Some(image.clone());
+            let start = Instant::now();
+            let image = match self.sim.config().upscale {
+                0 | 1 => image,
+                factor => Arc::new(upscale(&image, factor)),
+            };
+            let image = if self.effects.is_none() {
+                self.post.clear();
+                image
+            } else {
+                Arc::new(self.post.apply(&self.effects, Arc::unwrap_or_clone(image)))
+            };
+            let options = self.texture_filter().options();
+            // Updating the existing texture keeps its id; egui only reallocates it on a resize
+            match &mut self.texture {
+                Some(texture) => texture.set(image, options),
+                None => self.texture = Some(ctx.load_texture("display", image, options)),
+            }
+            timings.upload += start.elapsed();
+        }
+        if let Some(texture) = &self.texture {
+            let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+            painter.image(texture.id(), world_rect, uv, egui::Color32::WHITE);
+        }
+    }
+
+    /// Matches `layers` up with the sim's `layers()`, keeping the settings of layers
+    /// that are still there.
+    fn sync_layers(&mut self) {
+        let infos = self.sim.layers();
+        self.layers.truncate(infos.len());
+        for info in &infos[self.layers.len()..] {
+            self.layers.push(LayerState { visible: info.visible, opacity: 1.0 });
+        }
+    }
+
+    /// Makes the next `paint` render the sim even if its `version()` hasn't moved, for
+    /// changes the sim can't see coming, like parameter edits and theme switches.
+    fn invalidate(&mut self) {
+        self.rendered = None;
+    }
+}
+
+/// `image` with every pixel repeated into a `factor` × `factor` block.
+fn upscale(image: &egui::ColorImage, factor: usize) -> egui::ColorImage {
+    let [w, h] = image.size;
+    let mut pixels = Vec::with_capacity(w * h * factor * factor);
+    for row in image.pixels.chunks_exact(w.max(1)) {
+        let wide: Vec<egui::Color32> =
+            row.iter().flat_map(|&pixel| std::iter::repeat_n(pixel, factor)).collect();
+        for _ in 0..factor {
+            pixels.extend_from_slice(&wide);
+        }
+    }
+    egui::ColorImage::new([w * factor, h * factor], pixels)
+}
+
+fn default_speed(config: &SimConfig) -> f32 {
+    config.default_speed.clamp(config.min_speed, config.max_speed)
+}
+
+/// A workspace tab: sims compared side by side, with their own clock and pause state.
+struct Tab {
+    /// Sims shown side by side and stepped in lockstep; never empty.
+    slots: Vec<SimSlot>,
+    /// The slot the side panel's sim-specific controls act on.
+    focus: usize,
+    is_paused: bool,
+    updates_per_second: f32,
+    time_scale: f32,
+    turbo: bool,
+    time_accumulator: f32,
+    /// Set while playing back recorded frames instead of showing the live sims.
+    replay: Option<Replay>,
+}
+
+/// Playback position in the focused slot's frame history.
+struct Replay {
+    /// Fractional frame index, so slow playback still moves.
+    position: f64,
+    /// Frames per second to play at.
+    speed: f32,
+    /// 1 plays forwards, -1 backwards and 0 holds the current frame.
+    direction: f32,
+}
+
+impl Tab {
+    /// Most continuous steps one frame may run; owed steps past it are dropped.
+    const MAX_STEPS_PER_FRAME: usize = 5000;
+
+    fn new(sim: Box<dyn Simulation>) -> Self {
+        Self {
+            updates_per_second: default_speed(&sim.config()),
+            slots: vec![SimSlot::new(sim)],
+            focus: 0,
+            is_paused: false,
+            time_scale: 1.0,
+            turbo: false,
+            time_accumulator: 0.0,
+            replay: None,
+        }
+    }
+
+    fn focused(&self) -> &SimSlot {
+        &self.slots[self.focus]
+    }
+
+    fn title(&self) -> String {
+        let names: Vec<&str> = self.slots.iter().map(|slot| slot.sim.name()).collect();
+        names.join(" vs ")
+    }
+
+    fn reset(&mut self, seed: u64) {
+        for slot in &mut self.slots {
+            slot.reset(seed);
+        }
+        self.replay = None;
+    }
+
+    fn running(&self) -> bool {
+        !self.is_paused && self.slots.iter().any(|slot| slot.pace().is_some())
+    }
+
+    /// Runs `f` on every slot that hasn't failed, pausing the tab if any of them does.
+    fn lockstep(&mut self, mut f: impl FnMut(&mut SimSlot) -> Result<(), SimError>) {
+        for slot in self.slots.iter_mut().filter(|slot| slot.error.is_none()) {
+            if let Err(e) = f(slot) {
+                slot.fail(e);
+                self.is_paused = true;
+            }
+        }
+    }
+
+    /// Advances every running slot by one frame of `dt` seconds, with sim time passing at
+    /// `rate` times the requested speed. Turbo only applies at full rate. Returns how many
+    /// due steps were dropped because they exceeded the per-frame cap.
+    fn advance(&mut self, dt: f32, rate: f32) -> usize {
+        if self.replay.is_some() {
+            self.stop_replay();
+        }
+        let scaled = dt * rate * self.time_scale;
+        self.lockstep(|slot| match slot.pace() {
+            Some(StepMode::Realtime) => {
+                slot.apply_playback();
+                slot.sim.update_dt(scaled)?;
+                slot.stats.record(1, dt as f64);
+                Ok(())
+            }
+            _ => Ok(()),
+        });
+
+        // Turbo's batches would run straight past a replay's actions
+        let replaying = self.slots.iter().any(|slot| slot.playback.is_some());
+        if self.turbo && rate >= 1.0 && !replaying {
+            self.run_turbo(dt);
+            self.record_checkpoints();
+            return 0;
+        }
+
+        self.time_accumulator += dt * rate;
+
+        // How long ONE step should take, e.g. 10 Hz = 0.1s per step
+        let step_duration = 1.0 / self.updates_per_second;
+
+        // "Spend" the accumulated time to run updates.
+        // If speed is 1000Hz, this runs ~16 steps per 60Hz frame.
+        // If speed is 1Hz, this runs one step every 60 frames.
+        // Anything past the cap is dropped rather than carried over, so a sim that can't
+        // keep up doesn't build an ever-growing backlog.
+        let owed = (self.time_accumulator / step_duration) as usize;
+        let due = owed.min(Self::MAX_STEPS_PER_FRAME);
+        self.time_accumulator -= owed as f32 * step_duration;
+        self.lockstep(|slot| match slot.pace() {
+            Some(StepMode::Continuous) => slot.run_steps(due, dt),
+            _ => Ok(()),
+        });
+        if !self.slots.iter().any(|slot| slot.pace() == Some(StepMode::Continuous)) {
+            self.time_accumulator = 0.0;
+        }
+        self.record_checkpoints();
+        owed - due
+    }
+
+    /// Records parameter and speed changes into the slots' session recordings.
+    fn observe_sessions(&mut self) {
+        for slot in self.slots.iter_mut().filter(|slot| slot.session.is_some()) {
+            let params = slot.sim.params().map(|params| params.to_json());
+            let steps = slot.stats.stats.steps;
+            if let Some(session) = &mut slot.session {
+                session.observe(steps, params, self.updates_per_second);
+            }
+        }
+    }
+
+    /// Applies speed changes from replays, and ends replays that reached the end of their
+    /// recording, pausing the tab. Returns whether one ended.
+    fn update_playback(&mut self) -> bool {
+        let mut ended = false;
+        for slot in &mut self.slots {
+            if let Some(speed) = slot.replayed_speed.take() {
+                self.updates_per_second = speed;
+            }
+            let steps = slot.stats.stats.steps;
+            if slot.playback.as_ref().is_some_and(|playback| playback.is_finished(steps)) {
+                slot.playback = None;
+                ended = true;
+            }
+        }
+        self.is_paused |= ended;
+        ended
+    }
+
+    fn record_checkpoints(&mut self) {
+        for slot in &mut self.slots {
+            slot.record_checkpoint();
+        }
+    }
+
+    /// Rewinds every slot to its checkpoint nearest the focused slot's checkpoint `index`,
+    /// keeping side-by-side sims in step, and pauses so the state can be inspected.
+    fn rewind_to(&mut self, index: usize) {
+        let Some(checkpoint) = self.focused().rewind.get(index) else { return };
+        let target = checkpoint.stats.wall_time;
+        for slot in &mut self.slots {
+            if let Some(nearest) = slot.rewind.nearest(target) {
+                slot.rewind_to(nearest);
+            }
+        }
+        self.is_paused = true;
+    }
+
+    /// Shows recorded frame `index` of the focused slot, and the frames nearest to it in
+    /// the other slots. Pauses the tab; resuming goes back to the live sims.
+    fn seek_replay(&mut self, index: usize) {
+        let replay = self.replay.get_or_insert(Replay { position: 0.0, speed: 30.0, direction: 0.0 });
+        replay.position = index as f64;
+        self.is_paused = true;
+        self.sync_replay();
+    }
+
+    /// Moves the replay along by `dt` seconds of playback, stopping at either end.
+    fn play_replay(&mut self, dt: f32) {
+        let last = self.focused().history.len().saturating_sub(1) as f64;
+        let Some(replay) = &mut self.replay else { return };
+        replay.position += (replay.direction * replay.speed * dt) as f64;
+        if replay.position <= 0.0 || replay.position >= last {
+            replay.position = replay.position.clamp(0.0, last);
+            replay.direction = 0.0;
+        }
+        self.sync_replay();
+    }
+
+    fn sync_replay(&mut self) {
+        let focus = self.focus;
+        let Some(replay) = &self.replay else { return };
+        let index = replay.position.round() as usize;
+        let Some(steps) = self.slots[focus].history.get(index).map(|frame| frame.steps) else {
+            self.stop_replay();
+            return;
+        };
+        for (i, slot) in self.slots.iter_mut().enumerate() {
+            slot.history.position = if i == focus { Some(index) } else { slot.history.nearest(steps) };
+        }
+    }
+
+    fn stop_replay(&mut self) {
+        self.replay = None;
+        for slot in &mut self.slots {
+            slot.history.position = None;
+        }
+    }
+
+    /// Steps as many times as fit in a fixed slice of each frame, sizing batches from the
+    /// measured cost per step so a slow sim can't blow far past the budget. All continuous
+    /// slots get the same batches, so they stay in lockstep.
+    fn run_turbo(&mut self, dt: f32) {
+        const BUDGET: Duration = Duration::from_millis(12);
+        /// How long to wait for async workers when none of them had a state ready.
+        #[cfg(not(target_arch = "wasm32"))]
+        const IDLE: Duration = Duration::from_micros(500);
+        let start = Instant::now();
+        let mut done = vec![0u64; self.slots.len()];
+        let mut issued = 0;
+        let mut batch = 1;
+        while start.elapsed() < BUDGET && !self.is_paused {
+            let (mut stepped, mut advanced) = (false, 0);
+            for (slot, done) in self.slots.iter_mut().zip(&mut done) {
+                if slot.pace() != Some(StepMode::Continuous) {
+                    continue;
+                }
+                stepped = true;
+                match slot.sim.update_n(batch) {
+                    Ok(steps) => {
+                        *done += steps as u64;
+                        advanced += steps;
+                    }
+                    Err(e) => {
+                        slot.fail(e);
+                        self.is_paused = true;
+                    }
+                }
+            }
+            if !stepped {
+                break;
+            }
+            // Async sims take nothing while their workers are busy; rather than spin on
+            // empty channels, give the workers a moment, or the frame back on the web
+            if advanced == 0 && !self.is_paused {
+                #[cfg(not(target_arch = "wasm32"))]
+                std::thread::sleep(IDLE);
+                #[cfg(target_arch = "wasm32")]
+                break;
+            }
+            issued += batch;
+            let per_step = start.elapsed().as_secs_f64() / issued as f64;
+            let remaining = BUDGET.saturating_sub(start.elapsed()).as_secs_f64();
+            batch = ((remaining / per_step) as usize).clamp(1, 1 << 20);
+        }
+        for (slot, done) in self.slots.iter_mut().zip(done) {
+            slot.stats.record(done, dt as f64);
+        }
+    }
+}
+
+/// What the App remembers between sessions through eframe's storage.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct AppSettings {
+    last_sim: Option<String>,
+    is_paused: bool,
+    turbo: bool,
+    time_scale: f32,
+    background_rate: f32,
+    show_hud: bool,
+    canvas_fit: CanvasFit,
+    show_minimap: bool,
+    follow: bool,
+    export_scale: usize,
+    record_frames: bool,
+    history_cap_mb: usize,
+    seed: u64,
+    theme: Theme,
+    /// Arrangement of the dockable panels; `None` means the default layout.
+    layout: Option<DockState<Panel>>,
+    /// Parameter values per sim name, as JSON.
+    sim_params: BTreeMap<String, String>,
+    /// Last speed chosen per sim name.
+    sim_speeds: BTreeMap<String, f32>,
+    sim_effects: BTreeMap<String, Effects>,
+    sim_palettes: BTreeMap<String, Palette>,
+    worker_settings: WorkerSettings,
+    sim_workers: BTreeMap<String, WorkerSettings>,
+    /// The tabs that were open, restored on startup.
+    open: Option<Workspace>,
+    workspaces: BTreeMap<String, Workspace>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            last_sim: None,
+            is_paused: false,
+            turbo: false,
+            time_scale: 1.0,
+            background_rate: 0.0,
+            show_hud: false,
+            canvas_fit: CanvasFit::default(),
+            show_minimap: true,
+            follow: true,
+            export_scale: 1,
+            record_frames: true,
+            history_cap_mb: 256,
+            seed: rand::random::<u32>() as u64,
+            theme: Theme::DARK,
+            layout: None,
+            sim_params: BTreeMap::new(),
+            sim_speeds: BTreeMap::new(),
+            sim_effects: BTreeMap::new(),
+            sim_palettes: BTreeMap::new(),
+            worker_settings: WorkerSettings::default(),
+            sim_workers: BTreeMap::new(),
+            open: None,
+            workspaces: BTreeMap::new(),
+        }
+    }
+}
+
+pub struct App {
+    registry: SimRegistry,
+    browser: SimBrowser,
+    palette: CommandPalette,
+    console: LogConsole,
+    /// Never empty.
+    tabs: Vec<Tab>,
+    active: usize,
+    /// Fraction of their speed that tabs other than the active one keep running at;
+    /// zero leaves them paused.
+    background_rate: f32,
+    step_count: usize,
+    hud: PerfHud,
+    show_hud: bool,
+    show_help: bool,
+    canvas_fit: CanvasFit,
+    show_minimap: bool,
+    /// Whether the camera keeps a sim's `focus()` in view.
+    follow: bool,
+    /// Multiple of the sim's resolution that saved frames are rendered at.
+    export_scale: usize,
+    /// Width and height typed into the resolution editor.
+    custom_size: (usize, usize),
+    /// Timings for the frame being built; handed to the HUD at the end of `update`.
+    timings: FrameTimings,
+    /// Whether rendered frames are kept for the history scrubber.
+    record_frames: bool,
+    /// Memory each slot's frame history may take up.
+    history_cap_mb: usize,
+    dock: DockState<Panel>,
+    /// Where the canvas panel was last drawn, for overlays.
+    canvas_rect: egui::Rect,
+    /// Canvas-only mode: panels and toolbar hidden, window fullscreen.
+    presentation: bool,
+    theme: Theme,
+    #[cfg(not(target_arch = "wasm32"))]
+    gif: export::GifRecorder,
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    video: VideoRecorder,
+    status: Option<String>,
+    seed: u64,
+    presets: PresetStore,
+    selected_preset: Option<String>,
+    new_preset_name: String,
+    sim_params: BTreeMap<String, String>,
+    sim_speeds: BTreeMap<String, f32>,
+    /// Post-processing the user chose, by sim name.
+    sim_effects: BTreeMap<String, Effects>,
+    /// Palettes the user chose for indexed sims, by sim name.
+    sim_palettes: BTreeMap<String, Palette>,
+    /// Threads and priority for worker-backed sims without their own.
+    worker_settings: WorkerSettings,
+    /// Worker settings the user gave individual sims, by sim name.
+    sim_workers: BTreeMap<String, WorkerSettings>,
+    workspaces: BTreeMap<String, Workspace>,
+    /// The workspace last opened or saved, shown in the menu.
+    workspace: Option<String>,
+    new_workspace_name: String,
+}
+
+impl App {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self::with_registry(cc, SimRegistry::default())
+    }
+
+    pub fn with_registry(cc: &eframe::CreationContext<'_>, registry: SimRegistry) -> Self {
+        #[cfg(feature = "gpu")]
+        if let Some(state) = &cc.wgpu_render_state {
+            crate::gpu::set_render_state(state);
+        }
+        let settings: AppSettings = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, eframe::APP_KEY))
+            .unwrap_or_default();
+
+        let mut app = Self {
+            registry,
+            browser: SimBrowser::default(),
+            palette: CommandPalette::default(),
+            console: LogConsole::default(),
+            tabs: vec![Tab::new(Box::new(NoSim))],
+            active: 0,
+            background_rate: settings.background_rate,
+            step_count: 10,
+            hud: PerfHud::default(),
+            show_hud: settings.show_hud,
+            show_help: false,
+            canvas_fit: settings.canvas_fit,
+            show_minimap: settings.show_minimap,
+            follow: settings.follow,
+            export_scale: settings.export_scale,
+            custom_size: (400, 300),
+            timings: FrameTimings::default(),
+            record_frames: settings.record_frames,
+            history_cap_mb: settings.history_cap_mb,
+            dock: settings.layout.unwrap_or_else(dock::default_layout),
+            canvas_rect: egui::Rect::NOTHING,
+            presentation: false,
+            theme: settings.theme,
+            #[cfg(not(target_arch = "wasm32"))]
+            gif: export::GifRecorder::default(),
+            #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+            video: VideoRecorder::default(),
+            status: None,
+            seed: settings.seed,
+            presets: PresetStore::load(),
+            selected_preset: None,
+            new_preset_name: String::new(),
+            sim_params: settings.sim_params,
+            sim_speeds: settings.sim_speeds,
+            sim_effects: settings.sim_effects,
+            sim_palettes: settings.sim_palettes,
+            worker_settings: settings.worker_settings,
+            sim_workers: settings.sim_workers,
+            workspaces: settings.workspaces,
+            workspace: None,
+            new_workspace_name: String::new(),
+        };
+        app.theme.apply(&cc.egui_ctx);
+
+        if let Some(open) = &settings.open {
+            app.open_workspace(&cc.egui_ctx, open);
+            return app;
+        }
+
+        if let Some(entry) = settings.last_sim.as_deref().and_then(|name| app.registry.get(name)) {
+            let sim = entry.create();
+            app.load_sim(&cc.egui_ctx, sim);
+        }
+        let tab = app.tab_mut();
+        tab.is_paused = settings.is_paused;
+        tab.turbo = settings.turbo;
+        tab.time_scale = settings.time_scale;
+        app
+    }
+
+    fn tab(&self) -> &Tab {
+        &self.tabs[self.active]
+    }
+
+    fn tab_mut(&mut self) -> &mut Tab {
+        &mut self.tabs[self.active]
+    }
+
+    fn focused(&self) -> &SimSlot {
+        self.tab().focused()
+    }
+
+    /// Stashes a sim's parameters so they survive switching sims and restarts.
+    fn remember_params(&mut self, tab: usize, index: usize) {
+        let sim = &mut self.tabs[tab].slots[index].sim;
+        let name = sim.name().to_owned();
+        if let Some(params) = sim.params() {
+            self.sim_params.insert(name, params.to_json().to_string());
+        }
+    }
+
+    /// Stashes the tab's speed under its focused sim, if that sim runs at a set speed.
+    fn remember_speed(&mut self, tab: usize) {
+        let tab = &self.tabs[tab];
+        let sim = &tab.focused().sim;
+        if sim.config().step_mode == StepMode::Continuous {
+            self.sim_speeds.insert(sim.name().to_owned(), tab.updates_per_second);
+        }
+    }
+
+    /// Replaces the focused sim of the active tab, at the speed it last ran at.
+    fn load_sim(&mut self, ctx: &egui::Context, mut sim: Box<dyn Simulation>) {
+        let focus = self.tab().focus;
+        self.remember_params(self.active, focus);
+        self.remember_speed(self.active);
+        self.tab_mut().slots[focus].sim.on_unload();
+        if let Some(json) = self.sim_params.get(sim.name())
+            && let Ok(values) = serde_json::from_str(json)
+            && let Some(params) = sim.params()
+        {
+            // Stale values from an older params layout just fall back to defaults
+            let _ = params.set_json(values);
+        }
+        sim.reset(self.seed);
+        sim.on_load(ctx);
+        let config = sim.config();
+        let speed = self.sim_speeds.get(sim.name()).copied().unwrap_or(default_speed(&config));
+        let tab = self.tab_mut();
+        tab.updates_per_second = speed.clamp(config.min_speed, config.max_speed);
+        tab.slots[focus] = SimSlot::new(sim);
+        self.hud.reset_dropped();
+        self.status = None;
+        self.selected_preset = None;
+    }
+
+    /// Opens a fresh instance of the focused sim, with the same parameters, in a new slot
+    /// alongside it and focuses the new slot.
+    fn add_comparison(&mut self, ctx: &egui::Context) {
+        let Some(entry) = self.registry.get(self.focused().sim.name()) else { return };
+        let mut sim = entry.create();
+        let seed = self.seed;
+        let tab = &mut self.tabs[self.active];
+        if let Some(values) = tab.slots[tab.focus].sim.params().map(|params| params.to_json())
+            && let Some(params) = sim.params()
+        {
+            let _ = params.set_json(values);
+        }
+        sim.reset(seed);
+        sim.on_load(ctx);
+        tab.slots.push(SimSlot::new(sim));
+        tab.focus = tab.slots.len() - 1;
+        self.selected_preset = None;
+    }
+
+    fn close_slot(&mut self, index: usize) {
+        if self.tab().slots.len() == 1 {
+            return;
+        }
+        self.remember_params(self.active, index);
+        let tab = self.tab_mut();
+        tab.slots.remove(index).sim.on_unload();
+        if tab.focus >= index && tab.focus > 0 {
+            tab.focus -= 1;
+        }
+        self.selected_preset = None;
+    }
+
+    fn palette_commands(&self) -> Vec<(String, Command)> {
+        let tab = self.tab();
+        let mut commands = vec![
+            (if tab.is_paused { "Resume" } else { "Pause" }.to_owned(), Command::TogglePause),
+            ("Reset".to_owned(), Command::Reset),
+            ("Step".to_owned(), Command::Step),
+            (format!("Turbo {}", if tab.turbo { "off" } else { "on" }), Command::ToggleTurbo),
+            ("Compare side by side".to_owned(), Command::Compare),
+            ("New tab".to_owned(), Command::NewTab),
+            ("Close tab".to_owned(), Command::CloseTab),
+            ("Toggle presentation mode".to_owned(), Command::TogglePresentation),
+            ("Toggle performance overlay".to_owned(), Command::ToggleHud),
+            ("Reset panel layout".to_owned(), Command::ResetLayout),
+            ("Show help".to_owned(), Command::ShowHelp),
+        ];
+        let snapshots = self.focused().sim.capabilities().contains(Capabilities::SNAPSHOTS);
+        if cfg!(not(target_arch = "wasm32")) && snapshots {
+            commands.push(("Save snapshot".to_owned(), Command::SaveSnapshot));
+            commands.push(("Load snapshot".to_owned(), Command::LoadSnapshot));
+        }
+        let exportable = self.focused().sim.capabilities().contains(Capabilities::EXPORT);
+        if cfg!(not(target_arch = "wasm32")) && exportable {
+            let label = match self.focused().render_mode() {
+                RenderMode::Raster => "Save frame as PNG",
+                RenderMode::Vector => "Save view as SVG",
+            };
+            commands.push((label.to_owned(), Command::SaveFrame));
+        }
+        for entry in self.registry.entries() {
+            commands.push((format!("Load {}", entry.name), Command::LoadSim(entry.name.clone())));
+        }
+        commands
+    }
+
+    fn run_command(&mut self, ctx: &egui::Context, command: Command) {
+        let seed = self.seed;
+        let tab = self.tab_mut();
+        match command {
+            Command::LoadSim(name) => {
+                if let Some(sim) = self.registry.get(&name).map(SimEntry::create) {
+                    self.load_sim(ctx, sim);
+                }
+            }
+            Command::TogglePause => tab.is_paused = !tab.is_paused,
+            Command::Reset => {
+                tab.reset(seed);
+                self.hud.reset_dropped();
+            }
+            Command::Step => {
+                let time_scale = tab.time_scale;
+                tab.lockstep(|slot| slot.step_manually(1, time_scale));
+            }
+            Command::SetSpeed(speed) => {
+                let config = tab.focused().sim.config();
+                tab.updates_per_second = speed.clamp(config.min_speed, config.max_speed);
+                tab.turbo = false;
+            }
+            Command::ToggleTurbo => tab.turbo = !tab.turbo,
+            Command::Compare => self.add_comparison(ctx),
+            Command::NewTab => {
+                self.tabs.push(Tab::new(Box::new(NoSim)));
+                self.select_tab(self.tabs.len() - 1);
+            }
+            Command::CloseTab => self.close_tab(self.active),
+            Command::TogglePresentation => self.set_presentation(ctx, !self.presentation),
+            Command::ToggleHud => self.show_hud = !self.show_hud,
+            Command::ResetLayout => self.dock = dock::default_layout(),
+            Command::ShowHelp => self.show_help = true,
+            #[cfg(not(target_arch = "wasm32"))]
+            Command::SaveSnapshot => self.save_snapshot(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Command::LoadSnapshot => self.load_snapshot(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Command::SaveFrame => self.save_frame(ctx),
+            #[cfg(target_arch = "wasm32")]
+            Command::SaveSnapshot | Command::LoadSnapshot | Command::SaveFrame => {}
+        }
+    }
+
+    fn select_tab(&mut self, index: usize) {
+        self.active = index;
+        self.status = None;
+        self.selected_preset = None;
+    }
+
+    fn close_tab(&mut self, index: usize) {
+        if self.tabs.len() == 1 {
+            return;
+        }
+        for slot in 0..self.tabs[index].slots.len() {
+            self.remember_params(index, slot);
+        }
+        self.remember_speed(index);
+        for slot in &mut self.tabs.remove(index).slots {
+            slot.sim.on_unload();
+        }
+        if self.active >= index && self.active > 0 {
+            self.select_tab(self.active - 1);
+        }
+    }
+
+    fn tabs_ui(&mut self, ui: &mut egui::Ui) {
+        let mut selected = None;
+        let mut closed = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.menu_button("Workspaces", |ui| self.workspaces_ui(ui));
+            ui.menu_button("Panels", |ui| {
+                for panel in Panel::ALL.into_iter().filter(|panel| panel.closeable()) {
+                    let mut shown = self.dock.find_tab(&panel).is_some();
+                    if ui.checkbox(&mut shown, panel.title()).changed() {
+                        dock::toggle(&mut self.dock, panel);
+                    }
+                }
+                ui.separator();
+                if ui.button("Reset layout").clicked() {
+                    self.dock = dock::default_layout();
+                }
+            });
+            ui.separator();
+            for (index, tab) in self.tabs.iter().enumerate() {
+                let mut title = tab.title();
+                if index != self.active && tab.running() && self.background_rate > 0.0 {
+                    title.insert_str(0, "▶ ");
+                }
+                if ui.selectable_label(index == self.active, title).clicked() {
+                    selected = Some(index);
+                }
+                if self.tabs.len() > 1 && ui.small_button("✖").on_hover_text("Close tab").clicked() {
+                    closed = Some(index);
+                }
+                ui.separator();
+            }
+            if ui.button("➕").on_hover_text("New tab").clicked() {
+                self.tabs.push(Tab::new(Box::new(NoSim)));
+                selected = Some(self.tabs.len() - 1);
+            }
+        });
+        if let Some(index) = selected {
+            self.select_tab(index);
+        }
+        if let Some(index) = closed {
+            self.close_tab(index);
+        }
+    }
+
+    /// The open tabs and panel layout as a workspace.
+    fn capture_workspace(&mut self) -> Workspace {
+        let tabs = self
+            .tabs
+            .iter_mut()
+            .map(|tab| TabState {
+                slots: tab
+                    .slots
+                    .iter_mut()
+                    .filter(|slot| self.registry.get(slot.sim.name()).is_some())
+                    .map(|slot| SlotState {
+                        sim: slot.sim.name().to_owned(),
+                        params: slot.sim.params().map(|params| params.to_json()),
+                        view: slot.view_index,
+                        size: slot
+                            .sim
+                            .capabilities()
+                            .contains(Capabilities::RESIZE)
+                            .then(|| slot.sim.dimensions()),
+                    })
+                    .collect(),
+                focus: tab.focus,
+                speed: tab.updates_per_second,
+                time_scale: tab.time_scale,
+                turbo: tab.turbo,
+                paused: tab.is_paused,
+            })
+            .collect();
+        Workspace { tabs, active: self.active, layout: self.dock.clone() }
+    }
+
+    /// Replaces every open tab with fresh instances of the workspace's sims. Sims that are
+    /// no longer registered are left out.
+    fn open_workspace(&mut self, ctx: &egui::Context, workspace: &Workspace) {
+        for tab in 0..self.tabs.len() {
+            for slot in 0..self.tabs[tab].slots.len() {
+                self.remember_params(tab, slot);
+            }
+            self.remember_speed(tab);
+        }
+        for slot in self.tabs.iter_mut().flat_map(|tab| &mut tab.slots) {
+            slot.sim.on_unload();
+        }
+
+        self.tabs = workspace
+            .tabs
+            .iter()
+            .map(|state| {
+                let mut slots: Vec<SimSlot> = state
+                    .slots
+                    .iter()
+                    .filter_map(|slot| {
+                        let mut sim = self.registry.get(&slot.sim)?.create();
+                        if let Some(values) = slot.params.clone()
+                            && let Some(params) = sim.params()
+                        {
+                            let _ = params.set_json(values);
+                        }
+                        if let Some((w, h)) = slot.size
+                            && sim.capabilities().contains(Capabilities::RESIZE)
+                        {
+                            let _ = sim.resize(w, h);
+                        }
+                        sim.reset(self.seed);
+                        sim.on_load(ctx);
+                        let view = slot.view.min(sim.views().len().saturating_sub(1));
+                        sim.select_view(view);
+                        let mut slot = SimSlot::new(sim);
+                        slot.view_index = view;
+                        Some(slot)
+                    })
+                    .collect();
+                if slots.is_empty() {
+                    slots.push(SimSlot::new(Box::new(NoSim)));
+                }
+                let config = slots[0].sim.config();
+                let mut tab = Tab::new(Box::new(NoSim));
+                tab.focus = state.focus.min(slots.len() - 1);
+                tab.slots = slots;
+                tab.updates_per_second = state.speed.clamp(config.min_speed, config.max_speed);
+                tab.time_scale = state.time_scale;
+                tab.turbo = state.turbo;
+                tab.is_paused = state.paused;
+                tab
+            })
+            .collect();
+        if self.tabs.is_empty() {
+            self.tabs.push(Tab::new(Box::new(NoSim)));
+        }
+        self.dock = workspace.layout.clone();
+        self.select_tab(workspace.active.min(self.tabs.len() - 1));
+        self.hud.reset_dropped();
+    }
+
+    /// One line along the bottom: the active sim, how far its run has got, what the
+    /// rewind and frame history hold, and anything being recorded.
+    fn status_bar_ui(&self, ui: &mut egui::Ui) {
+        let slot = self.focused();
+        let stats = slot.stats.stats;
+        let memory: usize = self
+            .tabs
+            .iter()
+            .flat_map(|tab| &tab.slots)
+            .map(|slot| slot.rewind.bytes() + slot.history.bytes())
+            .sum();
+        let elapsed = stats.wall_time as u64;
+
+        let mut recording = Vec::new();
+        if slot.session.is_some() {
+            recording.push("⏺ session".to_owned());
+        }
+        if slot.playback.is_some() {
+            recording.push("▶ replaying session".to_owned());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.gif.is_recording() {
+            recording.push(format!("⏺ GIF ({} frames)", self.gif.len()));
+        }
+        #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+        if self.video.is_recording() {
+            recording.push(format!("⏺ video ({} frames)", self.video.len()));
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(slot.sim.name());
+            ui.separator();
+            ui.label(format!("{}:{:02}:{:02}", elapsed / 3600, elapsed / 60 % 60, elapsed % 60))
+                .on_hover_text("Run time since the last reset");
+            ui.separator();
+            ui.label(format!("{} steps", stats.steps));
+            ui.separator();
+            ui.label(format!("{:.1} MB history", memory as f64 / (1024.0 * 1024.0)))
+                .on_hover_text("Memory held by rewind checkpoints and recorded frames, across all tabs");
+            if !self.record_frames {
+                ui.weak("(frame recording off)");
+            }
+            if !recording.is_empty() {
+                ui.separator();
+                ui.colored_label(egui::Color32::from_rgb(230, 80, 80), recording.join(", "));
+            }
+            if let Some(status) = &self.status {
+                ui.separator();
+                ui.weak(status);
+            }
+        });
+    }
+
+    /// Lists the saved workspaces to switch to, plus saving the current setup under a name.
+    fn workspaces_ui(&mut self, ui: &mut egui::Ui) {
+        let mut open = None;
+        let mut delete = None;
+        for name in self.workspaces.keys() {
+            ui.horizontal(|ui| {
+                let current = self.workspace.as_ref() == Some(name);
+                if ui.selectable_label(current, name).clicked() {
+                    open = Some(name.clone());
+                }
+                if ui.small_button("🗑").on_hover_text("Delete workspace").clicked() {
+                    delete = Some(name.clone());
+                }
+            });
+        }
+        if self.workspaces.is_empty() {
+            ui.weak("No saved workspaces");
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_workspace_name)
+                    .hint_text("Workspace name")
+                    .desired_width(140.0),
+            );
+            let name = self.new_workspace_name.trim().to_owned();
+            if ui.add_enabled(!name.is_empty(), egui::Button::new("Save")).clicked() {
+                let workspace = self.capture_workspace();
+                self.workspaces.insert(name.clone(), workspace);
+                self.workspace = Some(name);
+                self.new_workspace_name.clear();
+            }
+        });
+
+        if let Some(name) = open
+            && let Some(workspace) = self.workspaces.get(&name).cloned()
+        {
+            self.open_workspace(ui.ctx(), &workspace);
+            self.workspace = Some(name);
+            ui.close();
+        }
+        if let Some(name) = delete {
+            self.workspaces.remove(&name);
+            if self.workspace == Some(name) {
+                self.workspace = None;
+            }
+        }
+    }
+
+    fn params_ui(&mut self, ui: &mut egui::Ui) {
+        let tab = &mut self.tabs[self.active];
+        let sim = &mut tab.slots[tab.focus].sim;
+        let sim_name = sim.name().to_owned();
+        let Some(params) = sim.params() else {
+            ui.weak(format!("{sim_name} has no parameters"));
+            return;
+        };
+
+        let mut changed = params.ui(ui);
+
+        let mut save_presets = false;
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("preset_select")
+                .selected_text(self.selected_preset.as_deref().unwrap_or("Presets"))
+                .show_ui(ui, |ui| {
+                    for name in self.presets.names(&sim_name) {
+                        if ui.selectable_label(false, &name).clicked()
+                            && let Some(values) = self.presets.get(&sim_name, &name)
+                        {
+                            self.status = params.set_json(values.clone()).err();
+                            self.selected_preset = Some(name);
+                            changed = true;
+                        }
+                    }
+                });
+            if let Some(name) = &self.selected_preset
+                && ui.button("Delete").clicked()
+            {
+                self.presets.remove(&sim_name, name);
+                self.selected_preset = None;
+                save_presets = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.new_preset_name).hint_text("Preset name").desired_width(120.0));
+            let name = self.new_preset_name.trim();
+            if ui.add_enabled(!name.is_empty(), egui::Button::new("Save preset")).clicked() {
+                self.presets.insert(&sim_name, name, params.to_json());
+                self.selected_preset = Some(name.to_owned());
+                self.new_preset_name.clear();
+                save_presets = true;
+            }
+        });
+
+        if changed {
+            let tab = &mut self.tabs[self.active];
+            tab.slots[tab.focus].invalidate();
+        }
+        if save_presets && let Err(e) = self.presets.save() {
+            self.status = Some(format!("Saving presets failed: {e}"));
+        }
+    }
+
+    /// Statistics for the focused sim of the active tab.
+    pub fn stats(&self) -> SimStats {
+        self.focused().stats.stats
+    }
+
+    fn stats_ui(&self, ui: &mut egui::Ui) {
+        let stats = self.stats();
+        egui::Grid::new("sim_stats").num_columns(2).show(ui, |ui| {
+            ui.label("Steps:");
+            ui.label(stats.steps.to_string());
+            ui.end_row();
+            ui.label("Run time:");
+            ui.label(format!("{:.1} s", stats.wall_time));
+            ui.end_row();
+            ui.label("Achieved:");
+            ui.label(format!("{:.0} {}", stats.steps_per_sec, self.focused().sim.config().speed_unit));
+            ui.end_row();
+        });
+    }
+
+    /// Asks where to save a snapshot of the focused sim's state and writes it there.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_snapshot(&mut self) {
+        let sim = &self.focused().sim;
+        let Some(data) = sim.save_state() else {
+            self.status = Some(format!("{} does not support snapshots", sim.name()));
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(export::file_name(sim.name(), "snapshot.json"))
+            .add_filter("Snapshot", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        self.status = Some(match std::fs::write(&path, data) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Save failed: {e}"),
+        });
+    }
+
+    /// Asks where to save the focused sim's current frame, rendered at `export_scale` times
+    /// its own resolution rather than as it appears on screen, and writes it as a PNG.
+    /// Vector views are saved as SVG instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_frame(&mut self, ctx: &egui::Context) {
+        let slot = self.focused();
+        let sim = &slot.sim;
+        if slot.render_mode() == RenderMode::Vector {
+            self.save_svg(ctx);
+            return;
+        }
+        let (w, h) = sim.dimensions();
+        let (w, h) = (w * self.export_scale, h * self.export_scale);
+        let mut format = sim.config().pixel_format;
+        let mut buffer = Vec::new();
+        sim.render_at(w, h, &mut buffer);
+        if format == PixelFormat::Indexed {
+            let lut = slot.palette.lut(slot.palette_shift);
+            buffer = buffer.iter().flat_map(|&index| lut[index as usize]).collect();
+            format = PixelFormat::Rgb;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(export::file_name(sim.name(), "png"))
+            .add_filter("PNG image", &["png"])
+            .save_file()
+        else {
+            return;
+        };
+        self.status = Some(match export::save_png(&path, (w, h), format, &buffer) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Save failed: {e}"),
+        });
+    }
+
+    /// Asks where to save the focused sim's vector view and writes what `render_world()`
+    /// paints, at the sim's resolution, as an SVG.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_svg(&mut self, ctx: &egui::Context) {
+        let sim = &self.focused().sim;
+        let (w, h) = sim.dimensions();
+        let size = egui::vec2(w as f32, h as f32);
+        let shapes = svg::capture(ctx, sim.as_ref(), size);
+        if shapes.is_empty() {
+            self.status = Some("This view draws nothing that can be saved as SVG".to_owned());
+            return;
+        }
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(export::file_name(sim.name(), "svg"))
+            .add_filter("SVG image", &["svg"])
+            .save_file()
+        else {
+            return;
+        };
+        let document = svg::document(&shapes, size, self.theme.background);
+        self.status = Some(match std::fs::write(&path, document) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Save failed: {e}"),
+        });
+    }
+
+    /// Record button for GIF clips of the focused pane, with the capture rate and scale.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn gif_ui(&mut self, ui: &mut egui::Ui) {
+        if self.gif.is_encoding() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Encoding GIF…");
+            });
+            return;
+        }
+        if self.gif.is_recording() {
+            let label = format!("⏹ Stop recording ({} frames)", self.gif.len());
+            if ui.button(label).clicked() {
+                let path = rfd::FileDialog::new()
+                    .set_file_name(export::file_name(self.focused().sim.name(), "gif"))
+                    .add_filter("GIF animation", &["gif"])
+                    .save_file();
+                self.gif.stop(path);
+            }
+            return;
+        }
+        if !self.focused().sim.capabilities().contains(Capabilities::EXPORT) {
+            return;
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .button("⏺ Record GIF")
+                .on_hover_text("Capture the focused pane until stopped; vector views aren't captured")
+                .clicked()
+            {
+                self.gif.start();
+            }
+            ui.add(egui::DragValue::new(&mut self.gif.fps).range(1.0..=50.0).suffix(" fps"));
+            ui.add(
+                egui::DragValue::new(&mut self.gif.scale)
+                    .range(0.25..=4.0)
+                    .speed(0.05)
+                    .suffix("×"),
+            )
+            .on_hover_text("Size relative to the sim's resolution");
+        });
+    }
+
+    /// Record and replay buttons for session files.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn session_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        let focused = self.focused();
+        if focused.playback.is_some() {
+            ui.horizontal(|ui| {
+                ui.label("▶ Replaying session");
+                if ui.button("⏹ Stop").clicked() {
+                    let tab = self.tab_mut();
+                    tab.slots[tab.focus].playback = None;
+                }
+            });
+            return;
+        }
+        if let Some(session) = &focused.session {
+            if ui.button(format!("⏹ Stop session recording ({} events)", session.len())).clicked() {
+                self.stop_session();
+            }
+            return;
+        }
+        ui.horizontal(|ui| {
+            let recordable = self.registry.get(self.focused().sim.name()).is_some();
+            if ui
+                .add_enabled(recordable, egui::Button::new("⏺ Record session"))
+                .on_hover_text("Reset, then record seed, parameters, speed and input to replay the run")
+                .clicked()
+            {
+                self.start_session();
+            }
+            if ui.button("▶ Play replay…").clicked() {
+                self.play_session(ctx);
+            }
+        });
+    }
+
+    /// Resets the focused sim and starts recording a session from there.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start_session(&mut self) {
+        let seed = self.seed;
+        let tab = &mut self.tabs[self.active];
+        let speed = tab.updates_per_second;
+        let slot = &mut tab.slots[tab.focus];
+        let Some(entry) = self.registry.get(slot.sim.name()) else { return };
+        slot.reset(seed);
+        let params = slot.sim.params().map(|params| params.to_json());
+        slot.session = Some(SessionRecorder::new(&entry.name, seed, params, speed));
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn stop_session(&mut self) {
+        let tab = self.tab_mut();
+        let slot = &mut tab.slots[tab.focus];
+        let Some(session) = slot.session.take() else { return };
+        let file = session.finish(slot.stats.stats.steps);
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(export::file_name(&file.sim, "replay.json"))
+            .add_filter("Session replay", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+        let result = serde_json::to_vec_pretty(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|data| std::fs::write(&path, data).map_err(|e| e.to_string()));
+        self.status = Some(match result {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Save failed: {e}"),
+        });
+    }
+
+    /// Asks for a session file and replays it in a new tab.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn play_session(&mut self, ctx: &egui::Context) {
+        let picked = rfd::FileDialog::new().add_filter("Session replay", &["json"]).pick_file();
+        let Some(path) = picked else { return };
+        let result = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| SessionFile::from_json(&data))
+            .and_then(|file| self.start_replay(ctx, &file));
+        self.status = Some(match result {
+            Ok(()) => format!("Replaying {}", path.display()),
+            Err(e) => format!("Load failed: {e}"),
+        });
+    }
+
+    /// Opens a new tab replaying `file`.
+    fn start_replay(&mut self, ctx: &egui::Context, file: &SessionFile) -> Result<(), String> {
+        let entry = self.registry.get(&file.sim).ok_or(format!("no simulation named {}", file.sim))?;
+        let mut sim = entry.create();
+        if let Some(values) = file.params.clone()
+            && let Some(params) = sim.params()
+        {
+            let _ = params.set_json(values);
+        }
+        sim.on_load(ctx);
+        let mut tab = Tab::new(sim);
+        tab.updates_per_second = file.speed;
+        tab.slots[0].restart(file.seed);
+        tab.slots[0].playback = Some(SessionPlayback::new(file));
+        self.tabs.push(tab);
+        self.select_tab(self.tabs.len() - 1);
+        Ok(())
+    }
+
+    /// Loads files dropped onto the window: session replays open in a new tab, snapshots
+    /// restore the focused sim, and anything else goes to the focused sim's `load_file`
+    /// if it lists the file's extension.
+    fn load_dropped_files(&mut self, ctx: &egui::Context) {
+        for file in ctx.input(|i| i.raw.dropped_files.clone()) {
+            let name = file
+                .path
+                .as_ref()
+                .and_then(|path| path.file_name())
+                .map_or(file.name.clone(), |name| name.to_string_lossy().into_owned());
+            let data = match (&file.bytes, &file.path) {
+                (Some(bytes), _) => Ok(bytes.to_vec()),
+                (None, Some(path)) => std::fs::read(path).map_err(|e| e.to_string()),
+                (None, None) => continue,
+            };
+            let result = data.and_then(|data| self.load_file(ctx, &name, &data));
+            self.status = Some(match result {
+                Ok(()) => format!("Loaded {name}"),
+                Err(e) => format!("Couldn't load {name}: {e}"),
+            });
+        }
+    }
+
+    fn load_file(&mut self, ctx: &egui::Context, name: &str, data: &[u8]) -> Result<(), String> {
+        let lower = name.to_lowercase();
+        if lower.ends_with(".replay.json") {
+            return self.start_replay(ctx, &SessionFile::from_json(data)?);
+        }
+        let tab = &mut self.tabs[self.active];
+        let slot = &mut tab.slots[tab.focus];
+        let extension = lower.rsplit_once('.').map_or("", |(_, extension)| extension);
+        let result = if lower.ends_with(".snapshot.json") {
+            slot.sim.load_state(data)
+        } else if slot.sim.file_formats().contains(&extension) {
+            slot.sim.load_file(name, data)
+        } else {
+            return Err(format!("{} doesn't open .{extension} files", slot.sim.name()));
+        };
+        result.map_err(|e| e.to_string())?;
+        slot.rewind.clear();
+        slot.error = None;
+        Ok(())
+    }
+
+    /// F1 toggles a window explaining the focused sim: what it models, its colors,
+    /// parameters and controls, plus the App's own canvas controls.
+    fn help_ui(&mut self, ctx: &egui::Context) {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F1)) {
+            self.show_help = !self.show_help;
+        }
+        if !self.show_help {
+            return;
+        }
+        let tab = &mut self.tabs[self.active];
+        let sim = &mut tab.slots[tab.focus].sim;
+        let (name, info, help) = (sim.name().to_owned(), sim.info(), sim.help());
+        let specs = sim.params().map(|params| params.specs()).unwrap_or_default();
+        let takes_input = sim.capabilities().contains(Capabilities::INPUT);
+
+        egui::Window::new(format!("{name} help"))
+            .id(egui::Id::new("sim_help"))
+            .open(&mut self.show_help)
+            .default_size([380.0, 460.0])
+            .vscroll(true)
+            .show(ctx, |ui| {
+                info_ui(ui, &info);
+                if !help.overview.is_empty() {
+                    ui.add_space(4.0);
+                    ui.label(help.overview);
+                }
+                if !help.legend.is_empty() {
+                    ui.add_space(4.0);
+                    ui.strong("Colors");
+                    for (color, meaning) in &help.legend {
+                        ui.horizontal(|ui| {
+                            let size = egui::vec2(14.0, 14.0);
+                            let (swatch, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+                            ui.painter().rect_filled(swatch, 2.0, *color);
+                            ui.painter().rect_stroke(
+                                swatch,
+                                2.0,
+                                ui.visuals().widgets.noninteractive.bg_stroke,
+                                egui::StrokeKind::Inside,
+                            );
+                            ui.label(*meaning);
+                        });
+                    }
+                }
+                if !specs.is_empty() {
+                    ui.add_space(4.0);
+                    ui.strong("Parameters");
+                    egui::Grid::new("help_params").num_columns(2).striped(true).show(ui, |ui| {
+                        for spec in &specs {
+                            ui.label(spec.name.replace('_', " "));
+                            let doc = help.params.iter().find(|(param, _)| *param == spec.name);
+                            match (doc, spec.range) {
+                                (Some((_, doc)), _) => ui.label(*doc),
+                                (None, Some((min, max))) => ui.weak(format!("{min} to {max}")),
+                                (None, None) => ui.weak("—"),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                }
+                ui.add_space(4.0);
+                ui.strong("Controls");
+                let pan = if takes_input { "Middle drag" } else { "Drag" };
+                let app_controls = [
+                    ("Scroll / pinch", "Zoom the canvas"),
+                    (pan, "Pan the canvas"),
+                    ("Hover", "Read the cell under the pointer"),
+                    ("F1", "Toggle this help"),
+                    ("F3", "Performance overlay"),
+                    ("F11", "Presentation mode"),
+                    ("Ctrl+P", "Command palette"),
+                    ("Ctrl+S", "Save the frame as a PNG, or a vector view as SVG"),
+                ];
+                egui::Grid::new("help_controls").num_columns(2).striped(true).show(ui, |ui| {
+                    for (input, effect) in help.controls.iter().chain(&app_controls) {
+                        ui.monospace(*input);
+                        ui.label(*effect);
+                        ui.end_row();
+                    }
+                });
+            });
+    }
+
+    /// Dims the window and says what will happen while files are dragged over it.
+    fn drop_hint_ui(&self, ctx: &egui::Context) {
+        if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+            return;
+        }
+        let sim = &self.focused().sim;
+        let mut formats: Vec<String> =
+            sim.file_formats().iter().map(|format| format!(".{format}")).collect();
+        if sim.capabilities().contains(Capabilities::SNAPSHOTS) {
+            formats.push(".snapshot.json".to_owned());
+        }
+        formats.push(".replay.json".to_owned());
+        let text = format!("Drop to load into {}\n{}", sim.name(), formats.join("  "));
+
+        let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_hint"));
+        let painter = ctx.layer_painter(layer);
+        let screen = ctx.content_rect();
+        painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(160));
+        painter.text(
+            screen.center(),
+            egui::Align2::CENTER_CENTER,
+            text,
+            egui::FontId::proportional(22.0),
+            egui::Color32::WHITE,
+        );
+    }
+
+    /// Record button for MP4/WebM videos, with the format, frame rate and bitrate.
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    fn video_ui(&mut self, ui: &mut egui::Ui) {
+        if self.video.is_finishing() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label("Finishing video…");
+            });
+            return;
+        }
+        if self.video.is_recording() {
+            if ui.button(format!("⏹ Stop video ({} frames)", self.video.len())).clicked() {
+                self.video.stop();
+            }
+            return;
+        }
+        if !self.focused().sim.capabilities().contains(Capabilities::EXPORT) {
+            return;
+        }
+        ui.horizontal(|ui| {
+            let record = ui.button("🎬 Record video").on_hover_text("Encode with ffmpeg until stopped");
+            if record.clicked() {
+                let extension = self.video.container.extension();
+                let path = rfd::FileDialog::new()
+                    .set_file_name(export::file_name(self.focused().sim.name(), extension))
+                    .add_filter("Video", &[extension])
+                    .save_file();
+                if let Some(path) = path {
+                    self.video.start(path);
+                    self.show_hud |= self.video.overlay;
+                }
+            }
+            for (container, label) in [(Container::Mp4, "MP4"), (Container::WebM, "WebM")] {
+                ui.selectable_value(&mut self.video.container, container, label);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut self.video.fps).range(1..=120).suffix(" fps"));
+            ui.add(
+                egui::DragValue::new(&mut self.video.bitrate_kbps)
+                    .range(100..=100_000)
+                    .speed(50)
+                    .suffix(" kbps"),
+            );
+            ui.checkbox(&mut self.video.overlay, "Include overlays")
+                .on_hover_text("Record the canvas as shown on screen, stats overlay included");
+        });
+    }
+
+    /// Feeds the video recorder a screen capture of the canvas when recording overlays.
+    #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+    fn capture_overlay_video(&mut self, ctx: &egui::Context) {
+        if !self.video.overlay {
+            return;
+        }
+        let screenshots: Vec<_> = ctx.input(|i| {
+            i.events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Screenshot { image, .. } => Some(image.clone()),
+                    _ => None,
+                })
+                .collect()
+        });
+        let canvas = self.canvas_rect.intersect(ctx.content_rect());
+        for screenshot in screenshots {
+            if self.video.is_recording() && canvas.is_positive() {
+                self.video.push(&screenshot.region(&canvas, Some(ctx.pixels_per_point())));
+            }
+        }
+        if self.video.due(ctx.input(|i| i.time)) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::default()));
+        }
+    }
+
+    /// Asks for a snapshot file and restores the focused sim from it.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load_snapshot(&mut self) {
+        let picked = rfd::FileDialog::new().add_filter("Snapshot", &["json"]).pick_file();
+        let Some(path) = picked else { return };
+        let tab = &mut self.tabs[self.active];
+        let sim = &mut tab.slots[tab.focus].sim;
+        let result = std::fs::read(&path)
+            .map_err(|e| SimError::State(e.to_string()))
+            .and_then(|data| sim.load_state(&data));
+        self.status = Some(match result {
+            Ok(()) => {
+                tab.slots[tab.focus].rewind.clear();
+                format!("Loaded {}", path.display())
+            }
+            Err(e) => format!("Load failed: {e}"),
+        });
+    }
+
+    /// Scrubs back through the focused sim's checkpoints, or branches a new tab from one.
+    fn rewind_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        let rewind = &self.focused().rewind;
+        let count = rewind.len();
+        if count < 2 {
+            return;
+        }
+        let newest = rewind.get(count - 1).map_or(0.0, |checkpoint| checkpoint.stats.wall_time);
+        let times: Vec<f64> = (0..count)
+            .filter_map(|index| rewind.get(index).map(|checkpoint| checkpoint.stats.wall_time - newest))
+            .collect();
+        let mut index = rewind.cursor.unwrap_or(count - 1);
+        let rewound = rewind.cursor.is_some();
+
+        ui.horizontal(|ui| {
+            ui.label("⏪ Rewind:");
+            let slider = egui::Slider::new(&mut index, 0..=count - 1)
+                .custom_formatter(|i, _| format!("{:+.1} s", times[i as usize]));
+            if ui.add(slider).changed() {
+                self.tab_mut().rewind_to(index);
+            }
+        });
+        if rewound
+            && ui
+                .button("Branch to new tab")
+                .on_hover_text("Open this state in a new tab and leave this timeline as it was")
+                .clicked()
+        {
+            self.branch_tab(ctx, index);
+        }
+    }
+
+    /// Plots of the focused sim's metrics, with CSV export.
+    fn metrics_ui(&mut self, ui: &mut egui::Ui) {
+        let slot = self.focused();
+        if slot.metrics.is_empty() {
+            ui.weak(format!("{} doesn't report any metrics", slot.sim.name()));
+            return;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui.button("Export CSV").clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .set_file_name(export::file_name(slot.sim.name(), "csv"))
+                .add_filter("CSV", &["csv"])
+                .save_file()
+        {
+            self.status = Some(match std::fs::write(&path, slot.metrics.to_csv()) {
+                Ok(()) => format!("Saved {}", path.display()),
+                Err(e) => format!("Save failed: {e}"),
+            });
+        }
+        let slot = self.focused();
+        slot.metrics.plot_ui(ui, ("metrics", slot.sim.name()));
+    }
+
+    /// Recording settings and a timeline for playing the focused sim's recorded frames
+    /// back and forth without re-running it.
+    fn history_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.record_frames, "Record frames");
+            ui.add(
+                egui::DragValue::new(&mut self.history_cap_mb)
+                    .range(16..=8192)
+                    .suffix(" MB"),
+            )
+            .on_hover_text("Memory each sim's recording may use; the oldest frames go first");
+        });
+        let focused = self.focused();
+        let history = &focused.history;
+        ui.weak(format!(
+            "{} frames, {:.1} MB",
+            history.len(),
+            history.bytes() as f64 / (1024.0 * 1024.0)
+        ));
+        if focused.render_mode() == RenderMode::Vector {
+            ui.weak("Vector views aren't recorded.");
+        }
+        let count = history.len();
+        if count == 0 {
+            return;
+        }
+        let steps: Vec<u64> = (0..count)
+            .filter_map(|index| history.get(index).map(|frame| frame.steps))
+            .collect();
+
+        let tab = &mut self.tabs[self.active];
+        let mut index = tab.replay.as_ref().map_or(count - 1, |replay| replay.position.round() as usize);
+        let slider = egui::Slider::new(&mut index, 0..=count - 1)
+            .custom_formatter(|i, _| format!("step {}", steps[i as usize]));
+        if ui.add(slider).changed() {
+            tab.seek_replay(index);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text("First frame").clicked() {
+                tab.seek_replay(0);
+            }
+            for (label, direction, hint) in [
+                ("◀", -1.0, "Play backwards"),
+                ("⏸", 0.0, "Hold this frame"),
+                ("▶", 1.0, "Play forwards"),
+            ] {
+                if ui.button(label).on_hover_text(hint).clicked() {
+                    tab.seek_replay(index);
+                    if let Some(replay) = &mut tab.replay {
+                        replay.direction = direction;
+                    }
+                }
+            }
+            if ui.button("⏭").on_hover_text("Last frame").clicked() {
+                tab.seek_replay(count - 1);
+            }
+            if let Some(replay) = &mut tab.replay {
+                ui.add(
+                    egui::DragValue::new(&mut replay.speed)
+                        .range(1.0..=1000.0)
+                        .suffix(" fps"),
+                );
+            }
+        });
+        if tab.replay.is_some() && ui.button("Back to live").clicked() {
+            tab.stop_replay();
+        }
+    }
+
+    /// Opens a new tab holding a fresh instance of the focused sim restored to checkpoint
+    /// `index`, paused.
+    fn branch_tab(&mut self, ctx: &egui::Context, index: usize) {
+        let focused = self.focused();
+        let entry = self.registry.get(focused.sim.name());
+        let (Some(entry), Some(checkpoint)) = (entry, focused.rewind.get(index)) else { return };
+        let mut sim = entry.create();
+        sim.on_load(ctx);
+        if let Err(e) = sim.load_state(&checkpoint.data) {
+            self.status = Some(format!("Branch failed: {e}"));
+            return;
+        }
+        let stats = checkpoint.stats;
+        let mut tab = Tab::new(sim);
+        tab.is_paused = true;
+        tab.slots[0].stats.stats = stats;
+        self.tabs.push(tab);
+        self.select_tab(self.tabs.len() - 1);
+    }
+
+    fn set_presentation(&mut self, ctx: &egui::Context, on: bool) {
+        self.presentation = on;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(on));
+    }
+
+    /// F11 toggles presentation mode and Escape leaves it; while presenting, moving the
+    /// pointer to the left edge reveals a button to bring the controls back.
+    fn presentation_ui(&mut self, ctx: &egui::Context) {
+        let toggle = ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F11));
+        let escape = self.presentation
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Escape));
+        if toggle || escape {
+            self.set_presentation(ctx, !self.presentation);
+        }
+
+        let near_edge = ctx.input(|i| i.pointer.hover_pos().is_some_and(|pos| pos.x < 40.0));
+        if self.presentation && near_edge {
+            egui::Area::new(egui::Id::new("presentation_strip"))
+                .fixed_pos(egui::pos2(8.0, 8.0))
+                .show(ctx, |ui| {
+                    if ui.button("☰ Show controls (F11)").clicked() {
+                        self.set_presentation(ctx, false);
+                    }
+                });
+        }
+    }
+
+    fn browser_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        let current_name = self.focused().sim.name().to_owned();
+        if let Some(sim) = self.browser.ui(ui, &self.registry, &current_name) {
+            self.load_sim(ctx, sim);
+        }
+    }
+
+    fn controls_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        let tab = &mut self.tabs[self.active];
+        if tab.slots.len() > 1 {
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Controlling:");
+                for (index, slot) in tab.slots.iter().enumerate() {
+                    let label = format!("{}. {}", index + 1, slot.sim.name());
+                    if ui.selectable_label(tab.focus == index, label).clicked() {
+                        tab.focus = index;
+                        self.selected_preset = None;
+                    }
+                }
+            });
+        }
+
+        let comparable = self.registry.get(self.focused().sim.name()).is_some();
+        if ui
+            .add_enabled(comparable, egui::Button::new("➕ Compare"))
+            .on_hover_text("Open another instance side by side, stepped in lockstep")
+            .clicked()
+        {
+            self.add_comparison(ctx);
+        }
+
+        info_ui(ui, &self.focused().sim.info());
+        let help = ui.small_button("❓ Help").on_hover_text("What this sim shows and how to use it (F1)");
+        if help.clicked() {
+            self.show_help = true;
+        }
+
+        if self.focused().error.is_none() && self.focused().sim.is_finished() {
+            ui.colored_label(egui::Color32::GREEN, "✔ Completed");
+        }
+
+        ui.separator();
+
+        let seed = self.seed;
+        let step_count = &mut self.step_count;
+        let tab = &mut self.tabs[self.active];
+        let modes: Vec<StepMode> = tab.slots.iter().map(|slot| slot.sim.config().step_mode).collect();
+        let all_manual = modes.iter().all(|&mode| mode == StepMode::Manual);
+
+        ui.horizontal(|ui| {
+            if !all_manual
+                && ui.button(if tab.is_paused { "Resume" } else { "Pause" }).clicked()
+            {
+                tab.is_paused = !tab.is_paused;
+            }
+            if ui.button("Reset").clicked() {
+                tab.reset(seed);
+                self.hud.reset_dropped();
+            }
+        });
+
+        if tab.is_paused || modes.contains(&StepMode::Manual) {
+            let unfinished = tab.slots.iter().any(|slot| !slot.sim.is_finished());
+            ui.add_enabled_ui(unfinished, |ui| {
+                ui.horizontal(|ui| {
+                    let mut steps = 0;
+                    if ui.button("Step").clicked() {
+                        steps = 1;
+                    }
+                    if ui.button(format!("Step ×{step_count}")).clicked() {
+                        steps = *step_count;
+                    }
+                    ui.add(egui::DragValue::new(step_count).range(1..=1_000_000));
+                    if steps > 0 {
+                        let time_scale = tab.time_scale;
+                        tab.lockstep(|slot| slot.step_manually(steps, time_scale));
+                    }
+                });
+            });
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Seed:");
+            ui.add(egui::DragValue::new(&mut self.seed));
+            if ui.button("🎲").on_hover_text("Randomize (applies on Reset)").clicked() {
+                self.seed = rand::random::<u32>() as u64;
+            }
+        });
+
+        if self.focused().sim.capabilities().contains(Capabilities::RESIZE) {
+            self.resolution_ui(ui);
+        }
+        if self.focused().render_mode() == RenderMode::Raster {
+            self.layers_ui(ui);
+            if self.focused().sim.config().pixel_format == PixelFormat::Indexed {
+                egui::CollapsingHeader::new("Palette").show(ui, |ui| self.palette_ui(ui));
+            }
+            egui::CollapsingHeader::new("Effects").show(ui, |ui| self.effects_ui(ui));
+        }
+        if self.focused().sim.capabilities().contains(Capabilities::WORKERS) {
+            egui::CollapsingHeader::new("Workers").show(ui, |ui| self.workers_ui(ui));
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.focused().sim.capabilities().contains(Capabilities::SNAPSHOTS) {
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    self.save_snapshot();
+                }
+                if ui.button("Load").clicked() {
+                    self.load_snapshot();
+                }
+            });
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.focused().sim.capabilities().contains(Capabilities::EXPORT) {
+            ui.horizontal(|ui| {
+                if self.focused().render_mode() == RenderMode::Vector {
+                    let save = ui.button("📐 Save SVG").on_hover_text("Save the view as an SVG (Ctrl+S)");
+                    if save.clicked() {
+                        self.save_svg(ctx);
+                    }
+                    return;
+                }
+                let save = ui.button("📷 Save frame").on_hover_text("Save the frame as a PNG (Ctrl+S)");
+                if save.clicked() {
+                    self.save_frame(ctx);
+                }
+                let (w, h) = self.focused().sim.dimensions();
+                egui::ComboBox::from_id_salt("export_scale")
+                    .selected_text(format!("{}×", self.export_scale))
+                    .show_ui(ui, |ui| {
+                        for scale in [1, 2, 4, 8] {
+                            let label = format!("{scale}× ({}×{})", w * scale, h * scale);
+                            ui.selectable_value(&mut self.export_scale, scale, label);
+                        }
+                    })
+                    .response
+                    .on_hover_text("Resolution to render saved frames at");
+            });
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.gif_ui(ui);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.session_ui(ctx, ui);
+        #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+        self.video_ui(ui);
+
+        self.rewind_ui(ctx, ui);
+
+        // The focused sim's speed range wins; otherwise the first continuous sim's
+        let tab = &mut self.tabs[self.active];
+        let continuous = std::iter::once(tab.focused())
+            .chain(&tab.slots)
+            .map(|slot| slot.sim.config())
+            .find(|config| config.step_mode == StepMode::Continuous);
+        if let Some(config) = continuous {
+            ui.add_enabled_ui(!tab.turbo, |ui| {
+                speed_slider(ui, &mut tab.updates_per_second, &config);
+                speed_presets(ui, &mut tab.updates_per_second, &config);
+            });
+            ui.checkbox(&mut tab.turbo, "Turbo (as fast as possible)");
+        }
+        if modes.contains(&StepMode::Realtime) {
+            ui.add(
+                egui::Slider::new(&mut tab.time_scale, 0.1..=10.0)
+                    .text("Time scale")
+                    .logarithmic(true)
+            );
+        }
+        if self.tabs.len() > 1 {
+            ui.add(
+                egui::Slider::new(&mut self.background_rate, 0.0..=1.0)
+                    .text("Background tabs")
+                    .custom_formatter(|rate, _| match rate {
+                        0.0 => "paused".to_owned(),
+                        rate => format!("{:.0}%", rate * 100.0),
+                    })
+            )
+            .on_hover_text("Speed that tabs you aren't looking at keep running at");
+        }
+
+        let composited = self.tab().slots.iter().any(|slot| {
+            slot.sim.config().pixel_format == PixelFormat::Rgba || slot.render_mode() == RenderMode::Vector
+        });
+        let mut restyle = self.theme.ui(ui);
+        if composited && self.theme.kind != ThemeKind::Custom {
+            ui.horizontal(|ui| {
+                ui.label("Background:");
+                if ui.color_edit_button_srgba(&mut self.theme.background).changed() {
+                    self.theme.kind = ThemeKind::Custom;
+                    restyle = true;
+                }
+            });
+        }
+        if restyle {
+            self.theme.apply(ctx);
+            // Sims draw with the theme's colors
+            for slot in self.tabs.iter_mut().flat_map(|tab| &mut tab.slots) {
+                slot.invalidate();
+            }
+        }
+    }
+
+    /// A visibility toggle and opacity slider for each of the focused sim's layers.
+    fn layers_ui(&mut self, ui: &mut egui::Ui) {
+        let tab = &mut self.tabs[self.active];
+        let slot = &mut tab.slots[tab.focus];
+        let infos = slot.sim.layers();
+        if infos.is_empty() {
+            return;
+        }
+        slot.sync_layers();
+        let mut changed = false;
+        egui::CollapsingHeader::new("Layers").default_open(true).show(ui, |ui| {
+            for (info, layer) in infos.iter().zip(&mut slot.layers) {
+                ui.horizontal(|ui| {
+                    changed |= ui.checkbox(&mut layer.visible, &info.name).changed();
+                    ui.add_enabled_ui(layer.visible, |ui| {
+                        changed |= ui
+                            .add(egui::Slider::new(&mut layer.opacity, 0.0..=1.0).show_value(false))
+                            .on_hover_text("Opacity")
+                            .changed();
+                    });
+                });
+            }
+        });
+        if changed {
+            slot.invalidate();
+        }
+    }
+
+    /// Post-processing sliders for the focused sim, remembered under its name.
+    fn effects_ui(&mut self, ui: &mut egui::Ui) {
+        let sim = &self.focused().sim;
+        let name = sim.name().to_owned();
+        let mut effects = self.sim_effects.get(&name).copied().unwrap_or(sim.config().effects);
+        if effects.ui(ui) {
+            self.sim_effects.insert(name, effects);
+        }
+    }
+
+    /// Threads and priority for the focused sim's workers: the ones every sim shares, or
+    /// its own, remembered under its name. Changes restart the workers where they were.
+    fn workers_ui(&mut self, ui: &mut egui::Ui) {
+        let name = self.focused().sim.name().to_owned();
+        let mut own = self.sim_workers.contains_key(&name);
+        if ui.checkbox(&mut own, "Just for this sim").changed() {
+            if own {
+                self.sim_workers.insert(name.clone(), self.worker_settings);
+            } else {
+                self.sim_workers.remove(&name);
+            }
+        }
+        match self.sim_workers.get_mut(&name) {
+            Some(settings) => settings.ui(ui, "sim_worker_priority"),
+            None => self.worker_settings.ui(ui, "worker_priority"),
+        }
+    }
+
+    fn palette_ui(&mut self, ui: &mut egui::Ui) {
+        let sim = &self.focused().sim;
+        let name = sim.name().to_owned();
+        let mut palette = self.sim_palettes.get(&name).cloned().unwrap_or_else(|| sim.palette());
+        if palette.ui(ui) {
+            self.sim_palettes.insert(name, palette);
+        }
+    }
+
+    /// Preset and custom resolutions for the focused sim. Changing it resets the sim.
+    fn resolution_ui(&mut self, ui: &mut egui::Ui) {
+        const PRESETS: [(usize, usize); 7] = [
+            (200, 150),
+            (400, 300),
+            (640, 480),
+            (800, 600),
+            (1024, 768),
+            (1280, 720),
+            (1920, 1080),
+        ];
+        let (w, h) = self.focused().sim.dimensions();
+        let mut size = (w, h);
+        ui.horizontal(|ui| {
+            ui.label("Resolution:");
+            egui::ComboBox::from_id_salt("resolution")
+                .selected_text(format!("{w}×{h}"))
+                .show_ui(ui, |ui| {
+                    for (pw, ph) in PRESETS {
+                        ui.selectable_value(&mut size, (pw, ph), format!("{pw}×{ph}"));
+                    }
+                });
+        })
+        .response
+        .on_hover_text("Changing the resolution resets the sim");
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut self.custom_size.0).range(1..=4096).suffix(" w"));
+            ui.add(egui::DragValue::new(&mut self.custom_size.1).range(1..=4096).suffix(" h"));
+            if ui.button("Apply").clicked() {
+                size = self.custom_size;
+            }
+        });
+        if size != (w, h) {
+            let seed = self.seed;
+            let tab = &mut self.tabs[self.active];
+            tab.slots[tab.focus].resize(size.0, size.1, seed);
+            self.custom_size = size;
+        }
+    }
+
+    fn canvas_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        self.canvas_rect = ui.max_rect();
+        let mut closed = None;
+        ui.columns(self.tab().slots.len(), |columns| {
+            for (index, ui) in columns.iter_mut().enumerate() {
+                if self.pane_ui(ctx, ui, index) {
+                    closed = Some(index);
+                }
+            }
+        });
+        if let Some(index) = closed {
+            self.close_slot(index);
+        }
+    }
+
+    fn panel_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, panel: Panel) {
+        match panel {
+            Panel::Canvas => self.canvas_ui(ctx, ui),
+            Panel::Browser => self.browser_ui(ctx, ui),
+            Panel::Controls => self.controls_ui(ctx, ui),
+            Panel::Parameters => self.params_ui(ui),
+            Panel::Stats => {
+                self.stats_ui(ui);
+                ui.checkbox(&mut self.show_hud, "Performance overlay (F3)");
+            }
+            Panel::SimUi => {
+                let tab = &mut self.tabs[self.active];
+                let sim = &mut tab.slots[tab.focus].sim;
+                if let Some(progress) = sim.progress() {
+                    progress.ui(ui);
+                    ui.separator();
+                }
+                sim.ui(ui);
+            }
+            Panel::History => self.history_ui(ui),
+            Panel::Console => self.console.ui(ui),
+            Panel::Metrics => self.metrics_ui(ui),
+        }
+    }
+
+    /// One slot's error banner, toolbar and canvas. Returns whether the pane asked to close.
+    fn pane_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, index: usize) -> bool {
+        let mut close = false;
+        let mut present = false;
+        let tab = &mut self.tabs[self.active];
+        let count = tab.slots.len();
+
+        if let Some(error) = &tab.slots[index].error {
+            let text = match error {
+                SimError::WorkerPanicked(msg) => format!("Worker crashed: {msg}"),
+                error => format!("Simulation failed: {error}"),
+            };
+            let restart = egui::Frame::new()
+                .fill(egui::Color32::from_rgb(120, 20, 20))
+                .inner_margin(6.0)
+                .show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.colored_label(egui::Color32::WHITE, text);
+                        ui.button("⟲ Restart").on_hover_text("Reset this sim and run again").clicked()
+                    })
+                    .inner
+                })
+                .inner;
+            // Only the slot that failed starts over; the others in a comparison carry on
+            if restart {
+                if tab.replay.is_some() {
+                    tab.stop_replay();
+                }
+                tab.slots[index].reset(self.seed);
+                tab.is_paused = false;
+                self.hud.reset_dropped();
+            }
+        }
+        let slot = &mut tab.slots[index];
+
+        if !self.presentation {
+            let views = slot.sim.views();
+            ui.horizontal(|ui| {
+                if count > 1 {
+                    if ui.selectable_label(tab.focus == index, slot.sim.name()).clicked() {
+                        tab.focus = index;
+                    }
+                    ui.separator();
+                }
+                if views.len() > 1 {
+                    ui.label("View:");
+                    for (view_index, view) in views.iter().enumerate() {
+                        if ui.selectable_label(slot.view_index == view_index, &view.name).clicked() {
+                            slot.view_index = view_index;
+                            slot.sim.select_view(view_index);
+                        }
+                    }
+                    ui.separator();
+                }
+                if ui.button("Reset view").clicked() {
+                    slot.camera.reset();
+                }
+                egui::ComboBox::from_id_salt(("canvas_fit", index))
+                    .selected_text(self.canvas_fit.label())
+                    .width(100.0)
+                    .show_ui(ui, |ui| {
+                        for fit in CanvasFit::ALL {
+                            ui.selectable_value(&mut self.canvas_fit, fit, fit.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("How the sim is scaled into the canvas");
+                if slot.render_mode() == RenderMode::Raster {
+                    let mut smooth = slot.texture_filter() == TextureFilter::Linear;
+                    if ui
+                        .toggle_value(&mut smooth, "Smooth")
+                        .on_hover_text("Blend neighboring pixels instead of drawing sharp squares")
+                        .changed()
+                    {
+                        slot.filter =
+                            Some(if smooth { TextureFilter::Linear } else { TextureFilter::Nearest });
+                    }
+                }
+                ui.label(format!("{:.0}%", slot.camera.zoom * 100.0));
+                ui.toggle_value(&mut self.show_minimap, "🗺")
+                    .on_hover_text("Show a minimap while the sim is larger than the canvas");
+                if slot.sim.focus().is_some() {
+                    ui.toggle_value(&mut self.follow, "🎯")
+                        .on_hover_text("Move the camera to keep the action in view");
+                }
+                if count > 1 && ui.button("✖").on_hover_text("Close this pane").clicked() {
+                    close = true;
+                }
+                if index == count - 1
+                    && ui.button("⛶").on_hover_text("Presentation mode (F11)").clicked()
+                {
+                    present = true;
+                }
+            });
+        }
+
+        let takes_input = slot.sim.capabilities().contains(Capabilities::INPUT);
+        let (response, painter) =
+            ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        slot.frame_rect =
+            self.canvas_fit.frame(response.rect, slot.sim.dimensions(), ctx.pixels_per_point());
+        slot.camera.navigate(ui, &response, slot.frame_rect, !takes_input);
+        if self.follow
+            && let Some(target) = slot.focus()
+        {
+            slot.camera.follow(target, slot.frame_rect, response.rect);
+        }
+        let record_cap = self.record_frames.then_some(self.history_cap_mb * 1024 * 1024);
+        slot.effects =
+            self.sim_effects.get(slot.sim.name()).copied().unwrap_or(slot.sim.config().effects);
+        if slot.sim.config().pixel_format == PixelFormat::Indexed {
+            let palette =
+                self.sim_palettes.get(slot.sim.name()).cloned().unwrap_or_else(|| slot.sim.palette());
+            if palette != slot.palette {
+                slot.palette = palette;
+                slot.invalidate();
+            }
+            slot.palette_shift = slot.palette.shift(ctx.input(|i| i.time));
+            if slot.palette.cycle != 0.0 {
+                ctx.request_repaint();
+            }
+        }
+        slot.paint(ctx, &painter, response.rect, self.theme.background, record_cap, &mut self.timings);
+        slot.route_input(ctx, &response, tab.focus == index);
+        #[cfg(not(target_arch = "wasm32"))]
+        if tab.focus == index
+            && let Some(frame) = &slot.frame
+        {
+            self.gif.capture(frame, ctx.input(|i| i.time));
+        }
+        #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+        if tab.focus == index
+            && !self.video.overlay
+            && let Some(frame) = &slot.frame
+            && self.video.due(ctx.input(|i| i.time))
+        {
+            self.video.push(frame);
+        }
+        slot.annotations_ui(&painter, response.rect);
+        slot.color_bar_ui(&painter, response.rect);
+        if !self.presentation {
+            slot.probe_ui(&painter, &response);
+            if self.show_minimap {
+                slot.minimap_ui(ui, &painter, response.rect, index);
+            }
+        }
+
+        if response.clicked() || response.drag_started() {
+            tab.focus = index;
+        }
+        if present {
+            self.set_presentation(ctx, true);
+        }
+        close
+    }
+}
+
+/// Draws the App's panels into the dock.
+struct PanelViewer<'a> {
+    app: &'a mut App,
+    ctx: &'a egui::Context,
+}
+
+impl TabViewer for PanelViewer<'_> {
+    type Tab = Panel;
+
+    fn title(&mut self, panel: &mut Panel) -> egui::WidgetText {
+        panel.title().into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, panel: &mut Panel) {
+        self.app.panel_ui(self.ctx, ui, *panel);
+    }
+
+    fn is_closeable(&self, panel: &Panel) -> bool {
+        panel.closeable()
+    }
+
+    fn scroll_bars(&self, panel: &Panel) -> [bool; 2] {
+        // The console scrolls itself so it can follow new messages
+        [false, !matches!(panel, Panel::Canvas | Panel::Console | Panel::Metrics)]
+    }
+}
+
+impl eframe::App for App {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // The active tab's focused sim goes last so its parameters and speed win over other instances
+        for tab in 0..self.tabs.len() {
+            for slot in 0..self.tabs[tab].slots.len() {
+                self.remember_params(tab, slot);
+            }
+            self.remember_speed(tab);
+        }
+        self.remember_params(self.active, self.tab().focus);
+        self.remember_speed(self.active);
+        let tab = self.tab();
+        let settings = AppSettings {
+            last_sim: self
+                .registry
+                .get(tab.focused().sim.name())
+                .map(|entry| entry.name.clone()),
+            is_paused: tab.is_paused,
+            turbo: tab.turbo,
+            time_scale: tab.time_scale,
+            background_rate: self.background_rate,
+            show_hud: self.show_hud,
+            canvas_fit: self.canvas_fit,
+            show_minimap: self.show_minimap,
+            follow: self.follow,
+            export_scale: self.export_scale,
+            record_frames: self.record_frames,
+            history_cap_mb: self.history_cap_mb,
+            seed: self.seed,
+            theme: self.theme,
+            layout: Some(self.dock.clone()),
+            sim_params: self.sim_params.clone(),
+            sim_speeds: self.sim_speeds.clone(),
+            sim_effects: self.sim_effects.clone(),
+            sim_palettes: self.sim_palettes.clone(),
+            worker_settings: self.worker_settings,
+            sim_workers: self.sim_workers.clone(),
+            open: Some(self.capture_workspace()),
+            workspaces: self.workspaces.clone(),
+        };
+        eframe::set_value(storage, eframe::APP_KEY, &settings);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        for slot in self.tabs.iter_mut().flat_map(|tab| &mut tab.slots) {
+            slot.sim.on_unload();
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Some(command) = self.palette.ui(ctx, self.palette_commands()) {
+            self.run_command(ctx, command);
+        }
+        self.presentation_ui(ctx);
+
+        if !self.presentation {
+            egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+                self.tabs_ui(ui);
+            });
+        }
+
+        if !self.presentation {
+            egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+                self.status_bar_ui(ui);
+            });
+        }
+
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::F3)) {
+            self.show_hud = !self.show_hud;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.focused().sim.capabilities().contains(Capabilities::EXPORT)
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::S))
+        {
+            self.save_frame(ctx);
+        }
+        self.load_dropped_files(ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(result) = self.gif.poll() {
+            self.status = Some(match result {
+                Ok(path) => format!("Saved {}", path.display()),
+                Err(e) => format!("GIF export failed: {e}"),
+            });
+        }
+        #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+        if let Some(result) = self.video.poll() {
+            self.status = Some(match result {
+                Ok(path) => format!("Saved {}", path.display()),
+                Err(e) => format!("Video export failed: {e}"),
+            });
+        }
+        self.timings = FrameTimings::default();
+
+        let dt = ctx.input(|i| i.stable_dt);
+        let mut running = false;
+        for tab in &mut self.tabs {
+            tab.observe_sessions();
+        }
+        let tab = &mut self.tabs[self.active];
+        if tab.replay.as_ref().is_some_and(|replay| replay.direction != 0.0) {
+            tab.play_replay(dt);
+            running = true;
+        }
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            let rate = if index == self.active { 1.0 } else { self.background_rate };
+            let stepping = rate > 0.0 && tab.running();
+            for slot in &mut tab.slots {
+                slot.sim.set_running(stepping && slot.pace().is_some());
+                let workers = self.sim_workers.get(slot.sim.name()).copied();
+                slot.sim.set_worker_settings(workers.unwrap_or(self.worker_settings));
+            }
+            if stepping {
+                let start = Instant::now();
+                self.timings.dropped += tab.advance(dt, rate);
+                self.timings.update += start.elapsed();
+                if tab.update_playback() && index == self.active {
+                    self.status = Some("Replay finished".to_owned());
+                }
+                running = true;
+            }
+        }
+
+        if self.presentation {
+            egui::CentralPanel::default().frame(egui::Frame::NONE).show(ctx, |ui| {
+                self.canvas_ui(ctx, ui);
+            });
+        } else {
+            // The viewer needs the whole App, so the dock is lent out for the frame
+            let mut dock = std::mem::replace(&mut self.dock, DockState::new(Vec::new()));
+            DockArea::new(&mut dock)
+                .show_add_buttons(false)
+                .show(ctx, &mut PanelViewer { app: self, ctx });
+            self.dock = dock;
+        }
+
+        self.help_ui(ctx);
+        self.drop_hint_ui(ctx);
+        self.hud.record(ctx.input(|i| i.unstable_dt), self.timings);
+        if self.show_hud {
+            let tab = self.tab();
+            let config = tab.focused().sim.config();
+            let requested = (config.step_mode == StepMode::Continuous && !tab.turbo)
+                .then_some(tab.updates_per_second);
+            self.hud.ui(ctx, self.canvas_rect, self.stats().steps_per_sec, requested, config.speed_unit);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.gif.is_recording() || self.gif.is_encoding() {
+            running = true;
+        }
+        #[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+        {
+            self.capture_overlay_video(ctx);
+            running |= self.video.is_recording() || self.video.is_finishing();
+        }
+        if running {
+            ctx.request_repaint();
+        }
+    }
+}
@@ -0,0 +1,519 @@
+use std::fmt;
+use crate::{Boundary, Grid, Neighborhood};
+
+/// An outer-totalistic Life-like rule: a dead cell with a neighbor count in `birth` comes
+/// alive, a live one with a count in `survival` stays alive, and every other cell dies.
+/// Bit n of each mask stands for n live neighbors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LifeRule {
+    pub birth: u16,
+    pub survival: u16,
+}
+
+impl LifeRule {
+    /// Conway's Game of Life, B3/S23.
+    pub const CONWAY: LifeRule = LifeRule { birth: 1 << 3, survival: 1 << 2 | 1 << 3 };
+
+    /// Reads a rule in B/S notation ("B36/S23"), or the older S/B form ("23/36").
+    ///
+    /// ```
+    /// use simulation_station::LifeRule;
+    ///
+    /// assert_eq!(LifeRule::parse("B3/S23"), Ok(LifeRule::CONWAY));
+    /// assert_eq!(LifeRule::parse("23/3"), Ok(LifeRule::CONWAY));
+    /// assert_eq!(LifeRule::parse("b36/s23").unwrap().to_string(), "B36/S23");
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let digits = |part: &str| {
+            part.chars().try_fold(0u16, |mask, c| match c.to_digit(10) {
+                Some(n) if n <= 8 => Ok(mask | 1 << n),
+                _ => Err(format!("bad neighbor count {c:?} in rule {text:?}")),
+            })
+        };
+        let (first, second) = text.trim().split_once('/').ok_or(format!("rule {text:?} has no '/'"))?;
+        let (first, second) = (first.trim(), second.trim());
+        fn tagged(part: &str, tag: char) -> Option<&str> {
+            part.strip_prefix([tag, tag.to_ascii_lowercase()])
+        }
+        match (tagged(first, 'B'), tagged(second, 'S'), tagged(first, 'S'), tagged(second, 'B')) {
+            (Some(birth), Some(survival), ..) | (.., Some(survival), Some(birth)) => {
+                Ok(Self { birth: digits(birth)?, survival: digits(survival)? })
+            }
+            _ => Ok(Self { birth: digits(second)?, survival: digits(first)? }),
+        }
+    }
+
+    /// Whether a cell, alive or not, with `neighbors` live neighbors is alive next step.
+    pub fn next(&self, alive: bool, neighbors: usize) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        mask >> neighbors & 1 == 1
+    }
+}
+
+impl Default for LifeRule {
+    fn default() -> Self {
+        Self::CONWAY
+    }
+}
+
+impl fmt::Display for LifeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counts = |mask: u16| {
+            (0..=8).filter(|n| mask >> n & 1 == 1).map(|n| n.to_string()).collect::<String>()
+        };
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survival))
+    }
+}
+
+/// A board of live and dead cells that Life-like rules can run on, so a sim can pick
+/// between a [`Grid<bool>`], which any other grid code can use, and a [`BitGrid`], which
+/// is an eighth the size and steps 64 cells at a time.
+pub trait LifeGrid {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn alive(&self, x: usize, y: usize) -> bool;
+    fn set_alive(&mut self, x: usize, y: usize, alive: bool);
+    /// Live cells among the eight around (x, y).
+    fn live_neighbors(&self, x: usize, y: usize) -> usize;
+    /// How many cells are alive.
+    fn population(&self) -> usize;
+    /// Kills every cell.
+    fn clear(&mut self);
+    /// Moves every cell on a generation under `rule`.
+    fn step_life(&mut self, rule: LifeRule);
+}
+
+/// Edges follow the grid's [`Boundary`].
+impl LifeGrid for Grid<bool> {
+    fn width(&self) -> usize {
+        Grid::width(self)
+    }
+
+    fn height(&self) -> usize {
+        Grid::height(self)
+    }
+
+    fn alive(&self, x: usize, y: usize) -> bool {
+        *self.get(x, y)
+    }
+
+    fn set_alive(&mut self, x: usize, y: usize, alive: bool) {
+        self.set(x, y, alive);
+    }
+
+    fn live_neighbors(&self, x: usize, y: usize) -> usize {
+        self.count_neighbors_where(x, y, Neighborhood::Moore, |&alive| alive)
+    }
+
+    fn population(&self) -> usize {
+        self.cells().iter().filter(|&&alive| alive).count()
+    }
+
+    fn clear(&mut self) {
+        self.cells_mut().fill(false);
+    }
+
+    fn step_life(&mut self, rule: LifeRule) {
+        self.step(Neighborhood::Moore, |&alive, neighbors| {
+            rule.next(alive, neighbors.count_where(|&n| n))
+        });
+    }
+}
+
+/// A board of live and dead cells packed 64 to a word, row by row, with bit `x % 64` of a
+/// row's word `x / 64` holding cell x. Its edges follow a [`Boundary`] as a
+/// [`Grid<bool>`]'s do: dead cells by default, or wrapping around to make a torus.
+/// [`step_life`](LifeGrid::step_life) counts neighbors for a whole word of cells at once
+/// with bitwise adders.
+///
+/// ```
+/// use simulation_station::{BitGrid, Grid, LifeGrid, LifeRule};
+///
+/// let glider: Grid<bool> = Grid::from_rle("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+/// let mut board = BitGrid::new(200, 100).with_wrap(true);
+/// board.paste(&glider, 198, 98);
+/// for _ in 0..4 {
+///     board.step_life(LifeRule::CONWAY);
+/// }
+/// // A glider moves one cell down and right every four generations, here across the seam
+/// assert_eq!(board.population(), 5);
+/// assert!(board.alive(0, 99) && board.alive(1, 0) && board.alive(199, 1));
+/// ```
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    /// Words per row.
+    stride: usize,
+    words: Vec<u64>,
+    boundary: Boundary<bool>,
+    /// The generation `step_life` is writing and its shifted copies of the rows, kept to
+    /// reuse their allocations.
+    back: Vec<u64>,
+    left: Vec<u64>,
+    right: Vec<u64>,
+}
+
+/// Leaves out `step_life`'s spare buffers.
+impl Clone for BitGrid {
+    fn clone(&self) -> Self {
+        let empty = BitGrid::new(self.width, self.height).with_boundary(self.boundary);
+        Self { words: self.words.clone(), ..empty }
+    }
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let stride = width.div_ceil(64);
+        Self {
+            width,
+            height,
+            stride,
+            words: vec![0; stride * height],
+            boundary: Boundary::Open,
+            back: Vec::new(),
+            left: Vec::new(),
+            right: Vec::new(),
+        }
+    }
+
+    /// Makes the board a torus, or gives it dead edges.
+    pub fn with_wrap(self, wrap: bool) -> Self {
+        self.with_boundary(if wrap { Boundary::Wrap } else { Boundary::Open })
+    }
+
+    pub fn wraps(&self) -> bool {
+        self.boundary == Boundary::Wrap
+    }
+
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.boundary = if wrap { Boundary::Wrap } else { Boundary::Open };
+    }
+
+    pub fn with_boundary(self, boundary: Boundary<bool>) -> Self {
+        Self { boundary, ..self }
+    }
+
+    pub fn boundary(&self) -> Boundary<bool> {
+        self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary<bool>) {
+        self.boundary = boundary;
+    }
+
+    /// Row-major packed storage, [`stride`](Self::stride) words to a row. Bits past the
+    /// right edge are always 0.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.stride..][..self.stride]
+    }
+
+    /// Writes the live cells of `pattern` with its top-left cell at (x, y), wrapping or
+    /// dropping those past the edges.
+    pub fn paste(&mut self, pattern: &Grid<bool>, x: usize, y: usize) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        for (i, _) in pattern.cells().iter().enumerate().filter(|&(_, &alive)| alive) {
+            let (px, py) = (x + i % pattern.width(), y + i / pattern.width());
+            if self.wraps() {
+                self.set_alive(px % self.width, py % self.height, true);
+            } else if px < self.width && py < self.height {
+                self.set_alive(px, py, true);
+            }
+        }
+    }
+
+    /// The row that row `y` lands on under the board's boundary, or, past a dead or
+    /// constant edge, whether the cells there are alive.
+    fn row_at(&self, y: isize) -> Result<usize, bool> {
+        let h = self.height as isize;
+        match self.boundary {
+            _ if (0..h).contains(&y) => Ok(y as usize),
+            Boundary::Open => Err(false),
+            Boundary::Constant(alive) => Err(alive),
+            Boundary::Clamp => Ok(y.clamp(0, h - 1) as usize),
+            Boundary::Wrap => Ok(y.rem_euclid(h) as usize),
+        }
+    }
+
+    /// Like [`row_at`](Self::row_at), for columns.
+    fn column_at(&self, x: isize) -> Result<usize, bool> {
+        let w = self.width as isize;
+        match self.boundary {
+            _ if (0..w).contains(&x) => Ok(x as usize),
+            Boundary::Open => Err(false),
+            Boundary::Constant(alive) => Err(alive),
+            Boundary::Clamp => Ok(x.clamp(0, w - 1) as usize),
+            Boundary::Wrap => Ok(x.rem_euclid(w) as usize),
+        }
+    }
+
+    /// Masks off the bits past the right edge in a row's last word.
+    fn edge_mask(&self) -> u64 {
+        match self.width % 64 {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// Fills `left` and `right` with `row` moved so each cell's bit holds its left and
+    /// right neighbor, past the ends per the board's edges.
+    fn shifted(&self, row: &[u64], left: &mut [u64], right: &mut [u64]) {
+        let n = row.len();
+        for i in 0..n {
+            let before = if i > 0 { row[i - 1] >> 63 } else { 0 };
+            let after = if i + 1 < n { row[i + 1] << 63 } else { 0 };
+            left[i] = row[i] << 1 | before;
+            right[i] = row[i] >> 1 | after;
+        }
+        let last = self.width - 1;
+        let (first_bit, last_bit) = (row[0] & 1, row[last / 64] >> (last % 64) & 1);
+        let (before, after) = match self.boundary {
+            Boundary::Open => (0, 0),
+            Boundary::Clamp => (first_bit, last_bit),
+            Boundary::Wrap => (last_bit, first_bit),
+            Boundary::Constant(alive) => (alive as u64, alive as u64),
+        };
+        left[0] |= before;
+        right[last / 64] |= after << (last % 64);
+        left[n - 1] &= self.edge_mask();
+    }
+}
+
+impl LifeGrid for BitGrid {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn alive(&self, x: usize, y: usize) -> bool {
+        self.words[y * self.stride + x / 64] >> (x % 64) & 1 == 1
+    }
+
+    fn set_alive(&mut self, x: usize, y: usize, alive: bool) {
+        assert!(x < self.width && y < self.height, "cell ({x}, {y}) is off the board");
+        let word = &mut self.words[y * self.stride + x / 64];
+        if alive {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    fn live_neighbors(&self, x: usize, y: usize) -> usize {
+        let mut count = 0;
+        for dy in -1..=1 {
+            let row = match self.row_at(y as isize + dy) {
+                Ok(ny) => self.row(ny),
+                Err(alive) => {
+                    count += 3 * alive as usize;
+                    continue;
+                }
+            };
+            // The three cells around x in one word when they share one, else one by one
+            let window = if (1..63).contains(&(x % 64)) && x + 1 < self.width {
+                row[x / 64] >> (x % 64 - 1) & 0b111
+            } else {
+                (-1..=1isize).fold(0, |bits, dx| {
+                    let bit = match self.column_at(x as isize + dx) {
+                        Ok(nx) => row[nx / 64] >> (nx % 64) & 1 == 1,
+                        Err(alive) => alive,
+                    };
+                    bits | (bit as u64) << (dx + 1)
+                })
+            };
+            count += window.count_ones() as usize;
+        }
+        count - self.alive(x, y) as usize
+    }
+
+    fn population(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    fn step_life(&mut self, rule: LifeRule) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let (stride, height) = (self.stride, self.height);
+        // Each row's cells moved one to the right and one to the left
+        let (mut left, mut right) = (std::mem::take(&mut self.left), std::mem::take(&mut self.right));
+        left.resize(self.words.len(), 0);
+        right.resize(self.words.len(), 0);
+        for y in 0..height {
+            let (l, r) = (&mut left[y * stride..][..stride], &mut right[y * stride..][..stride]);
+            self.shifted(self.row(y), l, r);
+        }
+        let mut next = std::mem::take(&mut self.back);
+        next.clear();
+        next.resize(self.words.len(), 0);
+        for y in 0..height {
+            let (above, below) = (self.row_at(y as isize - 1), self.row_at(y as isize + 1));
+            for i in 0..stride {
+                let at = |v: &[u64], row: Result<usize, bool>| match row {
+                    Ok(row) => v[row * stride + i],
+                    Err(alive) => if alive { u64::MAX } else { 0 },
+                };
+                let neighbors = [
+                    at(&left, above),
+                    at(&self.words, above),
+                    at(&right, above),
+                    at(&left, Ok(y)),
+                    at(&right, Ok(y)),
+                    at(&left, below),
+                    at(&self.words, below),
+                    at(&right, below),
+                ];
+                // The 64 neighbor counts as four bit planes, adding one neighbor at a time
+                let mut planes = [0u64; 4];
+                for mut carry in neighbors {
+                    for plane in &mut planes {
+                        let overflow = *plane & carry;
+                        *plane ^= carry;
+                        carry = overflow;
+                    }
+                }
+                let count_is = |n: usize| {
+                    planes.iter().enumerate().fold(u64::MAX, |eq, (bit, &plane)| {
+                        eq & if n >> bit & 1 == 1 { plane } else { !plane }
+                    })
+                };
+                let (mut born, mut survives) = (0, 0);
+                for n in 0..=8 {
+                    if rule.birth >> n & 1 == 1 {
+                        born |= count_is(n);
+                    }
+                    if rule.survival >> n & 1 == 1 {
+                        survives |= count_is(n);
+                    }
+                }
+                let alive = self.words[y * stride + i];
+                next[y * stride + i] = alive & survives | !alive & born;
+            }
+            next[y * stride + stride - 1] &= self.edge_mask();
+        }
+        self.back = std::mem::replace(&mut self.words, next);
+        (self.left, self.right) = (left, right);
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    /// Keeps the grid's boundary.
+    fn from(grid: &Grid<bool>) -> Self {
+        let mut bits = BitGrid::new(grid.width(), grid.height()).with_boundary(*grid.boundary());
+        bits.paste(grid, 0, 0);
+        bits
+    }
+}
+
+impl From<&BitGrid> for Grid<bool> {
+    fn from(bits: &BitGrid) -> Self {
+        let mut grid = Grid::new(bits.width, bits.height).with_boundary(bits.boundary);
+        for (i, cell) in grid.cells_mut().iter_mut().enumerate() {
+            *cell = bits.alive(i % bits.width, i / bits.width);
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use super::*;
+
+    fn live<G: LifeGrid>(board: &G) -> Vec<(usize, usize)> {
+        let mut cells = Vec::new();
+        for y in 0..board.height() {
+            cells.extend((0..board.width()).filter(|&x| board.alive(x, y)).map(|x| (x, y)));
+        }
+        cells
+    }
+
+    fn blinker_oscillates<G: LifeGrid>(mut board: G) {
+        for x in 4..7 {
+            board.set_alive(x, 5, true);
+        }
+        board.step_life(LifeRule::CONWAY);
+        assert_eq!(live(&board), [(5, 4), (5, 5), (5, 6)]);
+        board.step_life(LifeRule::CONWAY);
+        assert_eq!(live(&board), [(4, 5), (5, 5), (6, 5)]);
+    }
+
+    fn glider_moves<G: LifeGrid>(mut board: G, at: (usize, usize)) {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+        let (w, h) = (board.width(), board.height());
+        // A glider cell, moved `steps` cells down and right
+        let place = |(x, y): (usize, usize), steps: usize| {
+            ((at.0 + x + steps) % w, (at.1 + y + steps) % h)
+        };
+        for &cell in &glider {
+            let (x, y) = place(cell, 0);
+            board.set_alive(x, y, true);
+        }
+        for _ in 0..8 {
+            board.step_life(LifeRule::CONWAY);
+        }
+        let mut expected: Vec<_> = glider.iter().map(|&cell| place(cell, 2)).collect();
+        expected.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(live(&board), expected);
+    }
+
+    #[test]
+    fn blinker() {
+        blinker_oscillates(Grid::<bool>::new(12, 12));
+        blinker_oscillates(BitGrid::new(12, 12));
+    }
+
+    #[test]
+    fn glider() {
+        glider_moves(Grid::<bool>::new(20, 20), (3, 3));
+        glider_moves(BitGrid::new(20, 20), (3, 3));
+        // Across a word boundary and, wrapped, across the seams
+        glider_moves(BitGrid::new(150, 20), (62, 3));
+        glider_moves(Grid::<bool>::new(150, 20).with_boundary(Boundary::Wrap), (148, 18));
+        glider_moves(BitGrid::new(150, 20).with_wrap(true), (148, 18));
+    }
+
+    #[test]
+    fn bit_grid_matches_grid() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let edges = [
+            Boundary::Open,
+            Boundary::Clamp,
+            Boundary::Wrap,
+            Boundary::Constant(false),
+            Boundary::Constant(true),
+        ];
+        for boundary in edges {
+            let mut grid = Grid::new(130, 40).with_boundary(boundary);
+            grid.cells_mut().iter_mut().for_each(|cell| *cell = rng.random_bool(0.35));
+            let mut bits = BitGrid::from(&grid);
+            for rule in [LifeRule::CONWAY, LifeRule::parse("B36/S23").unwrap()] {
+                for _ in 0..20 {
+                    for (x, y) in [(0, 0), (63, 5), (64, 39), (129, 20)] {
+                        assert_eq!(bits.live_neighbors(x, y), grid.live_neighbors(x, y));
+                    }
+                    grid.step_life(rule);
+                    bits.step_life(rule);
+                    assert_eq!(Grid::from(&bits).cells(), grid.cells());
+                }
+            }
+            assert_eq!(bits.population(), LifeGrid::population(&grid));
+        }
+    }
+}
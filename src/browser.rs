@@ -0,0 +1,135 @@
+use eframe::egui;
+use std::collections::HashMap;
+use crate::app::composite_over;
+use crate::{NoSim, PixelFormat, RenderMode, SimEntry, SimRegistry, Simulation, Theme, Viewport};
+
+const THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(64.0, 48.0);
+/// Steps a fresh instance takes before its thumbnail is captured, so it shows more than
+/// an empty field: about a second at the default speed.
+const THUMBNAIL_STEPS: usize = 60;
+
+enum Thumbnail {
+    Raster(egui::TextureHandle),
+    /// Vector sims are kept around and repainted at thumbnail size.
+    Vector(Box<dyn Simulation>),
+}
+
+impl Thumbnail {
+    fn capture(ctx: &egui::Context, entry: &SimEntry) -> Self {
+        let mut sim = entry.create();
+        let _ = sim.update_n(THUMBNAIL_STEPS);
+        sim.on_unload();
+
+        let config = sim.config();
+        let mode = sim.views().first().map_or(config.render_mode, |view| view.render_mode);
+        if mode == RenderMode::Vector {
+            return Thumbnail::Vector(sim);
+        }
+
+        let (w, h) = sim.dimensions();
+        let mut buffer = vec![0; w * h * config.pixel_format.channels()];
+        sim.render(&mut buffer);
+        let image = match config.pixel_format {
+            PixelFormat::Rgb => egui::ColorImage::from_rgb([w, h], &buffer),
+            PixelFormat::Rgba => composite_over([w, h], &buffer, Theme::current().background),
+            PixelFormat::Indexed => {
+                let lut = sim.palette().lut(0);
+                let rgb: Vec<u8> = buffer.iter().flat_map(|&index| lut[index as usize]).collect();
+                egui::ColorImage::from_rgb([w, h], &rgb)
+            }
+        };
+        let name = format!("thumbnail: {}", entry.name);
+        Thumbnail::Raster(ctx.load_texture(name, image, egui::TextureOptions::LINEAR))
+    }
+
+    fn ui(&self, ui: &mut egui::Ui) {
+        let (rect, _) = ui.allocate_exact_size(THUMBNAIL_SIZE, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, Theme::current().background);
+        match self {
+            Thumbnail::Raster(texture) => {
+                let fitted = egui::Rect::from_center_size(
+                    rect.center(),
+                    texture.size_vec2() * (rect.size() / texture.size_vec2()).min_elem(),
+                );
+                let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                painter.image(texture.id(), fitted, uv, egui::Color32::WHITE);
+            }
+            Thumbnail::Vector(sim) => {
+                sim.render_world(&painter, &Viewport::fixed(rect, sim.dimensions()))
+            }
+        }
+    }
+}
+
+/// Sim picker: a search box over names, descriptions and tags, a collapsible tree of the
+/// registry's categories, and a thumbnail of each sim captured the first time it's shown.
+#[derive(Default)]
+pub(crate) struct SimBrowser {
+    query: String,
+    thumbnails: HashMap<String, Thumbnail>,
+}
+
+impl SimBrowser {
+    /// Draws the browser and returns a fresh instance of whatever the user picked.
+    pub(crate) fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        registry: &SimRegistry,
+        current: &str,
+    ) -> Option<Box<dyn Simulation>> {
+        let mut picked: Option<Box<dyn Simulation>> = None;
+
+        ui.add(egui::TextEdit::singleline(&mut self.query).hint_text("🔍 Search simulations"));
+        let query = self.query.trim().to_lowercase();
+        let searching = !query.is_empty();
+
+        egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+            if !searching && ui.selectable_label(current == NoSim.name(), "None").clicked() {
+                picked = Some(Box::new(NoSim));
+            }
+
+            let mut any = false;
+            for (category, entries) in registry.by_category() {
+                let entries: Vec<_> = entries.into_iter().filter(|entry| matches(entry, &query)).collect();
+                if entries.is_empty() {
+                    continue;
+                }
+                any = true;
+                egui::CollapsingHeader::new(category.label())
+                    .default_open(true)
+                    .open(searching.then_some(true))
+                    .show(ui, |ui| {
+                        for entry in entries {
+                            let thumbnail = self
+                                .thumbnails
+                                .entry(entry.name.clone())
+                                .or_insert_with(|| Thumbnail::capture(ui.ctx(), entry));
+                            let row = ui.horizontal(|ui| {
+                                thumbnail.ui(ui);
+                                ui.selectable_label(entry.name == current, &entry.name)
+                            });
+                            if row.inner.on_hover_text(entry.info.description).clicked() {
+                                picked = Some(entry.create());
+                            }
+                        }
+                    });
+            }
+            if searching && !any {
+                ui.weak("No matching simulations");
+            }
+        });
+
+        picked
+    }
+}
+
+/// Case-insensitive match of an already-lowercased `query` against an entry's name,
+/// description, category and tags. An empty query matches everything.
+fn matches(entry: &SimEntry, query: &str) -> bool {
+    let info = &entry.info;
+    entry.name.to_lowercase().contains(query)
+        || info.description.to_lowercase().contains(query)
+        || info.category.label().to_lowercase().contains(query)
+        || info.tags.iter().any(|tag| tag.to_lowercase().contains(query))
+}
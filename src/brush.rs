@@ -0,0 +1,148 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::{Boundary, Grid, SimInput};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum BrushShape {
+    #[default]
+    Round,
+    Square,
+}
+
+/// Click-and-drag painting for grid sims: the left button paints `primary`, the right
+/// `secondary`, over a round or square tip `size` cells from the center (0 paints one
+/// cell), or stamps `stamp` where one is set. Drags are joined up cell by cell, so fast
+/// strokes don't leave gaps. A sim on a [`Grid`] needs two lines:
+///
+/// ```
+/// use eframe::egui::PointerButton;
+/// use simulation_station::{Brush, Grid, SimInput};
+///
+/// let mut grid = Grid::new(10, 10);
+/// let mut brush = Brush::new(1, 0).with_size(1);
+///
+/// // in handle_input():
+/// let (button, pos) = (PointerButton::Primary, Default::default());
+/// brush.paint(&mut grid, SimInput::PointerDown { x: 2, y: 2, button, pos });
+/// brush.paint(&mut grid, SimInput::PointerDrag { x: 7, y: 2, button, pos });
+/// // and in ui(): brush.ui(ui);
+///
+/// assert!((1..=8).all(|x| *grid.get(x, 2) == 1));
+/// assert_eq!((*grid.get(4, 1), *grid.get(4, 0), *grid.get(1, 1)), (1, 0, 0));
+/// ```
+#[derive(Clone)]
+pub struct Brush<T> {
+    pub size: usize,
+    pub shape: BrushShape,
+    pub primary: T,
+    pub secondary: T,
+    /// Painted by the left button in place of the tip, centered on the pointer. Its default
+    /// cells are left out, so only the pattern itself lands.
+    pub stamp: Option<Grid<T>>,
+    /// The cell the pointer was last painted at while the button is held.
+    last: Option<(usize, usize)>,
+}
+
+impl<T: Clone + Default + PartialEq> Brush<T> {
+    /// A one-cell brush painting `primary` with the left button, `secondary` with the right.
+    pub fn new(primary: T, secondary: T) -> Self {
+        Self { size: 0, shape: BrushShape::Round, primary, secondary, stamp: None, last: None }
+    }
+
+    pub fn with_size(self, size: usize) -> Self {
+        Self { size, ..self }
+    }
+
+    pub fn with_shape(self, shape: BrushShape) -> Self {
+        Self { shape, ..self }
+    }
+
+    /// The offsets the tip covers around its center.
+    fn tip(&self) -> impl Iterator<Item = (isize, isize)> + '_ {
+        let r = self.size as isize;
+        (-r..=r)
+            .flat_map(move |dy| (-r..=r).map(move |dx| (dx, dy)))
+            .filter(move |&(dx, dy)| self.shape == BrushShape::Square || dx * dx + dy * dy <= r * r)
+    }
+
+    /// Calls `set` for every cell a pointer `event` paints, with the value to paint there,
+    /// for sims whose cells aren't in a [`Grid`]. Positions may be off the world. Returns
+    /// whether the event painted anything.
+    pub fn apply(&mut self, event: SimInput, mut set: impl FnMut(isize, isize, &T)) -> bool {
+        let (x, y, button) = match event {
+            SimInput::PointerDown { x, y, button, .. } => {
+                self.last = None;
+                (x, y, button)
+            }
+            SimInput::PointerDrag { x, y, button, .. } => (x, y, button),
+            SimInput::PointerUp { .. } => {
+                self.last = None;
+                return false;
+            }
+            SimInput::Key { .. } => return false,
+        };
+        let value = match button {
+            egui::PointerButton::Primary => &self.primary,
+            egui::PointerButton::Secondary => &self.secondary,
+            _ => return false,
+        };
+        // A stamp lands once per event; strung along the drag it would smear
+        if let Some(stamp) = self.stamp.as_ref().filter(|_| button == egui::PointerButton::Primary) {
+            let left = x as isize - stamp.width() as isize / 2;
+            let top = y as isize - stamp.height() as isize / 2;
+            for (i, cell) in stamp.cells().iter().enumerate() {
+                if *cell != T::default() {
+                    set(left + (i % stamp.width()) as isize, top + (i / stamp.width()) as isize, cell);
+                }
+            }
+            return true;
+        }
+        let (x0, y0) = self.last.unwrap_or((x, y));
+        let (dx, dy) = (x as isize - x0 as isize, y as isize - y0 as isize);
+        let steps = dx.abs().max(dy.abs());
+        // Every cell along the drag from the last painted one, rounded to the nearest
+        let along = |d: isize, step: isize| (2 * d * step + steps).div_euclid(2 * steps.max(1));
+        for step in 0..=steps {
+            let (cx, cy) = (x0 as isize + along(dx, step), y0 as isize + along(dy, step));
+            self.tip().for_each(|(tx, ty)| set(cx + tx, cy + ty, value));
+        }
+        self.last = Some((x, y));
+        true
+    }
+
+    /// Paints `grid` for a pointer `event`, wrapping past the edges of a
+    /// [`Boundary::Wrap`] grid. Returns whether it changed anything.
+    pub fn paint(&mut self, grid: &mut Grid<T>, event: SimInput) -> bool {
+        let wrap = matches!(grid.boundary(), Boundary::Wrap);
+        let (width, height) = (grid.width() as isize, grid.height() as isize);
+        let mut changed = false;
+        self.apply(event, |x, y, value| {
+            let inside = (0..width).contains(&x) && (0..height).contains(&y);
+            if let Some((x, y)) = grid.locate(x, y).filter(|_| inside || wrap)
+                && grid.get(x, y) != value
+            {
+                grid.set(x, y, value.clone());
+                changed = true;
+            }
+        });
+        changed
+    }
+
+    /// Size and shape pickers; sims add their own for the values.
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::Slider::new(&mut self.size, 0..=40).text("Brush"))
+                .on_hover_text("Cells from the center to the edge of the brush");
+            ui.selectable_value(&mut self.shape, BrushShape::Round, "●").on_hover_text("Round");
+            ui.selectable_value(&mut self.shape, BrushShape::Square, "■").on_hover_text("Square");
+        });
+        if let Some((width, height)) = self.stamp.as_ref().map(|s| (s.width(), s.height())) {
+            ui.horizontal(|ui| {
+                ui.label(format!("Stamping a {width} × {height} pattern"));
+                if ui.button("✖").on_hover_text("Paint with the brush again").clicked() {
+                    self.stamp = None;
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use eframe::egui;
+use egui::emath::GuiRounding;
+use serde::{Deserialize, Serialize};
+
+/// How the world is laid out in the canvas before the camera zooms and pans it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum CanvasFit {
+    /// As large as fits while keeping the sim's aspect ratio, letterboxed.
+    #[default]
+    Fit,
+    /// Covers the whole canvas at the sim's aspect ratio, cropping what sticks out.
+    Fill,
+    /// Stretched over the whole canvas, ignoring the aspect ratio.
+    Stretch,
+    /// The largest whole number of screen pixels per sim pixel that fits, so every pixel
+    /// comes out the same size.
+    PixelPerfect,
+}
+
+impl CanvasFit {
+    pub const ALL: [CanvasFit; 4] =
+        [CanvasFit::Fit, CanvasFit::Fill, CanvasFit::Stretch, CanvasFit::PixelPerfect];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CanvasFit::Fit => "Fit",
+            CanvasFit::Fill => "Fill",
+            CanvasFit::Stretch => "Stretch",
+            CanvasFit::PixelPerfect => "Pixel perfect",
+        }
+    }
+
+    /// Where a world of `dims` pixels lands in `canvas` at zoom 1, aligned to the
+    /// physical pixel grid.
+    pub fn frame(self, canvas: egui::Rect, dims: (usize, usize), pixels_per_point: f32) -> egui::Rect {
+        let world = egui::vec2(dims.0.max(1) as f32, dims.1.max(1) as f32);
+        let scale = canvas.size() / world;
+        let size = match self {
+            CanvasFit::Fit => world * scale.min_elem(),
+            CanvasFit::Fill => world * scale.max_elem(),
+            CanvasFit::Stretch => canvas.size(),
+            CanvasFit::PixelPerfect => {
+                let physical = (scale.min_elem() * pixels_per_point).floor().max(1.0);
+                world * physical / pixels_per_point
+            }
+        };
+        egui::Rect::from_center_size(canvas.center(), size).round_to_pixels(pixels_per_point)
+    }
+}
+
+/// Zoom/pan transform between simulation pixels ("world") and the canvas on screen.
+///
+/// At zoom 1 with no offset the world covers the whole rect it is given, which the App
+/// picks with [`CanvasFit::frame`]. The offset is the displacement of the world's center
+/// from that rect's center, in units of its size, so the view stays put when the window
+/// is resized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera2D {
+    pub zoom: f32,
+    pub offset: egui::Vec2,
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Self { zoom: 1.0, offset: egui::Vec2::ZERO }
+    }
+}
+
+impl Camera2D {
+    pub const MIN_ZOOM: f32 = 0.25;
+    pub const MAX_ZOOM: f32 = 64.0;
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Where the whole world lands on screen for a canvas occupying `canvas`.
+    pub fn world_rect(&self, canvas: egui::Rect) -> egui::Rect {
+        egui::Rect::from_center_size(
+            canvas.center() + self.offset * canvas.size(),
+            canvas.size() * self.zoom,
+        )
+    }
+
+    /// Screen position to world coordinates for a world of `dims` pixels.
+    pub fn screen_to_world(&self, pos: egui::Pos2, canvas: egui::Rect, dims: (usize, usize)) -> egui::Pos2 {
+        let world = self.world_rect(canvas);
+        let rel = (pos - world.min) / world.size();
+        egui::pos2(rel.x * dims.0 as f32, rel.y * dims.1 as f32)
+    }
+
+    pub fn world_to_screen(&self, pos: egui::Pos2, canvas: egui::Rect, dims: (usize, usize)) -> egui::Pos2 {
+        let world = self.world_rect(canvas);
+        world.min + egui::vec2(pos.x / dims.0 as f32, pos.y / dims.1 as f32) * world.size()
+    }
+
+    /// Zooms by `factor`, keeping the world point under `pos` fixed on screen.
+    pub fn zoom_at(&mut self, factor: f32, pos: egui::Pos2, canvas: egui::Rect) {
+        let new_zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        let factor = new_zoom / self.zoom;
+        let center = canvas.center() + self.offset * canvas.size();
+        let new_center = pos + (center - pos) * factor;
+        self.zoom = new_zoom;
+        self.offset = (new_center - canvas.center()) / canvas.size();
+    }
+
+    /// Pans so the world point `fraction` of the way across the world (0..1 on each axis)
+    /// sits at the center of `view`, for a world covering `canvas` at zoom 1.
+    pub fn look_at(&mut self, fraction: egui::Vec2, canvas: egui::Rect, view: egui::Rect) {
+        let center = view.center() - (fraction - egui::vec2(0.5, 0.5)) * canvas.size() * self.zoom;
+        self.offset = (center - canvas.center()) / canvas.size();
+    }
+
+    /// Recenters on `target`, a part of the world in fractions of its size like `look_at`
+    /// takes, once it strays out of `view`; one too big to fit only once it's out of sight
+    /// altogether. The zoom is left to the user.
+    pub fn follow(&mut self, target: egui::Rect, canvas: egui::Rect, view: egui::Rect) {
+        let world = self.world_rect(canvas);
+        let on_screen = egui::Rect::from_min_max(
+            world.min + target.min.to_vec2() * world.size(),
+            world.min + target.max.to_vec2() * world.size(),
+        );
+        let fits = on_screen.width() <= view.width() && on_screen.height() <= view.height();
+        if view.contains_rect(on_screen) || (!fits && view.intersects(on_screen)) {
+            return;
+        }
+        self.look_at(target.center().to_vec2(), canvas, view);
+    }
+
+    pub fn pan_by(&mut self, delta: egui::Vec2, canvas: egui::Rect) {
+        self.offset += delta / canvas.size();
+    }
+
+    /// Scroll/pinch zooms around the pointer; middle-drag pans, as does primary-drag when
+    /// `drag_pans` (i.e. the sim doesn't use the primary button itself). `canvas` is the rect
+    /// the world covers at zoom 1.
+    pub fn navigate(
+        &mut self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        canvas: egui::Rect,
+        drag_pans: bool,
+    ) {
+        if response.hovered() {
+            let (scroll, pinch, pointer) =
+                ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta(), i.pointer.hover_pos()));
+            let factor = pinch * (scroll * 0.002).exp();
+            if factor != 1.0
+                && let Some(pos) = pointer
+            {
+                self.zoom_at(factor, pos, canvas);
+            }
+        }
+        if response.dragged_by(egui::PointerButton::Middle)
+            || (drag_pans && response.dragged_by(egui::PointerButton::Primary))
+        {
+            self.pan_by(response.drag_delta(), canvas);
+        }
+    }
+}
+
+/// A sim's world as the App is showing it: the camera, where the world sits at zoom 1
+/// and the canvas it's seen through. It converts between screen points and world
+/// units, which are the sim's cells (cell (x, y) covers `x..x + 1` by `y..y + 1`), and
+/// is what the App uses to draw vector views and to map pointer input onto the world.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub camera: Camera2D,
+    /// Where the world lands at zoom 1, from the App's [`CanvasFit`].
+    pub frame: egui::Rect,
+    /// The part of the screen the world is seen through.
+    pub canvas: egui::Rect,
+    pub dims: (usize, usize),
+}
+
+impl Viewport {
+    /// An unzoomed view with the world filling `rect`, for thumbnails and exports.
+    pub fn fixed(rect: egui::Rect, dims: (usize, usize)) -> Self {
+        Self { camera: Camera2D::default(), frame: rect, canvas: rect, dims }
+    }
+
+    /// Where the whole world lands on screen.
+    pub fn world_rect(&self) -> egui::Rect {
+        self.camera.world_rect(self.frame)
+    }
+
+    /// Screen points per world unit along x.
+    pub fn scale(&self) -> f32 {
+        self.world_rect().width() / self.dims.0.max(1) as f32
+    }
+
+    pub fn to_world(&self, pos: egui::Pos2) -> egui::Pos2 {
+        self.camera.screen_to_world(pos, self.frame, self.dims)
+    }
+
+    pub fn to_screen(&self, pos: egui::Pos2) -> egui::Pos2 {
+        self.camera.world_to_screen(pos, self.frame, self.dims)
+    }
+
+    /// The cell under screen position `pos`, if it's on the canvas and over the world.
+    pub fn cell_at(&self, pos: egui::Pos2) -> Option<(usize, usize)> {
+        let world = self.to_world(pos);
+        let (w, h) = (self.dims.0 as f32, self.dims.1 as f32);
+        let inside = world.x >= 0.0 && world.y >= 0.0 && world.x < w && world.y < h;
+        (self.canvas.contains(pos) && inside).then_some((world.x as usize, world.y as usize))
+    }
+
+    /// The world-space rect the canvas shows, which may reach past the world's edges.
+    pub fn visible(&self) -> egui::Rect {
+        egui::Rect::from_min_max(self.to_world(self.canvas.min), self.to_world(self.canvas.max))
+    }
+}
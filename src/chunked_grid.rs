@@ -0,0 +1,423 @@
+use eframe::egui;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::ops::Range;
+use crate::{
+    sim_params, Brush, Capabilities, Category, Grid, LayerInfo, Metrics, Params, SimConfig, SimError,
+    SimHelp, SimInfo, SimInput, Simulation,
+};
+
+pub const SAND_INFO: SimInfo = SimInfo {
+    description: "Falling sand on a grid of nearly a million cells, stepping only the chunks \
+                  where grains are still moving. Left-drag pours sand, right-drag builds walls.",
+    category: Category::CellularAutomata,
+    tags: &["grid", "falling-sand", "interactive"],
+};
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct ChunkState {
+    /// Stepped this step.
+    awake: bool,
+    /// Stepped next step, because something in or next to it changed.
+    wake_next: bool,
+    /// Changed since `render_dirty` last drew it.
+    dirty: bool,
+}
+
+/// A grid that keeps track of which 32 × 32 chunks have anything going on, so big worlds
+/// with mostly settled cells only pay for the parts that move.
+///
+/// A chunk is awake for a step if a cell in it, or a cell next to it, changed during the
+/// step before. Sims step only the cells of awake chunks (see
+/// [`awake_columns`](Self::awake_columns)), write through [`set`](Self::set), which only
+/// counts real changes, and call [`end_step`](Self::end_step) afterwards. Every chunk
+/// starts out awake. Cells are stored row-major like [`Grid`](crate::Grid)'s.
+///
+/// ```
+/// use simulation_station::ChunkedGrid;
+///
+/// let mut grid = ChunkedGrid::new(256, 256);
+/// grid.end_step();
+/// assert_eq!(grid.awake_count(), 0);
+///
+/// // The cell sits on a chunk corner, so the three chunks around it wake too
+/// grid.set(31, 31, 1u8);
+/// grid.end_step();
+/// assert_eq!(grid.awake_count(), 4);
+/// assert_eq!(grid.awake_columns(0).collect::<Vec<_>>(), vec![0..64]);
+/// ```
+#[derive(Clone)]
+pub struct ChunkedGrid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    chunks_x: usize,
+    chunks: Vec<ChunkState>,
+}
+
+impl<T: Clone + Default> ChunkedGrid<T> {
+    pub fn new(width: usize, height: usize) -> Self {
+        let (chunks_x, chunks_y) = (width.div_ceil(Self::CHUNK), height.div_ceil(Self::CHUNK));
+        let awake = ChunkState { awake: true, wake_next: false, dirty: true };
+        Self {
+            width,
+            height,
+            cells: vec![T::default(); width * height],
+            chunks_x,
+            chunks: vec![awake; chunks_x * chunks_y],
+        }
+    }
+}
+
+impl<T> ChunkedGrid<T> {
+    /// Cells per side of a chunk.
+    pub const CHUNK: usize = 32;
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &T {
+        &self.cells[y * self.width + x]
+    }
+
+    /// Row-major cell storage. Writing through [`set`](Self::set) instead keeps the chunks
+    /// up to date.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    fn chunk(&self, x: usize, y: usize) -> usize {
+        y / Self::CHUNK * self.chunks_x + x / Self::CHUNK
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn awake_count(&self) -> usize {
+        self.chunks.iter().filter(|chunk| chunk.awake).count()
+    }
+
+    /// Whether the chunk holding (x, y) is awake this step.
+    pub fn is_awake(&self, x: usize, y: usize) -> bool {
+        self.chunks[self.chunk(x, y)].awake
+    }
+
+    /// The spans of row `y` that lie in awake chunks, left to right, with neighboring
+    /// awake chunks merged into one span.
+    pub fn awake_columns(&self, y: usize) -> impl Iterator<Item = Range<usize>> + '_ {
+        let row = &self.chunks[y / Self::CHUNK * self.chunks_x..][..self.chunks_x];
+        let mut cx = 0;
+        std::iter::from_fn(move || {
+            cx += row[cx..].iter().position(|chunk| chunk.awake)?;
+            let start = cx;
+            cx += row[cx..].iter().position(|chunk| !chunk.awake).unwrap_or(row.len() - cx);
+            Some(start * Self::CHUNK..(cx * Self::CHUNK).min(self.width))
+        })
+    }
+
+    /// Moves on to the next step: the chunks woken during this one become the awake ones.
+    pub fn end_step(&mut self) {
+        for chunk in &mut self.chunks {
+            chunk.awake = std::mem::take(&mut chunk.wake_next);
+        }
+    }
+
+    /// Wakes and dirties every chunk, after changes made outside [`set`](Self::set).
+    pub fn wake_all(&mut self) {
+        for chunk in &mut self.chunks {
+            *chunk = ChunkState { awake: true, wake_next: true, dirty: true };
+        }
+    }
+
+    /// Fills every cell with `value` and wakes everything.
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        self.cells.fill(value);
+        self.wake_all();
+    }
+
+    /// Draws the chunks that changed since the last call into `pixels`, an RGB image the
+    /// size of the grid kept between calls, and returns how many it drew.
+    pub fn render_dirty(&mut self, pixels: &mut [u8], color: impl Fn(&T) -> [u8; 3]) -> usize {
+        let mut drawn = 0;
+        for (i, chunk) in self.chunks.iter_mut().enumerate().filter(|(_, chunk)| chunk.dirty) {
+            chunk.dirty = false;
+            drawn += 1;
+            let (x0, y0) = (i % self.chunks_x * Self::CHUNK, i / self.chunks_x * Self::CHUNK);
+            let x1 = (x0 + Self::CHUNK).min(self.width);
+            for y in y0..(y0 + Self::CHUNK).min(self.height) {
+                let row = y * self.width;
+                let cells = &self.cells[row + x0..row + x1];
+                let out = &mut pixels[(row + x0) * 3..(row + x1) * 3];
+                for (pixel, cell) in out.chunks_exact_mut(3).zip(cells) {
+                    pixel.copy_from_slice(&color(cell));
+                }
+            }
+        }
+        drawn
+    }
+}
+
+impl<T: PartialEq> ChunkedGrid<T> {
+    /// Writes `value` if it differs from what's there, then marks the cell's chunk dirty
+    /// and wakes it and whichever chunks border the cell for the next step.
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        let index = y * self.width + x;
+        if self.cells[index] == value {
+            return;
+        }
+        self.cells[index] = value;
+        let here = self.chunk(x, y);
+        self.chunks[here].dirty = true;
+        let (x0, x1) = (x.saturating_sub(1), (x + 1).min(self.width - 1));
+        let (y0, y1) = (y.saturating_sub(1), (y + 1).min(self.height - 1));
+        for cy in y0 / Self::CHUNK..=y1 / Self::CHUNK {
+            for cx in x0 / Self::CHUNK..=x1 / Self::CHUNK {
+                self.chunks[cy * self.chunks_x + cx].wake_next = true;
+            }
+        }
+    }
+}
+
+sim_params! {
+    pub struct SandParams {
+        pub spout: bool = true,
+        pub spout_rate: usize = 8 => 1..=64,
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Material {
+    #[default]
+    Air,
+    Sand,
+    Wall,
+}
+
+impl Material {
+    fn color(&self) -> [u8; 3] {
+        match self {
+            Material::Air => [16, 18, 28],
+            Material::Sand => [222, 190, 120],
+            Material::Wall => [110, 110, 120],
+        }
+    }
+}
+
+/// Falling sand on a ChunkedGrid: grains fall, or slide diagonally off whatever they land
+/// on, and chunks fall asleep once their grains settle.
+pub struct SandSim {
+    grid: ChunkedGrid<Material>,
+    /// The step each cell's grain last moved in, so a grain that falls ahead of the scan
+    /// isn't moved twice.
+    moved: Grid<u64>,
+    /// The grid drawn as RGB, redrawn a dirty chunk at a time.
+    pixels: Vec<u8>,
+    rng: StdRng,
+    step: u64,
+    version: u64,
+    params: SandParams,
+    brush: Brush<Material>,
+}
+
+impl SandSim {
+    const WIDTH: usize = 1024;
+    const HEIGHT: usize = 768;
+
+    pub fn new() -> Self {
+        let mut sim = Self {
+            grid: ChunkedGrid::new(Self::WIDTH, Self::HEIGHT),
+            moved: Grid::new(Self::WIDTH, Self::HEIGHT),
+            pixels: vec![0; Self::WIDTH * Self::HEIGHT * 3],
+            rng: StdRng::seed_from_u64(0),
+            step: 0,
+            version: 0,
+            params: SandParams::default(),
+            brush: Brush::new(Material::Sand, Material::Wall).with_size(6),
+        };
+        sim.reset(0);
+        sim
+    }
+
+    fn paint(&mut self, event: SimInput) {
+        let grid = &mut self.grid;
+        let painted = self.brush.apply(event, |x, y, &material| {
+            if (0..Self::WIDTH as isize).contains(&x) && (0..Self::HEIGHT as isize).contains(&y) {
+                grid.set(x as usize, y as usize, material);
+            }
+        });
+        if painted {
+            self.grid.render_dirty(&mut self.pixels, Material::color);
+            self.version += 1;
+        }
+    }
+
+    /// Moves the grain at (x, y) one cell down, or diagonally down if that's blocked.
+    fn fall(&mut self, x: usize, y: usize, left_first: bool) {
+        let below = y + 1;
+        let mut targets = [Some(x), None, None];
+        let (left, right) = (x.checked_sub(1), (x + 1 < Self::WIDTH).then_some(x + 1));
+        targets[1..].copy_from_slice(&if left_first { [left, right] } else { [right, left] });
+        let empty = |tx: usize| *self.grid.get(tx, below) == Material::Air;
+        if let Some(tx) = targets.into_iter().flatten().find(|&tx| empty(tx)) {
+            self.grid.set(x, y, Material::Air);
+            self.grid.set(tx, below, Material::Sand);
+            self.moved.set(tx, below, self.step);
+        }
+    }
+}
+
+impl Simulation for SandSim {
+    fn name(&self) -> &str {
+        "Falling Sand"
+    }
+
+    fn info(&self) -> SimInfo {
+        SAND_INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "Each step, every grain with air below it falls one cell, or slides \
+                       diagonally down when the cell below is taken. The world is split into \
+                       32 × 32 chunks and only chunks where something changed last step are \
+                       stepped or redrawn; the Awake chunks layer shows which.",
+            legend: vec![
+                (egui::Color32::from_rgb(222, 190, 120), "Sand"),
+                (egui::Color32::from_rgb(110, 110, 120), "Wall"),
+            ],
+            params: vec![
+                ("spout", "Pour sand in from the top"),
+                ("spout_rate", "Grains the spout adds each step"),
+            ],
+            controls: vec![
+                ("Left click / drag", "Pour sand"),
+                ("Right click / drag", "Build walls"),
+            ],
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            max_speed: 240.0,
+            default_speed: 60.0,
+            speed_unit: "steps/sec",
+            ..SimConfig::default()
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::INPUT
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (Self::WIDTH, Self::HEIGHT)
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        self.step += 1;
+        if self.params.spout {
+            for _ in 0..self.params.spout_rate {
+                let x = Self::WIDTH / 2 + self.rng.random_range(0..16) - 8;
+                self.grid.set(x, 0, Material::Sand);
+            }
+        }
+        // Top-down, so grains only fall into cells that were empty when the step began and
+        // a stack drains a grain at a time rather than sliding down all at once
+        let (mut spans, mut chunk_row) = (Vec::new(), usize::MAX);
+        for y in 0..Self::HEIGHT - 1 {
+            if y / ChunkedGrid::<Material>::CHUNK != chunk_row {
+                chunk_row = y / ChunkedGrid::<Material>::CHUNK;
+                spans.clear();
+                spans.extend(self.grid.awake_columns(y));
+            }
+            let left_first = (self.step + y as u64).is_multiple_of(2);
+            for span in &spans {
+                for x in span.clone() {
+                    if *self.grid.get(x, y) == Material::Sand && *self.moved.get(x, y) != self.step {
+                        self.fall(x, y, left_first);
+                    }
+                }
+            }
+        }
+        self.grid.end_step();
+        self.grid.render_dirty(&mut self.pixels, Material::color);
+        self.version += 1;
+        Ok(())
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.version)
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        buffer.copy_from_slice(&self.pixels);
+    }
+
+    fn layers(&self) -> Vec<LayerInfo> {
+        vec![LayerInfo { name: "Awake chunks".to_owned(), visible: false }]
+    }
+
+    fn render_layer(&self, _index: usize, buffer: &mut [u8]) {
+        for (y, row) in buffer.chunks_exact_mut(Self::WIDTH * 4).enumerate() {
+            for span in self.grid.awake_columns(y) {
+                for pixel in row[span.start * 4..span.end * 4].chunks_exact_mut(4) {
+                    pixel.copy_from_slice(&[255, 60, 60, 60]);
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.step = 0;
+        self.grid.fill(Material::Air);
+        self.moved.cells_mut().fill(0);
+        // A few ledges at random heights for the sand to pile up on and pour off
+        for _ in 0..6 {
+            let x = self.rng.random_range(0..Self::WIDTH - 200);
+            let y = self.rng.random_range(150..Self::HEIGHT - 50);
+            let slope = self.rng.random_range(-0.4..0.4f32);
+            for dx in 0..200 {
+                let ly = (y as f32 + dx as f32 * slope) as usize;
+                self.grid.set(x + dx, ly.min(Self::HEIGHT - 1), Material::Wall);
+            }
+        }
+        self.grid.render_dirty(&mut self.pixels, Material::color);
+        self.version += 1;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!("Step: {}", self.step));
+        ui.label(format!("Awake chunks: {} of {}", self.grid.awake_count(), self.grid.chunk_count()));
+        self.brush.ui(ui);
+    }
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+
+    fn record_metrics(&self, metrics: &mut Metrics) {
+        metrics.record("awake chunks", self.grid.awake_count() as f64);
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        let name = match self.grid.get(x, y) {
+            Material::Air => "air",
+            Material::Sand => "sand",
+            Material::Wall => "wall",
+        };
+        Some(name.to_owned())
+    }
+
+    fn handle_input(&mut self, event: SimInput) {
+        self.paint(event);
+    }
+}
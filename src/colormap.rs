@@ -0,0 +1,366 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+use crate::{Grid, ParamWidget};
+
+// Evenly spaced samples of matplotlib's perceptually uniform maps, interpolated linearly
+const VIRIDIS: [[u8; 3]; 9] = [
+    [0x44, 0x01, 0x54], [0x47, 0x2c, 0x7a], [0x3b, 0x51, 0x8b], [0x2c, 0x71, 0x8e], [0x21, 0x90, 0x8d],
+    [0x27, 0xad, 0x81], [0x5c, 0xc8, 0x63], [0xaa, 0xdc, 0x32], [0xfd, 0xe7, 0x25],
+];
+const MAGMA: [[u8; 3]; 9] = [
+    [0x00, 0x00, 0x04], [0x1c, 0x10, 0x44], [0x4f, 0x12, 0x7b], [0x81, 0x25, 0x81], [0xb5, 0x36, 0x7a],
+    [0xe5, 0x50, 0x64], [0xfb, 0x87, 0x61], [0xfe, 0xc2, 0x87], [0xfc, 0xfd, 0xbf],
+];
+const INFERNO: [[u8; 3]; 9] = [
+    [0x00, 0x00, 0x04], [0x1f, 0x0c, 0x48], [0x55, 0x0f, 0x6d], [0x88, 0x22, 0x6a], [0xba, 0x36, 0x55],
+    [0xe3, 0x59, 0x33], [0xf9, 0x8e, 0x09], [0xf9, 0xcb, 0x35], [0xfc, 0xff, 0xa4],
+];
+const PLASMA: [[u8; 3]; 9] = [
+    [0x0d, 0x08, 0x87], [0x4c, 0x02, 0xa1], [0x7e, 0x03, 0xa8], [0xa9, 0x23, 0x95], [0xcc, 0x47, 0x78],
+    [0xe5, 0x6b, 0x5d], [0xf8, 0x94, 0x41], [0xfd, 0xc3, 0x28], [0xf0, 0xf9, 0x21],
+];
+const GRAYSCALE: [[u8; 3]; 2] = [[0, 0, 0], [255, 255, 255]];
+
+/// Maps scalar values in [0, 1] to colors, for sims that draw a field. Usable as a
+/// `sim_params!` field, which gives the sim a colormap selector in its parameter panel.
+///
+/// ```
+/// use simulation_station::Colormap;
+///
+/// assert_eq!(Colormap::Grayscale.map(0.5), [128, 128, 128]);
+/// assert_eq!(Colormap::Custom(vec![[255, 0, 0], [0, 0, 255]]).map(1.0), [0, 0, 255]);
+/// ```
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum Colormap {
+    #[default]
+    Grayscale,
+    Viridis,
+    Magma,
+    Inferno,
+    Plasma,
+    /// Evenly spaced colors from 0 to 1, blended linearly in between.
+    Custom(Vec<[u8; 3]>),
+}
+
+impl Colormap {
+    pub const BUILTIN: [Colormap; 5] =
+        [Colormap::Grayscale, Colormap::Viridis, Colormap::Magma, Colormap::Inferno, Colormap::Plasma];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Grayscale => "Grayscale",
+            Colormap::Viridis => "Viridis",
+            Colormap::Magma => "Magma",
+            Colormap::Inferno => "Inferno",
+            Colormap::Plasma => "Plasma",
+            Colormap::Custom(_) => "Custom",
+        }
+    }
+
+    fn stops(&self) -> &[[u8; 3]] {
+        match self {
+            Colormap::Grayscale => &GRAYSCALE,
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Inferno => &INFERNO,
+            Colormap::Plasma => &PLASMA,
+            Colormap::Custom(stops) if stops.is_empty() => &GRAYSCALE,
+            Colormap::Custom(stops) => stops,
+        }
+    }
+
+    /// The color for `value`, clamped to [0, 1]; NaN maps like 0.
+    pub fn map(&self, value: f32) -> [u8; 3] {
+        let stops = self.stops();
+        let t = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) } * (stops.len() - 1) as f32;
+        let i = (t as usize).min(stops.len().saturating_sub(2));
+        let (a, b) = (stops[i], stops[(i + 1).min(stops.len() - 1)]);
+        let f = t - i as f32;
+        std::array::from_fn(|c| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * f).round() as u8)
+    }
+
+    pub fn color(&self, value: f32) -> egui::Color32 {
+        let [r, g, b] = self.map(value);
+        egui::Color32::from_rgb(r, g, b)
+    }
+
+    /// 256 precomputed colors, for rendering loops that map every cell.
+    pub fn lut(&self) -> [[u8; 3]; 256] {
+        std::array::from_fn(|i| self.map(i as f32 / 255.0))
+    }
+
+    /// The low and high ends, for a sim's help legend.
+    pub fn legend(&self, low: &'static str, high: &'static str) -> Vec<(egui::Color32, &'static str)> {
+        vec![(self.color(0.0), low), (self.color(1.0), high)]
+    }
+
+    /// Colors every cell of `field` into an RGB `buffer`, mapping values through `scale`.
+    /// Returns the (min, max) range the colors span, for labeling a legend.
+    ///
+    /// ```
+    /// use simulation_station::{Colormap, FieldScale, Grid};
+    ///
+    /// let mut field = Grid::new(2, 1);
+    /// field.set(0, 0, 10.0);
+    /// field.set(1, 0, 20.0);
+    /// let mut buffer = [0; 6];
+    /// let range = Colormap::Grayscale.render_field(&field, &FieldScale::AUTO, &mut buffer);
+    /// assert_eq!(range, (10.0, 20.0));
+    /// assert_eq!(buffer, [0, 0, 0, 255, 255, 255]);
+    /// ```
+    pub fn render_field(&self, field: &Grid<f32>, scale: &FieldScale, buffer: &mut [u8]) -> (f32, f32) {
+        let (min, max) = scale.range(field.cells());
+        let lut = self.lut();
+        for (pixel, &v) in buffer.chunks_exact_mut(3).zip(field.cells()) {
+            pixel.copy_from_slice(&lut[(scale.normalize(v, min, max) * 255.0).round() as usize]);
+        }
+        (min, max)
+    }
+
+    /// Paints the map across `rect`, left to right.
+    pub fn paint_gradient(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let steps = rect.width().max(1.0) as usize;
+        let step = rect.width() / steps as f32;
+        for i in 0..steps {
+            let x = rect.left() + i as f32 * step;
+            let cell = egui::Rect::from_x_y_ranges(x..=x + step + 0.5, rect.y_range());
+            painter.rect_filled(cell, 0.0, self.color(i as f32 / (steps - 1).max(1) as f32));
+        }
+    }
+}
+
+/// A dropdown of the built-in maps with a preview of each, and color pickers for the
+/// stops of a custom one.
+impl ParamWidget for Colormap {
+    fn widget(&mut self, ui: &mut egui::Ui, label: &str, _range: Option<RangeInclusive<Self>>) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt(label).selected_text(self.name()).show_ui(ui, |ui| {
+                let custom = match self {
+                    Colormap::Custom(_) => self.clone(),
+                    _ => Colormap::Custom(self.stops().to_vec()),
+                };
+                for map in Colormap::BUILTIN.into_iter().chain([custom]) {
+                    ui.horizontal(|ui| {
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(48.0, 12.0), egui::Sense::hover());
+                        map.paint_gradient(ui.painter(), rect);
+                        let selected = self.name() == map.name();
+                        if ui.selectable_label(selected, map.name()).clicked() && !selected {
+                            *self = map;
+                            changed = true;
+                        }
+                    });
+                }
+            });
+            ui.label(label);
+        });
+        if let Colormap::Custom(stops) = self {
+            ui.horizontal_wrapped(|ui| {
+                for stop in stops.iter_mut() {
+                    changed |= ui.color_edit_button_srgb(stop).changed();
+                }
+                if ui.small_button("+").on_hover_text("Add a color").clicked() {
+                    stops.push(stops.last().copied().unwrap_or([255, 255, 255]));
+                    changed = true;
+                }
+                if stops.len() > 2
+                    && ui.small_button("−").on_hover_text("Remove the last color").clicked()
+                {
+                    stops.pop();
+                    changed = true;
+                }
+            });
+        }
+        changed
+    }
+}
+
+/// The 256 colors the App gives the indices of a `PixelFormat::Indexed` sim, and how fast
+/// they rotate. Sims suggest one with `Simulation::palette`; the user's changes are
+/// remembered per sim, so the same indices can be recolored without touching the sim.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    pub colormap: Colormap,
+    /// Palette entries the colors move by per second, for classic color-cycling effects.
+    /// Negative values cycle the other way.
+    pub cycle: f32,
+}
+
+impl Palette {
+    pub fn new(colormap: Colormap) -> Self {
+        Self { colormap, cycle: 0.0 }
+    }
+
+    pub fn cycling(self, cycle: f32) -> Self {
+        Self { cycle, ..self }
+    }
+
+    /// How far the colors have rotated `time` seconds in.
+    pub fn shift(&self, time: f64) -> u8 {
+        (time * self.cycle as f64).rem_euclid(256.0) as u8
+    }
+
+    /// The color of every index, rotated by `shift`.
+    pub fn lut(&self, shift: u8) -> [[u8; 3]; 256] {
+        let lut = self.colormap.lut();
+        std::array::from_fn(|i| lut[(i + shift as usize) % 256])
+    }
+
+    /// The colormap picker and cycling speed. Returns whether either changed.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = self.colormap.widget(ui, "Colors", None);
+        changed |= ui
+            .add(egui::Slider::new(&mut self.cycle, -120.0..=120.0).text("Cycle speed"))
+            .on_hover_text("Palette entries per second the colors rotate by")
+            .changed();
+        changed
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new(Colormap::Grayscale)
+    }
+}
+
+/// A colormap spread over a range of values, which the App shows as a labeled color bar
+/// in the corner of the canvas. Sims report theirs through `Simulation::color_scale`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorScale {
+    pub colormap: Colormap,
+    pub min: f32,
+    pub max: f32,
+    /// Whether the colors follow the logarithm of the value, as with [`FieldScale::log`].
+    pub log: bool,
+    /// Shown under the bar, e.g. "°C"; empty for unitless values.
+    pub units: &'static str,
+}
+
+impl ColorScale {
+    /// The scale `render_field` uses to draw `field`, auto ranges included.
+    pub fn for_field(
+        colormap: &Colormap,
+        scale: &FieldScale,
+        field: &Grid<f32>,
+        units: &'static str,
+    ) -> Self {
+        let (min, max) = scale.range(field.cells());
+        Self { colormap: colormap.clone(), min, max, log: scale.log, units }
+    }
+
+    /// Paints the bar with its end values and units in the bottom-right corner of `canvas`.
+    pub(crate) fn paint(&self, painter: &egui::Painter, canvas: egui::Rect) {
+        const WIDTH: f32 = 160.0;
+        let font = egui::FontId::monospace(11.0);
+        let label = |value: f32| {
+            let text = if value != 0.0 && (value.abs() >= 1e4 || value.abs() < 1e-2) {
+                format!("{value:.2e}")
+            } else {
+                format!("{value:.2}")
+            };
+            painter.layout_no_wrap(text, font.clone(), egui::Color32::WHITE)
+        };
+        let (low, high) = (label(self.min), label(self.max));
+        let caption = match (self.units, self.log) {
+            (units, false) => units.to_owned(),
+            ("", true) => "log".to_owned(),
+            (units, true) => format!("{units}, log"),
+        };
+        let units = painter.layout_no_wrap(caption, font.clone(), egui::Color32::WHITE);
+        let text_height = low.size().y;
+
+        let backdrop = egui::Rect::from_min_max(
+            canvas.right_bottom() - egui::vec2(WIDTH + 16.0, 14.0 + text_height + 14.0),
+            canvas.right_bottom() - egui::vec2(6.0, 6.0),
+        );
+        painter.rect_filled(backdrop, 3.0, egui::Color32::from_black_alpha(180));
+        let bar_min = backdrop.min + egui::vec2(5.0, 5.0);
+        let bar = egui::Rect::from_min_size(bar_min, egui::vec2(WIDTH, 10.0));
+        self.colormap.paint_gradient(painter, bar);
+        let y = bar.bottom() + 3.0;
+        painter.galley(egui::pos2(bar.left(), y), low, egui::Color32::WHITE);
+        let x = bar.center().x - units.size().x / 2.0;
+        painter.galley(egui::pos2(x, y), units, egui::Color32::WHITE);
+        painter.galley(egui::pos2(bar.right() - high.size().x, y), high, egui::Color32::WHITE);
+    }
+}
+
+/// How [`Colormap::render_field`] turns field values into positions along the map. As a
+/// `sim_params!` field it lets the user switch between fitting the data and a fixed range.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldScale {
+    /// Stretch the map over each frame's smallest and largest finite value.
+    pub auto: bool,
+    /// The values mapped to the ends of the map when `auto` is off.
+    pub min: f32,
+    pub max: f32,
+    /// Map the logarithm of values, for fields spanning orders of magnitude. Values at
+    /// or below zero take the low end.
+    pub log: bool,
+}
+
+impl FieldScale {
+    pub const AUTO: FieldScale = FieldScale { auto: true, min: 0.0, max: 1.0, log: false };
+
+    pub const fn fixed(min: f32, max: f32) -> Self {
+        FieldScale { auto: false, min, max, log: false }
+    }
+
+    pub const fn log(self) -> Self {
+        FieldScale { log: true, ..self }
+    }
+
+    /// The range values are mapped over: the configured one, or the extent of `values`
+    /// (only their positive part under a log scale).
+    pub fn range(&self, values: &[f32]) -> (f32, f32) {
+        if !self.auto {
+            return (self.min, self.max);
+        }
+        let mut range = (f32::INFINITY, f32::NEG_INFINITY);
+        for &v in values.iter().filter(|v| v.is_finite() && (!self.log || **v > 0.0)) {
+            range = (range.0.min(v), range.1.max(v));
+        }
+        if range.0 > range.1 { (self.min, self.max) } else { range }
+    }
+
+    /// Where `value` falls between `min` and `max`, from 0 to 1.
+    pub fn normalize(&self, value: f32, min: f32, max: f32) -> f32 {
+        let (value, min, max) = if self.log {
+            let floor = f32::MIN_POSITIVE;
+            (value.max(floor).ln(), min.max(floor).ln(), max.max(floor).ln())
+        } else {
+            (value, min, max)
+        };
+        let t = if max > min { (value - min) / (max - min) } else { 0.5 };
+        if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) }
+    }
+}
+
+impl Default for FieldScale {
+    fn default() -> Self {
+        Self::AUTO
+    }
+}
+
+/// An auto toggle, min/max drag values while it's off, and a log toggle.
+impl ParamWidget for FieldScale {
+    fn widget(&mut self, ui: &mut egui::Ui, label: &str, _range: Option<RangeInclusive<Self>>) -> bool {
+        ui.horizontal_wrapped(|ui| {
+            let mut changed = ui.checkbox(&mut self.auto, "auto").changed();
+            if !self.auto {
+                let speed = ((self.max - self.min).abs() / 100.0).max(0.001);
+                for (value, prefix) in [(&mut self.min, "min "), (&mut self.max, "max ")] {
+                    changed |= ui.add(egui::DragValue::new(value).speed(speed).prefix(prefix)).changed();
+                }
+            }
+            changed |= ui.checkbox(&mut self.log, "log").changed();
+            ui.label(label);
+            changed
+        })
+        .inner
+    }
+}
@@ -0,0 +1,149 @@
+use eframe::egui;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::{
+    sim_params, Category, ColorScale, Colormap, FieldScale, Grid3, Neighborhood, Params, SimConfig,
+    SimError, SimHelp, SimInfo, Simulation, TextureFilter, VolumeView,
+};
+
+pub const INFO: SimInfo = SimInfo {
+    description: "Heat spreading through a cube from a few hot spots, shown a slice at a time \
+                  or as a maximum intensity projection.",
+    category: Category::Physics,
+    tags: &["3d", "diffusion", "heat"],
+};
+
+const SIZE: usize = 64;
+
+sim_params! {
+    pub struct DiffusionParams {
+        pub rate: f32 = 0.12 => 0.0..=0.16,
+        pub sources: usize = 6 => 1..=32,
+        pub colormap: Colormap = Colormap::Inferno,
+        pub scale: FieldScale = FieldScale::AUTO,
+    }
+}
+
+/// Explicit finite-difference heat diffusion on a `SIZE`³ grid whose faces are held at 0.
+pub struct Diffusion3D {
+    heat: Grid3<f32>,
+    next: Grid3<f32>,
+    view: VolumeView,
+    params: DiffusionParams,
+}
+
+impl Diffusion3D {
+    pub fn new() -> Self {
+        let mut sim = Self {
+            heat: Grid3::new(SIZE, SIZE, SIZE),
+            next: Grid3::new(SIZE, SIZE, SIZE),
+            view: VolumeView { slice: SIZE / 2, ..VolumeView::default() },
+            params: DiffusionParams::default(),
+        };
+        sim.reset(0);
+        sim
+    }
+}
+
+impl Simulation for Diffusion3D {
+    fn name(&self) -> &str {
+        "3D Heat Diffusion"
+    }
+
+    fn info(&self) -> SimInfo {
+        INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "Each step moves heat between every cell and its six neighbors in a cube, \
+                       starting from hot balls placed from the seed. The faces of the cube stay \
+                       cold, so the heat slowly drains away. Pick the axis to look along and \
+                       either scrub through slices or see the hottest cell along each line.",
+            legend: self.params.colormap.legend("Cold", "Hot"),
+            params: vec![
+                ("rate", "Fraction of the difference to each neighbor exchanged per step"),
+                ("sources", "Hot balls placed on reset"),
+                ("colormap", "Colors the temperatures"),
+                ("scale", "Temperatures at the ends of the colormap"),
+            ],
+            controls: Vec::new(),
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            max_speed: 240.0,
+            default_speed: 30.0,
+            texture_filter: TextureFilter::Linear,
+            upscale: 4,
+            ..SimConfig::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        self.view.dimensions(&self.heat)
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        let rate = self.params.rate;
+        let last = SIZE - 1;
+        for z in 0..SIZE {
+            for y in 0..SIZE {
+                for x in 0..SIZE {
+                    let value = if x == 0 || y == 0 || z == 0 || x == last || y == last || z == last {
+                        0.0
+                    } else {
+                        let here = *self.heat.get(x, y, z);
+                        let neighbors: f32 =
+                            self.heat.neighbors(x, y, z, Neighborhood::VonNeumann).sum();
+                        here + rate * (neighbors - 6.0 * here)
+                    };
+                    self.next.set(x, y, z, value);
+                }
+            }
+        }
+        std::mem::swap(&mut self.heat, &mut self.next);
+        Ok(())
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        self.view.render(&self.heat, &self.params.colormap, &self.params.scale, buffer);
+    }
+
+    fn color_scale(&self) -> Option<ColorScale> {
+        let image = self.view.project(&self.heat);
+        Some(ColorScale::for_field(&self.params.colormap, &self.params.scale, &image, ""))
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        let (cx, cy, cz) = self.view.cell_at(&self.heat, x, y);
+        Some(format!("({cx}, {cy}, {cz}) {:.3}", self.heat.get(cx, cy, cz)))
+    }
+
+    fn reset(&mut self, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.heat.cells_mut().fill(0.0);
+        for _ in 0..self.params.sources {
+            let center: [f32; 3] = std::array::from_fn(|_| rng.random_range(12.0..SIZE as f32 - 12.0));
+            let radius: f32 = rng.random_range(3.0..8.0);
+            for z in 0..SIZE {
+                for y in 0..SIZE {
+                    for x in 0..SIZE {
+                        let d = [x as f32 - center[0], y as f32 - center[1], z as f32 - center[2]];
+                        if d.iter().map(|d| d * d).sum::<f32>() < radius * radius {
+                            self.heat.set(x, y, z, 1.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        self.view.ui(ui, &self.heat);
+    }
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+}
@@ -0,0 +1,79 @@
+use egui_dock::{DockState, NodeIndex};
+use serde::{Deserialize, Serialize};
+
+/// The dockable panels the App's window is built from. The layout is saved with the
+/// rest of the settings.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub(crate) enum Panel {
+    Canvas,
+    Browser,
+    Controls,
+    Parameters,
+    Stats,
+    /// Whatever the focused sim draws in `Simulation::ui`.
+    SimUi,
+    /// Recorded frames and the scrubber for replaying them.
+    History,
+    /// Messages sims wrote through `SimLogger`.
+    Console,
+    /// Plots of the values sims report through `record_metrics`.
+    Metrics,
+}
+
+impl Panel {
+    pub(crate) const ALL: [Panel; 9] = [
+        Panel::Canvas,
+        Panel::Browser,
+        Panel::Controls,
+        Panel::Parameters,
+        Panel::Stats,
+        Panel::SimUi,
+        Panel::History,
+        Panel::Console,
+        Panel::Metrics,
+    ];
+
+    pub(crate) fn title(self) -> &'static str {
+        match self {
+            Panel::Canvas => "Canvas",
+            Panel::Browser => "Simulations",
+            Panel::Controls => "Controls",
+            Panel::Parameters => "Parameters",
+            Panel::Stats => "Stats",
+            Panel::SimUi => "Simulation",
+            Panel::History => "History",
+            Panel::Console => "Console",
+            Panel::Metrics => "Metrics",
+        }
+    }
+
+    /// Whether the panel can be closed. The canvas can't; the App draws the HUD over it.
+    pub(crate) fn closeable(self) -> bool {
+        self != Panel::Canvas
+    }
+}
+
+/// Browser and controls on the left, the canvas in the middle with the console and metrics below it,
+/// and the sim's own UI, parameters, stats and history tabbed on the right.
+pub(crate) fn default_layout() -> DockState<Panel> {
+    let mut dock = DockState::new(vec![Panel::Canvas]);
+    let tree = dock.main_surface_mut();
+    let [canvas, left] = tree.split_left(NodeIndex::root(), 0.24, vec![Panel::Controls]);
+    tree.split_above(left, 0.45, vec![Panel::Browser]);
+    let right = vec![Panel::SimUi, Panel::Parameters, Panel::Stats, Panel::History];
+    let [canvas, _] = tree.split_right(canvas, 0.7, right);
+    tree.split_below(canvas, 0.8, vec![Panel::Console, Panel::Metrics]);
+    dock
+}
+
+/// Closes `panel` if it's open anywhere and [closeable](Panel::closeable), otherwise opens
+/// it in the focused leaf.
+pub(crate) fn toggle(dock: &mut DockState<Panel>, panel: Panel) {
+    match dock.find_tab(&panel) {
+        Some(location) if panel.closeable() => {
+            dock.remove_tab(location);
+        }
+        Some(_) => {}
+        None => dock.push_to_focused_leaf(panel),
+    }
+}
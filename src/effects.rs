@@ -0,0 +1,176 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// Post-processing applied to a raster frame before it's shown. Every effect is off at
+/// its default value. Sims suggest their own through `SimConfig::effects`; the user's
+/// changes are remembered per sim.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Effects {
+    /// How much of the previous frame lingers, 0 to 1. Bright pixels fade out over
+    /// several frames instead of vanishing, leaving trails behind moving things.
+    pub trails: f32,
+    /// Strength of the glow around bright pixels.
+    pub bloom: f32,
+    /// Brightness, 0 to 1, above which pixels glow.
+    pub bloom_threshold: f32,
+    /// How much every other row is darkened, for a CRT look.
+    pub scanlines: f32,
+    /// Output gamma; values above 1 brighten dark tones.
+    pub gamma: f32,
+}
+
+impl Effects {
+    pub const NONE: Effects =
+        Effects { trails: 0.0, bloom: 0.0, bloom_threshold: 0.7, scanlines: 0.0, gamma: 1.0 };
+
+    pub fn is_none(&self) -> bool {
+        self.trails == 0.0 && self.bloom == 0.0 && self.scanlines == 0.0 && self.gamma == 1.0
+    }
+
+    /// Sliders for each effect. Returns whether any changed.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        changed |= ui.add(egui::Slider::new(&mut self.trails, 0.0..=0.98).text("Trails")).changed();
+        changed |= ui.add(egui::Slider::new(&mut self.bloom, 0.0..=2.0).text("Bloom")).changed();
+        ui.add_enabled_ui(self.bloom > 0.0, |ui| {
+            changed |= ui
+                .add(egui::Slider::new(&mut self.bloom_threshold, 0.0..=1.0).text("Bloom threshold"))
+                .changed();
+        });
+        changed |= ui.add(egui::Slider::new(&mut self.scanlines, 0.0..=1.0).text("Scanlines")).changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut self.gamma, 0.25..=4.0).logarithmic(true).text("Gamma"))
+            .changed();
+        if ui.button("No effects").clicked() {
+            *self = Self::NONE;
+            changed = true;
+        }
+        changed
+    }
+}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Runs [`Effects`] over a slot's frames, keeping the last output for trails.
+#[derive(Default)]
+pub(crate) struct PostProcessor {
+    previous: Option<egui::ColorImage>,
+}
+
+impl PostProcessor {
+    pub(crate) fn clear(&mut self) {
+        self.previous = None;
+    }
+
+    pub(crate) fn apply(&mut self, effects: &Effects, mut image: egui::ColorImage) -> egui::ColorImage {
+        if effects.trails > 0.0 {
+            if let Some(previous) = self.previous.as_ref().filter(|p| p.size == image.size) {
+                trails(&mut image, previous, effects.trails);
+            }
+            self.previous = Some(image.clone());
+        } else {
+            self.previous = None;
+        }
+        if effects.bloom > 0.0 {
+            bloom(&mut image, effects.bloom, effects.bloom_threshold);
+        }
+        if effects.scanlines > 0.0 {
+            scanlines(&mut image, effects.scanlines);
+        }
+        if effects.gamma != 1.0 {
+            gamma(&mut image, effects.gamma);
+        }
+        image
+    }
+}
+
+/// Keeps the brighter of each pixel and its faded predecessor.
+fn trails(image: &mut egui::ColorImage, previous: &egui::ColorImage, keep: f32) {
+    for (pixel, old) in image.pixels.iter_mut().zip(&previous.pixels) {
+        let fade = |now: u8, then: u8| now.max((then as f32 * keep) as u8);
+        *pixel = egui::Color32::from_rgb(
+            fade(pixel.r(), old.r()),
+            fade(pixel.g(), old.g()),
+            fade(pixel.b(), old.b()),
+        );
+    }
+}
+
+/// Adds a blurred copy of the pixels brighter than `threshold`.
+fn bloom(image: &mut egui::ColorImage, strength: f32, threshold: f32) {
+    let [w, h] = image.size;
+    let radius = (w.max(h) / 100).clamp(2, 16);
+    let cutoff = threshold * 255.0;
+    let mut glow: Vec<[f32; 3]> = image
+        .pixels
+        .iter()
+        .map(|c| {
+            let (r, g, b) = (c.r() as f32, c.g() as f32, c.b() as f32);
+            if r.max(g).max(b) > cutoff { [r, g, b] } else { [0.0; 3] }
+        })
+        .collect();
+    box_blur(&mut glow, w, h, radius, 1, w);
+    box_blur(&mut glow, h, w, radius, w, 1);
+    for (pixel, glow) in image.pixels.iter_mut().zip(&glow) {
+        let add = |c: u8, g: f32| (c as f32 + g * strength).min(255.0) as u8;
+        *pixel = egui::Color32::from_rgb(
+            add(pixel.r(), glow[0]),
+            add(pixel.g(), glow[1]),
+            add(pixel.b(), glow[2]),
+        );
+    }
+}
+
+/// Blurs `lines` runs of `len` values, `step` apart within a run and `stride` apart
+/// between runs, with a running box sum of width `2 * radius + 1`.
+fn box_blur(data: &mut [[f32; 3]], len: usize, lines: usize, radius: usize, step: usize, stride: usize) {
+    let mut line = vec![[0.0; 3]; len];
+    let width = (2 * radius + 1) as f32;
+    for l in 0..lines {
+        let at = |i: usize| l * stride + i * step;
+        for (i, value) in line.iter_mut().enumerate() {
+            *value = data[at(i)];
+        }
+        let mut sum = [0.0f32; 3];
+        for value in line.iter().take(radius + 1) {
+            (0..3).for_each(|c| sum[c] += value[c]);
+        }
+        for i in 0..len {
+            data[at(i)] = sum.map(|s| s / width);
+            if i + radius + 1 < len {
+                (0..3).for_each(|c| sum[c] += line[i + radius + 1][c]);
+            }
+            if i >= radius {
+                (0..3).for_each(|c| sum[c] -= line[i - radius][c]);
+            }
+        }
+    }
+}
+
+fn scanlines(image: &mut egui::ColorImage, darkness: f32) {
+    let [w, _] = image.size;
+    let keep = 1.0 - darkness;
+    for row in image.pixels.chunks_exact_mut(w.max(1)).skip(1).step_by(2) {
+        for pixel in row {
+            let dim = |c: u8| (c as f32 * keep) as u8;
+            *pixel = egui::Color32::from_rgb(dim(pixel.r()), dim(pixel.g()), dim(pixel.b()));
+        }
+    }
+}
+
+fn gamma(image: &mut egui::ColorImage, gamma: f32) {
+    let lut: [u8; 256] =
+        std::array::from_fn(|i| ((i as f32 / 255.0).powf(1.0 / gamma) * 255.0).round() as u8);
+    for pixel in &mut image.pixels {
+        *pixel = egui::Color32::from_rgb(
+            lut[pixel.r() as usize],
+            lut[pixel.g() as usize],
+            lut[pixel.b() as usize],
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use eframe::egui;
+use image::codecs::gif::{GifEncoder, Repeat};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::PixelFormat;
+
+/// Lowercase ASCII version of a sim name that is safe to use in file names.
+pub(crate) fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect()
+}
+
+/// The current UTC time as `YYYYMMDD-HHMMSS`.
+pub(crate) fn timestamp() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01, after Howard Hinnant's `civil_from_days`
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        rest / 3600,
+        rest / 60 % 60,
+        rest % 60
+    )
+}
+
+/// Default file name for an export from `sim_name`, e.g. `pixel_fill-20250101-120000.png`.
+pub(crate) fn file_name(sim_name: &str, extension: &str) -> String {
+    format!("{}-{}.{extension}", slug(sim_name), timestamp())
+}
+
+/// Writes a `render()` buffer to `path` as a PNG at the sim's own resolution. RGBA buffers
+/// keep their transparency; indexed ones come out as their raw indices in grayscale.
+pub(crate) fn save_png(
+    path: &Path,
+    size: (usize, usize),
+    format: PixelFormat,
+    buffer: &[u8],
+) -> Result<(), String> {
+    let (w, h) = (size.0 as u32, size.1 as u32);
+    let color = match format {
+        PixelFormat::Rgb => image::ExtendedColorType::Rgb8,
+        PixelFormat::Rgba => image::ExtendedColorType::Rgba8,
+        PixelFormat::Indexed => image::ExtendedColorType::L8,
+    };
+    image::save_buffer_with_format(path, buffer, w, h, color, image::ImageFormat::Png)
+        .map_err(|e| e.to_string())
+}
+
+/// Collects frames while recording and encodes them into an animated GIF when stopped.
+/// Encoding runs on its own thread so long clips don't stall the UI.
+pub(crate) struct GifRecorder {
+    /// Frames captured per second of wall time, which is also the playback rate.
+    pub(crate) fps: f32,
+    /// Output size relative to the sim's resolution.
+    pub(crate) scale: f32,
+    frames: Vec<image::RgbaImage>,
+    recording: bool,
+    next_capture: f64,
+    encoding: Option<Receiver<Result<PathBuf, String>>>,
+}
+
+impl GifRecorder {
+    /// Frames kept before capturing stops on its own, to bound memory.
+    const MAX_FRAMES: usize = 2000;
+
+    pub(crate) fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    pub(crate) fn is_encoding(&self) -> bool {
+        self.encoding.is_some()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub(crate) fn start(&mut self) {
+        self.frames.clear();
+        self.recording = true;
+        self.next_capture = 0.0;
+    }
+
+    /// Keeps `image` if a frame is due at `time` seconds. Frames are scaled to the size of
+    /// the first one so the clip stays consistent if the sim changes resolution.
+    pub(crate) fn capture(&mut self, image: &egui::ColorImage, time: f64) {
+        if !self.recording || time < self.next_capture || self.frames.len() >= Self::MAX_FRAMES {
+            return;
+        }
+        self.next_capture = time.max(self.next_capture) + 1.0 / self.fps as f64;
+        let [w, h] = image.size;
+        let pixels = image.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied()).collect();
+        let Some(frame) = image::RgbaImage::from_raw(w as u32, h as u32, pixels) else { return };
+        let (out_w, out_h) = match self.frames.first() {
+            Some(first) => first.dimensions(),
+            None => (
+                ((w as f32 * self.scale).round() as u32).max(1),
+                ((h as f32 * self.scale).round() as u32).max(1),
+            ),
+        };
+        let frame = if (out_w, out_h) == frame.dimensions() {
+            frame
+        } else {
+            image::imageops::resize(&frame, out_w, out_h, image::imageops::FilterType::Nearest)
+        };
+        self.frames.push(frame);
+    }
+
+    /// Stops recording and starts encoding the captured frames to `path`. Passing `None`
+    /// throws the frames away.
+    pub(crate) fn stop(&mut self, path: Option<PathBuf>) {
+        self.recording = false;
+        let frames = std::mem::take(&mut self.frames);
+        let Some(path) = path.filter(|_| !frames.is_empty()) else { return };
+        let delay = image::Delay::from_numer_denom_ms(1000, self.fps.round().max(1.0) as u32);
+        let (tx, rx) = channel();
+        std::thread::spawn(move || {
+            let result = encode_gif(&path, frames, delay).map(|()| path);
+            let _ = tx.send(result);
+        });
+        self.encoding = Some(rx);
+    }
+
+    /// The outcome of the last encode, once it has finished.
+    pub(crate) fn poll(&mut self) -> Option<Result<PathBuf, String>> {
+        let result = match self.encoding.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("the encoder stopped unexpectedly".to_owned()),
+        };
+        self.encoding = None;
+        Some(result)
+    }
+}
+
+impl Default for GifRecorder {
+    fn default() -> Self {
+        Self {
+            fps: 15.0,
+            scale: 1.0,
+            frames: Vec::new(),
+            recording: false,
+            next_capture: 0.0,
+            encoding: None,
+        }
+    }
+}
+
+fn encode_gif(path: &Path, frames: Vec<image::RgbaImage>, delay: image::Delay) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = GifEncoder::new_with_speed(std::io::BufWriter::new(file), 10);
+    encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+    encoder
+        .encode_frames(frames.into_iter().map(|frame| image::Frame::from_parts(frame, 0, 0, delay)))
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,402 @@
+use eframe::egui;
+use eframe::egui_wgpu::{self, wgpu};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::sync::OnceLock;
+use wgpu::util::DeviceExt;
+use crate::{
+    Capabilities, Category, RenderMode, SimConfig, SimError, SimHelp, SimInfo, Simulation,
+};
+
+/// The App's wgpu device, set once at startup when eframe runs on the wgpu renderer.
+static RENDER_STATE: OnceLock<egui_wgpu::RenderState> = OnceLock::new();
+
+pub(crate) fn set_render_state(state: &egui_wgpu::RenderState) {
+    let _ = RENDER_STATE.set(state.clone());
+}
+
+/// Cells per workgroup side; dispatches cover the grid in 8x8 tiles.
+const WORKGROUP: u32 = 8;
+
+/// Declarations put in front of every `step_shader`.
+const STEP_PRELUDE: &str = "
+struct Grid { width: u32, height: u32, seed: u32, pad: u32 }
+@group(0) @binding(0) var<storage, read> src: array<u32>;
+@group(0) @binding(1) var<storage, read_write> dst: array<u32>;
+@group(0) @binding(2) var<uniform> grid: Grid;
+";
+
+/// Draws the cells under each fragment with the sim's `color` function. The world rect
+/// comes in as physical pixels so the canvas can be zoomed past the screen edges.
+const DISPLAY_SHADER: &str = "
+struct View { min: vec2<f32>, size: vec2<f32>, dims: vec2<u32>, pad: vec2<u32> }
+@group(0) @binding(0) var<storage, read> cells: array<u32>;
+@group(0) @binding(1) var<uniform> view: View;
+
+@vertex
+fn vs(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    let corner = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+    return vec4<f32>(corner * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = (pos.xy - view.min) / view.size;
+    if any(uv < vec2<f32>(0.0)) || any(uv >= vec2<f32>(1.0)) {
+        discard;
+    }
+    let cell = min(vec2<u32>(uv * vec2<f32>(view.dims)), view.dims - 1u);
+    return color(cells[cell.y * view.dims.x + cell.x]);
+}
+";
+
+/// A simulation whose update rule runs as a compute shader over a grid of `u32` cells
+/// that stays on the GPU: each step reads the previous grid and writes the next, and
+/// the canvas is drawn straight from the grid buffer. Wrap it in [`GpuSim`] to register
+/// it. Needs the App to run on eframe's wgpu renderer (the `gpu` feature).
+pub trait GpuSimulation: Send + 'static {
+    fn name(&self) -> &str;
+    fn info(&self) -> SimInfo { SimInfo::default() }
+    fn help(&self) -> SimHelp { SimHelp::default() }
+    /// Speed settings; the render mode is always vector, since the GPU draws the canvas.
+    fn config(&self) -> SimConfig { SimConfig::default() }
+    fn dimensions(&self) -> (u32, u32);
+    /// WGSL for one step, with an entry point
+    /// `@compute @workgroup_size(8, 8) fn step(@builtin(global_invocation_id) id: vec3<u32>)`.
+    /// It reads `src`, writes `dst` (both `array<u32>` in row-major order) and can use
+    /// `grid.width`, `grid.height` and `grid.seed`.
+    fn step_shader(&self) -> &str;
+    /// WGSL `fn color(cell: u32) -> vec4<f32>`, giving a cell's on-screen color.
+    fn color_shader(&self) -> &str;
+    /// The grid after a reset, `width * height` cells long.
+    fn initial_state(&self, seed: u64) -> Vec<u32>;
+    fn ui(&mut self, _ui: &mut egui::Ui) {}
+}
+
+/// Buffers and pipelines of a [`GpuSim`], created once the device is known.
+struct GpuState {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    cells: [wgpu::Buffer; 2],
+    grid: wgpu::Buffer,
+    step: wgpu::ComputePipeline,
+    /// Bind group `i` reads `cells[i]` and writes the other one.
+    step_groups: [wgpu::BindGroup; 2],
+    display: wgpu::RenderPipeline,
+    /// Where on screen the grid is drawn, rewritten every frame.
+    view: wgpu::Buffer,
+    /// Bind group `i` draws `cells[i]`.
+    display_groups: [wgpu::BindGroup; 2],
+    /// Which of `cells` holds the current grid.
+    current: usize,
+}
+
+/// Runs a [`GpuSimulation`] as a regular [`Simulation`]. Steps are queued on the GPU
+/// without waiting for them, and nothing is read back, so there is no CPU `render()`.
+pub struct GpuSim<G: GpuSimulation> {
+    sim: G,
+    gpu: Option<GpuState>,
+    seed: u64,
+    steps: u64,
+}
+
+impl<G: GpuSimulation> GpuSim<G> {
+    pub fn new(sim: G) -> Self {
+        Self { sim, gpu: None, seed: 0, steps: 0 }
+    }
+
+    fn create_gpu(&self, state: &egui_wgpu::RenderState) -> GpuState {
+        let device = state.device.clone();
+        let (w, h) = self.sim.dimensions();
+        let cells = std::array::from_fn(|i| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("{} cells {i}", self.sim.name())),
+                size: (w as u64 * h as u64 * 4).max(4),
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+        let grid = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("grid"),
+            contents: &words(&[w, h, 0, 0]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let source = format!("{STEP_PRELUDE}\n{}", self.sim.step_shader());
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(self.sim.name()),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let step = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some(self.sim.name()),
+            layout: None,
+            module: &module,
+            entry_point: Some("step"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let step_groups: [wgpu::BindGroup; 2] = std::array::from_fn(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &step.get_bind_group_layout(0),
+                entries: &[
+                    buffer_entry(0, &cells[i]),
+                    buffer_entry(1, &cells[1 - i]),
+                    buffer_entry(2, &grid),
+                ],
+            })
+        });
+
+        let source = format!("{}\n{DISPLAY_SHADER}", self.sim.color_shader());
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("display"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let display = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("display"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: Some("vs"),
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: Some("fs"),
+                compilation_options: Default::default(),
+                targets: &[Some(state.target_format.into())],
+            }),
+            multiview: None,
+            cache: None,
+        });
+        let view = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("view"),
+            size: 32,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let display_groups: [wgpu::BindGroup; 2] = std::array::from_fn(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &display.get_bind_group_layout(0),
+                entries: &[buffer_entry(0, &cells[i]), buffer_entry(1, &view)],
+            })
+        });
+
+        GpuState {
+            device,
+            queue: state.queue.clone(),
+            cells,
+            grid,
+            step,
+            step_groups,
+            display,
+            view,
+            display_groups,
+            current: 0,
+        }
+    }
+
+    fn upload_initial_state(&mut self) {
+        let Some(gpu) = &mut self.gpu else { return };
+        let (w, h) = self.sim.dimensions();
+        gpu.queue.write_buffer(&gpu.grid, 0, &words(&[w, h, self.seed as u32, 0]));
+        gpu.queue.write_buffer(&gpu.cells[0], 0, &words(&self.sim.initial_state(self.seed)));
+        gpu.current = 0;
+    }
+}
+
+fn words(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+fn buffer_entry(binding: u32, buffer: &wgpu::Buffer) -> wgpu::BindGroupEntry<'_> {
+    wgpu::BindGroupEntry { binding, resource: buffer.as_entire_binding() }
+}
+
+impl<G: GpuSimulation> Simulation for GpuSim<G> {
+    fn name(&self) -> &str { self.sim.name() }
+
+    fn info(&self) -> SimInfo { self.sim.info() }
+
+    fn help(&self) -> SimHelp { self.sim.help() }
+
+    fn config(&self) -> SimConfig {
+        SimConfig { render_mode: RenderMode::Vector, ..self.sim.config() }
+    }
+
+    fn capabilities(&self) -> Capabilities { Capabilities::empty() }
+
+    fn dimensions(&self) -> (usize, usize) {
+        let (w, h) = self.sim.dimensions();
+        (w as usize, h as usize)
+    }
+
+    fn on_load(&mut self, _ctx: &egui::Context) {
+        if self.gpu.is_none()
+            && let Some(state) = RENDER_STATE.get()
+        {
+            self.gpu = Some(self.create_gpu(state));
+            self.upload_initial_state();
+        }
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        self.update_n(1).map(drop)
+    }
+
+    /// Queues `n` steps in one submission; the GPU works through them after this returns.
+    fn update_n(&mut self, n: usize) -> Result<usize, SimError> {
+        let Some(gpu) = &mut self.gpu else {
+            return Err(SimError::State(format!("{} needs the wgpu renderer", self.sim.name())));
+        };
+        let (w, h) = self.sim.dimensions();
+        let mut encoder = gpu.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&gpu.step);
+            for _ in 0..n {
+                pass.set_bind_group(0, &gpu.step_groups[gpu.current], &[]);
+                pass.dispatch_workgroups(w.div_ceil(WORKGROUP), h.div_ceil(WORKGROUP), 1);
+                gpu.current = 1 - gpu.current;
+            }
+        }
+        gpu.queue.submit([encoder.finish()]);
+        self.steps += n as u64;
+        Ok(n)
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.steps)
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        buffer.fill(0);
+    }
+
+    fn render_vector(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let Some(gpu) = &self.gpu else { return };
+        let (w, h) = self.sim.dimensions();
+        let ppp = painter.ctx().pixels_per_point();
+        let (min, size) = (rect.min.to_vec2() * ppp, rect.size() * ppp);
+        let view = [min.x, min.y, size.x, size.y].map(f32::to_bits);
+        gpu.queue.write_buffer(&gpu.view, 0, &words(&[view[0], view[1], view[2], view[3], w, h, 0, 0]));
+        let bind_group = gpu.display_groups[gpu.current].clone();
+        let callback = DisplayCallback { pipeline: gpu.display.clone(), bind_group };
+        painter.add(egui_wgpu::Callback::new_paint_callback(rect, callback));
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.seed = seed;
+        self.steps = 0;
+        self.upload_initial_state();
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        if self.gpu.is_none() {
+            ui.colored_label(ui.visuals().warn_fg_color, "Needs the wgpu renderer");
+        }
+        self.sim.ui(ui);
+    }
+}
+
+/// Draws a [`GpuSim`]'s grid into egui's render pass.
+struct DisplayCallback {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+impl egui_wgpu::CallbackTrait for DisplayCallback {
+    fn paint(
+        &self,
+        _info: egui::PaintCallbackInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+        _resources: &egui_wgpu::CallbackResources,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+pub const LIFE_INFO: SimInfo = SimInfo {
+    description: "Conway's Game of Life on a wrapping 1024×1024 grid, stepped by a compute shader.",
+    category: Category::CellularAutomata,
+    tags: &["gpu", "life", "cellular automaton"],
+};
+
+/// Conway's Game of Life on the GPU, a million cells at a time.
+pub struct GpuLife;
+
+impl GpuSimulation for GpuLife {
+    fn name(&self) -> &str {
+        "GPU Life"
+    }
+
+    fn info(&self) -> SimInfo {
+        LIFE_INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "Each step, live cells with two or three live neighbors survive and dead \
+                       cells with exactly three come alive. Edges wrap around. The grid never \
+                       leaves the graphics card.",
+            legend: vec![
+                (egui::Color32::from_rgb(230, 230, 230), "Live cell"),
+                (egui::Color32::from_rgb(15, 15, 20), "Dead cell"),
+            ],
+            params: Vec::new(),
+            controls: Vec::new(),
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig { speed_unit: "generations/sec", default_speed: 60.0, ..SimConfig::default() }
+    }
+
+    fn dimensions(&self) -> (u32, u32) {
+        (1024, 1024)
+    }
+
+    fn step_shader(&self) -> &str {
+        "
+@compute @workgroup_size(8, 8)
+fn step(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= grid.width || id.y >= grid.height {
+        return;
+    }
+    var neighbors = 0u;
+    for (var dy = 0u; dy < 3u; dy++) {
+        for (var dx = 0u; dx < 3u; dx++) {
+            if dx == 1u && dy == 1u {
+                continue;
+            }
+            let x = (id.x + grid.width + dx - 1u) % grid.width;
+            let y = (id.y + grid.height + dy - 1u) % grid.height;
+            neighbors += src[y * grid.width + x];
+        }
+    }
+    let alive = src[id.y * grid.width + id.x];
+    dst[id.y * grid.width + id.x] = select(0u, 1u, neighbors == 3u || (alive == 1u && neighbors == 2u));
+}
+"
+    }
+
+    fn color_shader(&self) -> &str {
+        "
+fn color(cell: u32) -> vec4<f32> {
+    return select(vec4<f32>(0.06, 0.06, 0.08, 1.0), vec4<f32>(0.9, 0.9, 0.9, 1.0), cell == 1u);
+}
+"
+    }
+
+    fn initial_state(&self, seed: u64) -> Vec<u32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (w, h) = self.dimensions();
+        (0..w * h).map(|_| rng.random_bool(0.25) as u32).collect()
+    }
+}
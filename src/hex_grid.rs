@@ -0,0 +1,320 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::{Category, Colormap, SimConfig, SimError, SimHelp, SimInfo, Simulation};
+
+pub const SNOWFLAKE_INFO: SimInfo = SimInfo {
+    description: "Packard's snowflake: ice grows on a hexagonal lattice wherever exactly one \
+                  neighbor is frozen, branching into six-fold dendrites.",
+    category: Category::CellularAutomata,
+    tags: &["grid", "hexagonal", "crystal"],
+};
+
+/// A hexagonal lattice of pointy-topped hexes, addressed by axial coordinates (q, r): r
+/// counts rows down and q runs along a row, with (q, r + 1) below and half a hex to the
+/// right of (q, r).
+///
+/// The lattice is `columns` hexes wide and `rows` tall, laid out as a rectangle of rows
+/// offset by half a hex every other row. Row r holds q from `-(r / 2)` up to
+/// `columns - r / 2`, stored row by row.
+///
+/// ```
+/// use simulation_station::HexGrid;
+///
+/// let mut grid = HexGrid::new(5, 5);
+/// grid.set(1, 2, true);
+/// assert_eq!(grid.count_neighbors_where(2, 2, |&ice| ice), 1);
+/// assert_eq!(grid.neighbors(0, 0).count(), 2);
+/// assert!(grid.get(-1, 0).is_none());
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HexGrid<T> {
+    columns: usize,
+    rows: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone + Default> HexGrid<T> {
+    pub fn new(columns: usize, rows: usize) -> Self {
+        Self { columns, rows, cells: vec![T::default(); columns * rows] }
+    }
+}
+
+impl<T> HexGrid<T> {
+    /// (dq, dr) of the six neighbors, clockwise from the one to the right.
+    pub const DIRECTIONS: [(i32, i32); 6] = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn index(&self, q: i32, r: i32) -> Option<usize> {
+        let column = q as isize + (r as isize).div_euclid(2);
+        let inside = (0..self.columns as isize).contains(&column)
+            && (0..self.rows as isize).contains(&(r as isize));
+        inside.then(|| r as usize * self.columns + column as usize)
+    }
+
+    pub fn contains(&self, q: i32, r: i32) -> bool {
+        self.index(q, r).is_some()
+    }
+
+    /// `None` off the lattice.
+    pub fn get(&self, q: i32, r: i32) -> Option<&T> {
+        self.index(q, r).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, q: i32, r: i32) -> Option<&mut T> {
+        self.index(q, r).map(|i| &mut self.cells[i])
+    }
+
+    /// Panics if (q, r) is off the lattice.
+    pub fn set(&mut self, q: i32, r: i32, value: T) {
+        let index = self.index(q, r).unwrap_or_else(|| panic!("hex ({q}, {r}) is off the grid"));
+        self.cells[index] = value;
+    }
+
+    /// Every hex's coordinates, in the order of [`cells`](Self::cells).
+    pub fn positions(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let (columns, rows) = (self.columns as i32, self.rows as i32);
+        (0..rows).flat_map(move |r| (0..columns).map(move |column| (column - r.div_euclid(2), r)))
+    }
+
+    /// Row-major cell storage.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// The neighbors of (q, r) on the lattice, in [`DIRECTIONS`](Self::DIRECTIONS) order.
+    pub fn neighbor_positions(&self, q: i32, r: i32) -> impl Iterator<Item = (i32, i32)> + '_ {
+        Self::DIRECTIONS
+            .iter()
+            .map(move |&(dq, dr)| (q + dq, r + dr))
+            .filter(|&(q, r)| self.contains(q, r))
+    }
+
+    pub fn neighbors(&self, q: i32, r: i32) -> impl Iterator<Item = &T> + '_ {
+        Self::DIRECTIONS.iter().filter_map(move |&(dq, dr)| self.get(q + dq, r + dr))
+    }
+
+    pub fn count_neighbors_where(&self, q: i32, r: i32, pred: impl Fn(&T) -> bool) -> usize {
+        self.neighbors(q, r).filter(|cell| pred(cell)).count()
+    }
+
+    /// The hex under pixel (x, y) of a `width` × `height` image the lattice is drawn into by
+    /// [`render_into`](Self::render_into), for mapping pointer input.
+    pub fn hex_at(&self, x: f32, y: f32, width: usize, height: usize) -> Option<(i32, i32)> {
+        let (q, r) = HexLayout::fit(self, width, height).hex_at(x, y);
+        self.contains(q, r).then_some((q, r))
+    }
+
+    /// Draws the lattice as large as fits, centered, into a `width` × `height` RGB
+    /// `buffer`, coloring each hex with `color` and the rest `background`.
+    pub fn render_into(
+        &self,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        background: [u8; 3],
+        color: impl Fn(&T) -> [u8; 3],
+    ) {
+        let layout = HexLayout::fit(self, width, height);
+        for (y, row) in buffer.chunks_exact_mut(width * 3).take(height).enumerate() {
+            for (x, pixel) in row.chunks_exact_mut(3).enumerate() {
+                let (q, r) = layout.hex_at(x as f32 + 0.5, y as f32 + 0.5);
+                pixel.copy_from_slice(&self.get(q, r).map_or(background, &color));
+            }
+        }
+    }
+}
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// Where a HexGrid sits in an image: hex size (center to corner) and the pixel position of
+/// hex (0, 0)'s center.
+struct HexLayout {
+    size: f32,
+    origin: (f32, f32),
+}
+
+impl HexLayout {
+    fn fit<T>(grid: &HexGrid<T>, width: usize, height: usize) -> Self {
+        let (width, height) = (width as f32, height as f32);
+        let (extent_x, extent_y) = (SQRT_3 * (grid.columns as f32 + 0.5), 1.5 * grid.rows as f32 + 0.5);
+        let size = (width / extent_x).min(height / extent_y);
+        let margin = ((width - extent_x * size) / 2.0, (height - extent_y * size) / 2.0);
+        Self { size, origin: (margin.0 + SQRT_3 / 2.0 * size, margin.1 + size) }
+    }
+
+    /// The axial coordinates of the hex containing (x, y), on the lattice or not.
+    fn hex_at(&self, x: f32, y: f32) -> (i32, i32) {
+        let (x, y) = ((x - self.origin.0) / self.size, (y - self.origin.1) / self.size);
+        let q = SQRT_3 / 3.0 * x - y / 3.0;
+        let r = 2.0 / 3.0 * y;
+        // Round in cube coordinates (q, r, s) with q + r + s = 0, fixing whichever
+        // component rounded furthest.
+        let s = -q - r;
+        let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        }
+        (rq as i32, rr as i32)
+    }
+}
+
+/// Packard's snowflake on a HexGrid. Each cell holds the generation it froze in, 0 for
+/// water; a water cell with exactly one frozen neighbor freezes.
+pub struct SnowflakeSim {
+    ice: HexGrid<u32>,
+    next: HexGrid<u32>,
+    generation: u32,
+    grew: usize,
+}
+
+impl SnowflakeSim {
+    const SIZE: usize = 101;
+    const WIDTH: usize = 600;
+    const HEIGHT: usize = 520;
+
+    pub fn new() -> Self {
+        let mut sim = Self {
+            ice: HexGrid::new(Self::SIZE, Self::SIZE),
+            next: HexGrid::new(Self::SIZE, Self::SIZE),
+            generation: 0,
+            grew: 0,
+        };
+        sim.reset(0);
+        sim
+    }
+
+    fn colormap() -> Colormap {
+        Colormap::Custom(vec![[255, 255, 255], [140, 200, 255], [40, 90, 200]])
+    }
+}
+
+impl Simulation for SnowflakeSim {
+    fn name(&self) -> &str {
+        "Snowflake"
+    }
+
+    fn info(&self) -> SimInfo {
+        SNOWFLAKE_INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "A seed crystal sits in the middle of a hexagonal lattice. Each step, every \
+                       water cell with exactly one frozen neighbor freezes, so tips grow while \
+                       crowded fronts stall, and the crystal branches into a six-armed flake.",
+            legend: Self::colormap().legend("Oldest ice", "Newest ice"),
+            params: Vec::new(),
+            controls: Vec::new(),
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            max_speed: 60.0,
+            default_speed: 10.0,
+            speed_unit: "generations/sec",
+            ..SimConfig::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (Self::WIDTH, Self::HEIGHT)
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        self.generation += 1;
+        self.grew = 0;
+        for ((q, r), next) in self.ice.positions().zip(self.next.cells_mut()) {
+            let here = *self.ice.get(q, r).unwrap_or(&0);
+            *next = if here == 0 && self.ice.count_neighbors_where(q, r, |&frozen| frozen > 0) == 1 {
+                self.grew += 1;
+                self.generation
+            } else {
+                here
+            };
+        }
+        std::mem::swap(&mut self.ice, &mut self.next);
+        Ok(())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.generation > 0 && self.grew == 0
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        let lut = Self::colormap().lut();
+        let newest = self.generation.max(1) as f32;
+        self.ice.render_into(buffer, Self::WIDTH, Self::HEIGHT, [8, 12, 24], |&frozen| match frozen {
+            0 => [16, 24, 48],
+            frozen => lut[(frozen as f32 / newest * 255.0) as usize],
+        });
+    }
+
+    fn reset(&mut self, _seed: u64) {
+        self.ice.cells_mut().fill(0);
+        let middle = Self::SIZE as i32 / 2;
+        self.ice.set(middle - middle / 2, middle, 1);
+        self.generation = 1;
+        self.grew = 1;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!("Generation: {}", self.generation));
+        ui.label(format!("Ice: {}", self.ice.cells().iter().filter(|&&frozen| frozen > 0).count()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_follow_cells() {
+        let mut grid = HexGrid::new(4, 5);
+        let positions: Vec<_> = grid.positions().collect();
+        assert_eq!(positions.len(), grid.cells().len());
+        for (i, &(q, r)) in positions.iter().enumerate() {
+            *grid.get_mut(q, r).unwrap() = i;
+        }
+        assert!(grid.cells().iter().enumerate().all(|(i, &cell)| cell == i));
+        assert_eq!(positions[..4], [(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert_eq!(positions[8..12], [(-1, 2), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn neighbors_are_mutual() {
+        let grid = HexGrid::<u8>::new(6, 7);
+        for (q, r) in grid.positions() {
+            for (nq, nr) in grid.neighbor_positions(q, r) {
+                assert!(grid.neighbor_positions(nq, nr).any(|n| n == (q, r)), "({q}, {r}) ({nq}, {nr})");
+            }
+        }
+    }
+
+    #[test]
+    fn neighbor_counts() {
+        let grid = HexGrid::<u8>::new(6, 7);
+        let around: Vec<_> = grid.neighbor_positions(2, 3).collect();
+        assert_eq!(around, [(3, 3), (2, 4), (1, 4), (1, 3), (2, 2), (3, 2)]);
+        // Even rows sit half a hex left of odd ones, so their left ends have fewer neighbors
+        let corners = [(0, 0), (5, 0), (-3, 6), (2, 6)];
+        let counts: Vec<_> = corners.iter().map(|&(q, r)| grid.neighbors(q, r).count()).collect();
+        assert_eq!(counts, [2, 3, 2, 3]);
+        assert_eq!(grid.neighbors(-1, 2).count(), 3);
+        assert_eq!(grid.neighbors(0, 1).count(), 5);
+    }
+}
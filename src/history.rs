@@ -0,0 +1,75 @@
+use eframe::egui;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+pub(crate) struct RecordedFrame {
+    /// Step count the frame was rendered at.
+    pub(crate) steps: u64,
+    pub(crate) image: Arc<egui::ColorImage>,
+}
+
+/// Rendered frames kept for replay, oldest first. Recording drops the oldest frames
+/// once the memory cap is reached.
+#[derive(Default)]
+pub(crate) struct FrameHistory {
+    frames: VecDeque<RecordedFrame>,
+    bytes: usize,
+    /// The frame on screen while replaying; `None` shows the live sim.
+    pub(crate) position: Option<usize>,
+}
+
+impl FrameHistory {
+    /// Keeps `image` unless a frame for the same step count is already the newest one.
+    /// Returns the oldest frames dropped to stay under `cap_bytes`, for their pixels to be
+    /// reused.
+    pub(crate) fn record(
+        &mut self,
+        steps: u64,
+        image: Arc<egui::ColorImage>,
+        cap_bytes: usize,
+    ) -> Vec<Arc<egui::ColorImage>> {
+        let mut evicted = Vec::new();
+        if self.frames.back().is_some_and(|last| last.steps == steps) {
+            return evicted;
+        }
+        self.bytes += image_bytes(&image);
+        self.frames.push_back(RecordedFrame { steps, image });
+        while self.bytes > cap_bytes && !self.frames.is_empty() {
+            if let Some(oldest) = self.frames.pop_front() {
+                self.bytes -= image_bytes(&oldest.image);
+                evicted.push(oldest.image);
+            }
+        }
+        evicted
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&RecordedFrame> {
+        self.frames.get(index)
+    }
+
+    /// The frame being replayed, if any.
+    pub(crate) fn shown(&self) -> Option<&RecordedFrame> {
+        self.position.and_then(|index| self.frames.get(index))
+    }
+
+    /// The frame recorded closest to step `steps`.
+    pub(crate) fn nearest(&self, steps: u64) -> Option<usize> {
+        (0..self.frames.len()).min_by_key(|&index| self.frames[index].steps.abs_diff(steps))
+    }
+}
+
+fn image_bytes(image: &egui::ColorImage) -> usize {
+    image.pixels.len() * std::mem::size_of::<egui::Color32>()
+}
@@ -0,0 +1,92 @@
+use eframe::egui;
+use web_time::Duration;
+
+/// Where one frame's time went, filled in as the App steps and paints.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FrameTimings {
+    /// Time inside `update()`/`update_n()`/`update_dt()`, across every tab.
+    pub(crate) update: Duration,
+    /// Time inside `render()` or `render_vector()`.
+    pub(crate) render: Duration,
+    /// Time turning rendered pixels into a texture.
+    pub(crate) upload: Duration,
+    /// Steps that were due but skipped because a sim couldn't keep up, in any tab.
+    pub(crate) dropped: usize,
+}
+
+/// Smoothed frame statistics for the performance overlay.
+#[derive(Default)]
+pub(crate) struct PerfHud {
+    frame_ms: f32,
+    update_ms: f32,
+    render_ms: f32,
+    upload_ms: f32,
+    dropped: u64,
+}
+
+impl PerfHud {
+    /// Weight of the newest frame in the moving averages.
+    const SMOOTHING: f32 = 0.05;
+
+    pub(crate) fn record(&mut self, frame_time: f32, timings: FrameTimings) {
+        let ema = |avg: &mut f32, sample: f32| {
+            *avg = if *avg == 0.0 { sample } else { *avg + (sample - *avg) * Self::SMOOTHING };
+        };
+        ema(&mut self.frame_ms, frame_time * 1000.0);
+        ema(&mut self.update_ms, timings.update.as_secs_f32() * 1000.0);
+        ema(&mut self.render_ms, timings.render.as_secs_f32() * 1000.0);
+        ema(&mut self.upload_ms, timings.upload.as_secs_f32() * 1000.0);
+        self.dropped += timings.dropped as u64;
+    }
+
+    pub(crate) fn reset_dropped(&mut self) {
+        self.dropped = 0;
+    }
+
+    /// Draws the overlay in the top-right corner of `canvas`. `requested` is the target
+    /// rate, or `None` when there isn't one (turbo, realtime or manual stepping).
+    pub(crate) fn ui(
+        &self,
+        ctx: &egui::Context,
+        canvas: egui::Rect,
+        achieved: f64,
+        requested: Option<f32>,
+        unit: &str,
+    ) {
+        egui::Area::new(egui::Id::new("perf_hud"))
+            .fixed_pos(canvas.right_top() + egui::vec2(-8.0, 8.0))
+            .pivot(egui::Align2::RIGHT_TOP)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .fill(egui::Color32::from_black_alpha(180))
+                    .show(ui, |ui| {
+                        let fps = if self.frame_ms > 0.0 { 1000.0 / self.frame_ms } else { 0.0 };
+                        let slowest = self.update_ms.max(self.render_ms).max(self.upload_ms);
+                        let phase = |ui: &mut egui::Ui, name: &str, ms: f32| {
+                            let text = egui::RichText::new(format!("{name:<7}{ms:>7.2} ms")).monospace();
+                            if ms == slowest && ms > 0.0 {
+                                ui.label(text.color(egui::Color32::YELLOW));
+                            } else {
+                                ui.label(text);
+                            }
+                        };
+
+                        ui.monospace(format!("FPS    {fps:>7.1} ({:.1} ms)", self.frame_ms));
+                        match requested {
+                            Some(target) => ui.monospace(format!("UPS    {achieved:>7.0} / {target:.0} {unit}")),
+                            None => ui.monospace(format!("UPS    {achieved:>7.0} {unit}")),
+                        };
+                        phase(ui, "update", self.update_ms);
+                        phase(ui, "render", self.render_ms);
+                        phase(ui, "upload", self.upload_ms);
+                        let dropped = format!("dropped{:>7} steps", self.dropped);
+                        if self.dropped > 0 {
+                            ui.colored_label(egui::Color32::LIGHT_RED, egui::RichText::new(dropped).monospace());
+                        } else {
+                            ui.monospace(dropped);
+                        }
+                    });
+            });
+    }
+}
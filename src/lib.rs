@@ -1,16 +1,193 @@
+mod annotations;
+mod bit_grid;
+mod app;
+mod browser;
+mod brush;
+mod diffusion3d;
+mod dock;
+mod effects;
+#[cfg(not(target_arch = "wasm32"))]
+mod export;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod hex_grid;
+mod history;
+mod life;
+mod life_formats;
+mod hud;
+mod logger;
+mod metrics;
 mod simple_grid;
+#[cfg(not(target_arch = "wasm32"))]
+mod svg;
 mod p0014;
+mod palette;
+mod pool;
+mod camera;
+mod chunked_grid;
+mod colormap;
+mod params;
+mod pipeline;
+mod plasma;
+mod registry;
+mod rewind;
+mod selection;
+mod session;
+mod shared;
+mod sparse_grid;
+mod theme;
+mod tiles;
+mod volume;
+mod wator;
+mod workers;
+mod workspace;
+#[cfg(all(feature = "video", not(target_arch = "wasm32")))]
+mod video;
 
+pub use annotations::{Annotation, Annotations};
+pub use app::{App, SimStats};
+pub use bit_grid::{BitGrid, LifeGrid, LifeRule};
+pub use brush::{Brush, BrushShape};
+pub use camera::{Camera2D, CanvasFit, Viewport};
+pub use chunked_grid::ChunkedGrid;
+pub use colormap::{ColorScale, Colormap, FieldScale, Palette};
+pub use effects::Effects;
+#[cfg(feature = "gpu")]
+pub use gpu::{GpuSim, GpuSimulation};
+pub use hex_grid::HexGrid;
+pub use life_formats::PatternCell;
+pub use logger::{LogEntry, LogLevel, SimLogger};
+pub use metrics::Metrics;
+#[cfg(feature = "parallel")]
+pub use rayon;
+#[cfg(feature = "tokio")]
+pub use tokio;
+pub use params::{ParamSpec, ParamWidget, Params, PresetStore, SimParams};
+pub use pipeline::PipelineSim;
+pub use registry::{SimEntry, SimRegistry};
+pub use selection::{GridSelection, SelectionTool};
+pub use shared::Shared;
+pub use sparse_grid::{Bounds, SparseGrid};
+pub use simple_grid::{Anchor, Boundary, Grid, Grid3, GridPatch, Neighborhood, Neighbors};
+pub use theme::{Theme, ThemeKind};
+pub use tiles::{SpriteAtlas, Tile, TileProjection, TileRenderer};
+pub use volume::{Axis, VolumeMode, VolumeView};
+pub use workers::{WorkerPriority, WorkerSettings};
+// For `sim_params!`, so crates using it don't need these under the same names
+pub use eframe;
+pub use serde;
+
+use bitflags::bitflags;
 use eframe::egui;
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
-use std::sync::Arc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt::{self, Write};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::cell::Cell;
+use std::thread::JoinHandle;
+use web_time::{Duration, Instant};
+
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Rgb,
+    /// Straight (non-premultiplied) alpha, composited by the App over its background color.
+    Rgba,
+    /// One byte per pixel, an index into a palette the App owns and colors at display
+    /// time. The sim suggests it with `Simulation::palette`.
+    Indexed,
+}
 
+impl PixelFormat {
+    pub fn channels(self) -> usize {
+        match self {
+            PixelFormat::Rgb => 3,
+            PixelFormat::Rgba => 4,
+            PixelFormat::Indexed => 1,
+        }
+    }
+}
+
+/// How the canvas texture is sampled when it's drawn at a different size than the sim.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextureFilter {
+    /// Sharp square pixels; what cellular automata and other pixel-art sims want.
+    Nearest,
+    /// Blends neighboring pixels; suits smooth scalar fields.
+    Linear,
+}
+
+impl TextureFilter {
+    pub(crate) fn options(self) -> egui::TextureOptions {
+        match self {
+            TextureFilter::Nearest => egui::TextureOptions::NEAREST,
+            TextureFilter::Linear => egui::TextureOptions::LINEAR,
+        }
+    }
+}
+
+/// How the App drives a simulation forward.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StepMode {
+    /// Fixed ticks at the Hz chosen on the speed slider.
+    Continuous,
+    /// Only advances when the user presses Step.
+    Manual,
+    /// One `update_dt()` per frame with the (scaled) wall-clock frame time.
+    Realtime,
+}
+
+/// Whether the App rasterizes the sim through `render()` or lets it paint shapes directly.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    Raster,
+    /// `render_vector()` draws with the egui Painter at native screen resolution.
+    Vector,
+}
+
+/// One of the ways a simulation can be displayed, picked from the central panel.
+#[derive(Clone)]
+pub struct ViewInfo {
+    pub name: String,
+    pub render_mode: RenderMode,
+}
+
+/// An overlay a sim draws above its base `render()` buffer, such as agents, annotations
+/// or debug vectors. The App lets the user hide it or fade it.
+#[derive(Clone)]
+pub struct LayerInfo {
+    pub name: String,
+    /// Whether the layer starts out shown.
+    pub visible: bool,
+}
+
+/// How the speed slider maps onto `min_speed..=max_speed`.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SpeedCurve {
+    Linear,
+    Logarithmic,
+    /// Only these speeds can be picked, in ascending order.
+    Steps(&'static [f32]),
+}
 
 #[derive(Clone, Copy)]
 pub struct SimConfig {
     pub min_speed: f32,
     pub max_speed: f32,
     pub default_speed: f32,
+    /// What one update means to the user, e.g. "generations/sec".
+    pub speed_unit: &'static str,
+    pub speed_curve: SpeedCurve,
+    pub pixel_format: PixelFormat,
+    pub step_mode: StepMode,
+    pub render_mode: RenderMode,
+    pub texture_filter: TextureFilter,
+    /// Each rendered pixel becomes an `upscale` × `upscale` block before it's uploaded, so
+    /// linear filtering only blurs the edges between pixels. 1 leaves frames as they are.
+    pub upscale: usize,
+    /// Post-processing suggested for raster views until the user picks their own.
+    pub effects: Effects,
 }
 
 impl Default for SimConfig {
@@ -19,212 +196,1684 @@ impl Default for SimConfig {
             min_speed: 1.0,
             max_speed: 10_000.0,
             default_speed: 60.0,
+            speed_unit: "steps/sec",
+            speed_curve: SpeedCurve::Logarithmic,
+            pixel_format: PixelFormat::Rgb,
+            step_mode: StepMode::Continuous,
+            render_mode: RenderMode::Raster,
+            texture_filter: TextureFilter::Nearest,
+            upscale: 1,
+            effects: Effects::NONE,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SimError {
+    /// A background worker thread panicked; carries the panic message.
+    WorkerPanicked(String),
+    /// A snapshot could not be saved or restored.
+    State(String),
+    Other(String),
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimError::WorkerPanicked(msg) => write!(f, "worker crashed: {msg}"),
+            SimError::State(msg) => write!(f, "state error: {msg}"),
+            SimError::Other(msg) => f.write_str(msg),
         }
     }
 }
 
+impl std::error::Error for SimError {}
+
+bitflags! {
+    /// Optional features a simulation supports. The App hides or disables the matching
+    /// controls for sims that don't declare them.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Capabilities: u32 {
+        /// Reacts to canvas input via `handle_input`.
+        const INPUT = 1 << 0;
+        /// Implements `save_state`/`load_state`; also what rewind is built on.
+        const SNAPSHOTS = 1 << 1;
+        /// Produces frames worth exporting (screenshots, recordings).
+        const EXPORT = 1 << 2;
+        /// Can change its resolution at runtime.
+        const RESIZE = 1 << 3;
+        /// Implements `render_region` to draw only the part of the world on screen.
+        const CULLING = 1 << 4;
+        /// Computes on worker threads that `set_worker_settings` can tune.
+        const WORKERS = 1 << 5;
+    }
+}
+
+/// A rectangle of simulation cells, such as the part of the world visible on screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    /// The whole of a world `dims` cells in size.
+    pub fn full(dims: (usize, usize)) -> Self {
+        Self { x: 0, y: 0, width: dims.0, height: dims.1 }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        self.columns().contains(&x) && self.rows().contains(&y)
+    }
+
+    pub fn columns(&self) -> std::ops::Range<usize> {
+        self.x..self.x + self.width
+    }
+
+    pub fn rows(&self) -> std::ops::Range<usize> {
+        self.y..self.y + self.height
+    }
+}
+
+/// Canvas input translated by the App into simulation cell coordinates. Pointer events
+/// also carry `pos`, the exact point in world units (see [`Viewport`]), for sims that
+/// place things between cells.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SimInput {
+    PointerDown {
+        x: usize,
+        y: usize,
+        button: egui::PointerButton,
+        #[serde(default)]
+        pos: egui::Pos2,
+    },
+    PointerDrag {
+        x: usize,
+        y: usize,
+        button: egui::PointerButton,
+        #[serde(default)]
+        pos: egui::Pos2,
+    },
+    PointerUp {
+        x: usize,
+        y: usize,
+        button: egui::PointerButton,
+        #[serde(default)]
+        pos: egui::Pos2,
+    },
+    Key { key: egui::Key, pressed: bool },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Category {
+    CellularAutomata,
+    ProjectEuler,
+    Physics,
+    Demo,
+    #[default]
+    Other,
+}
+
+impl Category {
+    pub fn label(self) -> &'static str {
+        match self {
+            Category::CellularAutomata => "Cellular Automata",
+            Category::ProjectEuler => "Project Euler",
+            Category::Physics => "Physics",
+            Category::Demo => "Demos",
+            Category::Other => "Other",
+        }
+    }
+}
+
+/// Human-readable metadata shown in the selector and side panel.
+#[derive(Clone, Copy, Default)]
+pub struct SimInfo {
+    pub description: &'static str,
+    pub category: Category,
+    pub tags: &'static [&'static str],
+}
+
+/// Longer documentation for a sim's help window, beyond the one-line description.
+#[derive(Clone, Default)]
+pub struct SimHelp {
+    /// What the sim models and how to read the display.
+    pub overview: &'static str,
+    /// What the colors on the canvas stand for.
+    pub legend: Vec<(egui::Color32, &'static str)>,
+    /// What each parameter does, keyed by its field name.
+    pub params: Vec<(&'static str, &'static str)>,
+    /// The sim's own keyboard and mouse controls, as (input, effect).
+    pub controls: Vec<(&'static str, &'static str)>,
+}
+
+/// How far a sim is through a task with an end, such as a search over a fixed range. The
+/// App shows it as a progress bar above the sim's own panel.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Progress {
+    /// 0 to 1.
+    pub fraction: f32,
+    /// Time left at the current pace, if known.
+    pub eta: Option<Duration>,
+    /// What's being worked on, e.g. "Checked 1,200 of 10,000".
+    pub message: String,
+}
+
+impl Progress {
+    pub(crate) fn ui(&self, ui: &mut egui::Ui) {
+        let mut text = format!("{:.1}%", self.fraction * 100.0);
+        if !self.message.is_empty() {
+            text = format!("{} · {text}", self.message);
+        }
+        if let Some(eta) = self.eta {
+            let secs = eta.as_secs();
+            let _ = match secs / 3600 {
+                0 => write!(text, " · {}:{:02} left", secs / 60, secs % 60),
+                hours => write!(text, " · {hours}:{:02}:{:02} left", secs / 60 % 60, secs % 60),
+            };
+        }
+        ui.add(egui::ProgressBar::new(self.fraction.clamp(0.0, 1.0)).text(text));
+    }
+}
+
 pub trait Simulation {
     fn name(&self) -> &str;
+    fn info(&self) -> SimInfo { SimInfo::default() }
+    /// Fills the help window. Called while it is open, so colors can follow the theme.
+    fn help(&self) -> SimHelp { SimHelp::default() }
     fn config(&self) -> SimConfig { SimConfig::default() }
-    fn update(&mut self);
-    fn render(&self, buffer: &mut Vec<u8>);
-    fn reset(&mut self);
+    fn capabilities(&self) -> Capabilities { Capabilities::empty() }
+    fn dimensions(&self) -> (usize, usize) { (400, 300) }
+    /// Changes the resolution to `width` × `height` and rebuilds whatever buffers depend
+    /// on it. Only called on sims declaring `Capabilities::RESIZE`; the App resets the sim
+    /// afterwards.
+    fn resize(&mut self, _width: usize, _height: usize) -> Result<(), SimError> {
+        Err(SimError::State(format!("{} has a fixed resolution", self.name())))
+    }
+    fn update(&mut self) -> Result<(), SimError>;
+    /// Advances up to `n` steps in one call and returns how many were taken. Override this
+    /// when a tight internal loop is cheaper than `n` separate `update()` calls.
+    fn update_n(&mut self, n: usize) -> Result<usize, SimError> {
+        for done in 0..n {
+            if self.is_finished() {
+                return Ok(done);
+            }
+            self.update()?;
+        }
+        Ok(n)
+    }
+    /// Used instead of `update()` by sims configured with `StepMode::Realtime`;
+    /// `dt` is the frame time in seconds, already multiplied by the App's time scale.
+    fn update_dt(&mut self, _dt: f32) -> Result<(), SimError> {
+        self.update()
+    }
+    /// A counter that moves whenever what `render()` would draw changes, letting the App
+    /// skip rendering and uploading a frame it already has. `None`, the default, renders
+    /// every frame. Edits made through the App's parameter panel are tracked by the App.
+    fn version(&self) -> Option<u64> { None }
+    /// Once true, the App stops calling `update()` until the sim is reset.
+    fn is_finished(&self) -> bool { false }
+    fn render(&self, buffer: &mut [u8]);
+    /// Like `render()`, but only the cells inside `visible` need drawing; the rest of the
+    /// buffer can be left alone. Called instead of `render()` for sims declaring
+    /// `Capabilities::CULLING` while part of the world is off screen, so huge worlds only
+    /// pay for what's shown.
+    fn render_region(&self, _visible: Region, buffer: &mut [u8]) {
+        self.render(buffer);
+    }
+    /// Draws the current state at `width` × `height` into `buffer`, resizing it to fit,
+    /// for exports larger than the live view. The default scales up `render()` with
+    /// nearest-neighbor sampling; override it to redraw at the higher resolution.
+    fn render_at(&self, width: usize, height: usize, buffer: &mut Vec<u8>) {
+        let (w, h) = self.dimensions();
+        let channels = self.config().pixel_format.channels();
+        let mut native = vec![0; w * h * channels];
+        self.render(&mut native);
+        buffer.resize(width * height * channels, 0);
+        for (y, row) in buffer.chunks_exact_mut(width * channels).enumerate() {
+            let src_row = y * h / height * w;
+            for (x, pixel) in row.chunks_exact_mut(channels).enumerate() {
+                let src = (src_row + x * w / width) * channels;
+                pixel.copy_from_slice(&native[src..src + channels]);
+            }
+        }
+    }
+    /// Paints into `rect` (screen space) for sims configured with `RenderMode::Vector`.
+    fn render_vector(&self, _painter: &egui::Painter, _rect: egui::Rect) {}
+    /// What the App calls to draw a vector view, with the camera it's seen through so the
+    /// sim can work in world units: `viewport.to_screen()` places a world point and
+    /// `viewport.visible()` bounds what's worth drawing. The default hands
+    /// `render_vector()` the world's screen rect.
+    fn render_world(&self, painter: &egui::Painter, viewport: &Viewport) {
+        self.render_vector(painter, viewport.world_rect());
+    }
+    /// The views this sim can render; `render`/`render_vector` draw the selected one.
+    fn views(&self) -> Vec<ViewInfo> {
+        vec![ViewInfo { name: "Default".to_owned(), render_mode: self.config().render_mode }]
+    }
+    fn select_view(&mut self, _index: usize) {}
+    /// Overlays for raster views, composited over `render()` in order.
+    fn layers(&self) -> Vec<LayerInfo> { Vec::new() }
+    /// Draws layer `index` as straight-alpha RGBA at the sim's resolution, transparent
+    /// wherever it has nothing to show.
+    fn render_layer(&self, _index: usize, _buffer: &mut [u8]) {}
+    /// The colormap and value range a raster view is drawn with, if it shows a scalar
+    /// field. The App draws it as a color bar beside the canvas; auto-ranged sims should
+    /// report the range of the frame they'd render now.
+    fn color_scale(&self) -> Option<ColorScale> { None }
+    /// The palette a `PixelFormat::Indexed` sim starts out with, before the user picks
+    /// another in the App.
+    fn palette(&self) -> Palette { Palette::default() }
+    /// Restarts from initial conditions. Stochastic sims should seed their RNG from `seed`
+    /// so the App's seed reproduces a run exactly.
+    fn reset(&mut self, seed: u64);
     fn ui(&mut self, ui: &mut egui::Ui);
+    fn handle_input(&mut self, _event: SimInput) {}
+    /// Adds labels and shapes, in cell coordinates, to draw over a raster view. Called
+    /// every frame the view is painted, with `annotations` emptied beforehand.
+    fn annotate(&self, _annotations: &mut Annotations) {}
+    /// Reports values for the Metrics panel's plots with `metrics.record(name, value)`.
+    /// Called each frame the sim advanced, at the step count it reached.
+    fn record_metrics(&self, _metrics: &mut Metrics) {}
+    /// How far through a finite task the sim is, for the progress bar above its panel.
+    fn progress(&self) -> Option<Progress> { None }
+    /// Describes cell (x, y) of a raster view for the canvas's hover readout, e.g. the
+    /// cell's state or value rather than the color it's drawn in.
+    fn probe(&self, _x: usize, _y: usize) -> Option<String> { None }
+    /// The cells where the action is, for sims whose interesting part wanders (an ant, a
+    /// glider). The App outlines it on the minimap and, with Follow on, moves the camera
+    /// to keep it in view.
+    fn focus(&self) -> Option<Region> { None }
+
+    /// Called when the App makes this the active simulation.
+    fn on_load(&mut self, _ctx: &egui::Context) {}
+    /// Told every frame whether the App is stepping this sim: false while it's paused,
+    /// finished, failed or in a background tab that isn't running. Sims computing on
+    /// threads of their own should idle them while it's false.
+    fn set_running(&mut self, _running: bool) {}
+    /// The thread count and priority the App picked for this sim's workers, passed every
+    /// frame. Sims with `Capabilities::WORKERS` should apply them when they change.
+    fn set_worker_settings(&mut self, _settings: WorkerSettings) {}
+    /// Called before the App drops this simulation (switching sims or shutting down).
+    /// Stop threads, release resources and flush files here.
+    fn on_unload(&mut self) {}
+    /// Publishes this sim's state as a scalar field for the next stage of a `PipelineSim`.
+    fn export_field(&self) -> Option<Grid<f32>> { None }
+    /// Receives the previous stage's exported field when running inside a `PipelineSim`.
+    fn import_field(&mut self, _field: &Grid<f32>) {}
+    /// Extensions, lowercase and without the dot, of the files `load_file` understands.
+    fn file_formats(&self) -> &'static [&'static str] { &[] }
+    /// Loads a file dropped onto the App whose extension is listed in `file_formats`,
+    /// e.g. a pattern or an image to use as initial conditions.
+    fn load_file(&mut self, _name: &str, _data: &[u8]) -> Result<(), SimError> {
+        Err(SimError::State(format!("{} doesn't load files", self.name())))
+    }
+    /// Exposes tunable parameters; the App draws their panel and manages presets.
+    fn params(&mut self) -> Option<&mut dyn Params> { None }
+
+    /// Serializes the current state, or `None` if the sim does not support snapshots.
+    fn save_state(&self) -> Option<Vec<u8>> { None }
+    fn load_state(&mut self, _data: &[u8]) -> Result<(), SimError> {
+        Err(SimError::State(format!("{} does not support snapshots", self.name())))
+    }
 }
 
 pub struct NoSim;
 
 impl Simulation for NoSim {
     fn name(&self) -> &str { "None" }
-    fn update(&mut self) {}
-    fn reset(&mut self) {}
+    fn info(&self) -> SimInfo {
+        SimInfo { description: "Pick a simulation from the list above.", ..SimInfo::default() }
+    }
+    fn update(&mut self) -> Result<(), SimError> { Ok(()) }
+    fn reset(&mut self, _seed: u64) {}
     fn ui(&mut self, ui: &mut egui::Ui) { ui.label("No simulation selected."); }
-    fn render(&self, buffer: &mut Vec<u8>) { buffer.fill(0); }
+    fn render(&self, buffer: &mut [u8]) { buffer.fill(0); }
+}
+
+/// Set by an AsyncSim when its worker should stop: on reset, when the sim is unloaded and
+/// when it's dropped at shutdown. Clones share the flag, so a worker can hand it to its own
+/// helper threads or long-running loops.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Completes once the token is cancelled, checking a few times a frame.
+    #[cfg(feature = "tokio")]
+    pub async fn cancelled(&self) {
+        while !self.is_cancelled() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+/// The runtime every task-based AsyncSim worker is driven on, started on first use.
+#[cfg(feature = "tokio")]
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .thread_name("sim-runtime")
+            .enable_all()
+            .build()
+            .expect("failed to start the tokio runtime")
+    })
+}
+
+/// Holds an AsyncSim's worker between states while the App isn't stepping the sim, except
+/// for the states manual steps ask for.
+#[derive(Default)]
+struct PauseGate {
+    state: Mutex<GateState>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct GateState {
+    paused: bool,
+    /// States the worker may still produce while paused.
+    permits: usize,
+}
+
+impl PauseGate {
+    fn set_paused(&self, paused: bool) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.paused != paused {
+            *state = GateState { paused, permits: 0 };
+            self.changed.notify_all();
+        }
+    }
+
+    /// Lets a paused worker produce `n` more states.
+    fn allow(&self, n: usize) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.paused {
+            state.permits += n;
+            self.changed.notify_all();
+        }
+    }
+
+    /// Wakes every waiting worker to check its cancel token.
+    fn wake(&self) {
+        drop(self.state.lock().unwrap_or_else(PoisonError::into_inner));
+        self.changed.notify_all();
+    }
+
+    /// Blocks while paused with no permits left. Returns false if `cancel` is set.
+    fn wait(&self, cancel: &CancelToken) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while state.paused && state.permits == 0 && !cancel.is_cancelled() {
+            state = self.changed.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        state.permits = state.permits.saturating_sub(1);
+        !cancel.is_cancelled()
+    }
+}
+
+/// How an AsyncSim handles a worker that panics or calls `WorkerContext::fail`. The
+/// default never restarts it, leaving the error for the App to show.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RestartPolicy {
+    /// Restarts in a row before giving up and reporting the failure.
+    pub max_restarts: u32,
+    /// Wait before the first restart, doubled for each one after it.
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    /// A worker that runs this long before failing counts as healthy, so the restarts in a
+    /// row are counted afresh.
+    pub healthy_after: Duration,
+}
+
+impl RestartPolicy {
+    /// Restarts up to `max_restarts` times in a row with the default backoff.
+    pub fn retry(max_restarts: u32) -> Self {
+        Self { max_restarts, ..Self::default() }
+    }
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_restarts: 0,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            healthy_after: Duration::from_secs(600),
+        }
+    }
+}
+
+/// How an AsyncSim's worker hands its states to the UI.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ChannelMode {
+    /// Each state waits for the UI to take it, so the worker runs at the App's speed and
+    /// every state is shown.
+    #[default]
+    Rendezvous,
+    /// The worker never waits: each state replaces the one still pending and the UI takes
+    /// the newest. For sims that step far faster than frames are drawn, where a handover
+    /// per step would be the bottleneck. The speed setting no longer limits the worker,
+    /// and the steps in between are counted but never seen. Deltas are never dropped:
+    /// they're applied to a pending state, or queued until the UI takes them.
+    LatestWins,
+}
+
+/// A small change to an AsyncSim state, sent with `WorkerContext::send_delta` in place of
+/// a clone of the whole state. The UI applies it to the state it already holds, so large
+/// states (grids, particle arrays, long histories) aren't copied on every step. The worker
+/// applies the same delta to its own copy to keep the two in step.
+///
+/// ```
+/// use simulation_station::StateDelta;
+///
+/// #[derive(Clone, Default)]
+/// struct Walk { pos: i64, trail: Vec<i64> }
+///
+/// #[derive(Clone, Copy)]
+/// struct Moved(i64);
+///
+/// impl StateDelta<Walk> for Moved {
+///     fn apply(self, walk: &mut Walk) {
+///         walk.pos += self.0;
+///         walk.trail.push(walk.pos);
+///     }
+/// }
+///
+/// let mut walk = Walk::default();
+/// Moved(2).apply(&mut walk);
+/// Moved(-1).apply(&mut walk);
+/// assert_eq!(walk.trail, [2, 1]);
+/// ```
+pub trait StateDelta<T>: Send + 'static {
+    fn apply(self, state: &mut T);
+
+    /// How many steps of the sim this delta covers.
+    fn steps(&self) -> usize { 1 }
+}
+
+/// A batch of deltas, applied in order, for workers keeping up with `WorkerContext::batch`.
+impl<T, D: StateDelta<T>> StateDelta<T> for Vec<D> {
+    fn apply(self, state: &mut T) {
+        for delta in self {
+            delta.apply(state);
+        }
+    }
+
+    fn steps(&self) -> usize {
+        self.iter().map(D::steps).sum()
+    }
+}
+
+type Delta<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+/// What a worker hands over: a whole state, or changes to the last one.
+enum Update<T> {
+    State(T),
+    Delta(Delta<T>),
+    /// Deltas queued up while a `LatestWins` UI wasn't looking.
+    Deltas(Vec<Delta<T>>),
 }
 
-pub struct AsyncSim<T: Send + 'static + Default> {
+impl<T> Update<T> {
+    fn apply_to(self, state: &mut T) {
+        match self {
+            Update::State(new_state) => *state = new_state,
+            Update::Delta(delta) => delta(state),
+            Update::Deltas(deltas) => deltas.into_iter().for_each(|delta| delta(state)),
+        }
+    }
+
+    /// Folds `next` into this pending update, so nothing sent is lost.
+    fn then(self, next: Update<T>) -> Update<T> {
+        match (self, next) {
+            (_, Update::State(state)) => Update::State(state),
+            (Update::State(mut state), next) => {
+                next.apply_to(&mut state);
+                Update::State(state)
+            }
+            (Update::Delta(first), next) => Update::Deltas(vec![first]).then(next),
+            (Update::Deltas(mut deltas), Update::Delta(delta)) => {
+                deltas.push(delta);
+                Update::Deltas(deltas)
+            }
+            (Update::Deltas(mut deltas), Update::Deltas(rest)) => {
+                deltas.extend(rest);
+                Update::Deltas(deltas)
+            }
+        }
+    }
+}
+
+/// The newest update a `LatestWins` worker has sent, with how many it sent since the UI
+/// last took one.
+struct Latest<T> {
+    pending: Mutex<(Option<Update<T>>, usize)>,
+    /// Set once the worker is gone.
+    closed: AtomicBool,
+}
+
+/// Updates go with the number of steps they cover.
+enum StateSender<T> {
+    Rendezvous(SyncSender<(Update<T>, usize)>),
+    Latest(Arc<Latest<T>>),
+}
+
+enum StateReceiver<T> {
+    Rendezvous(Receiver<(Update<T>, usize)>),
+    Latest(Arc<Latest<T>>),
+}
+
+impl<T> StateReceiver<T> {
+    fn new(mode: ChannelMode) -> (StateSender<T>, Self) {
+        match mode {
+            ChannelMode::Rendezvous => {
+                let (tx, rx) = sync_channel(0);
+                (StateSender::Rendezvous(tx), StateReceiver::Rendezvous(rx))
+            }
+            ChannelMode::LatestWins => {
+                let pending = Mutex::new((None, 0));
+                let latest = Arc::new(Latest { pending, closed: AtomicBool::new(false) });
+                (StateSender::Latest(latest.clone()), StateReceiver::Latest(latest))
+            }
+        }
+    }
+
+    /// The next update and how many steps it's worth.
+    fn try_recv(&self) -> Result<(Update<T>, usize), TryRecvError> {
+        match self {
+            StateReceiver::Rendezvous(rx) => rx.try_recv(),
+            StateReceiver::Latest(latest) => {
+                // Read before taking, so a last state sent just before closing isn't lost
+                let closed = latest.closed.load(Ordering::Acquire);
+                let mut pending = latest.pending.lock().unwrap_or_else(PoisonError::into_inner);
+                match std::mem::take(&mut *pending) {
+                    (Some(update), steps) => Ok((update, steps)),
+                    (None, _) if closed => Err(TryRecvError::Disconnected),
+                    (None, _) => Err(TryRecvError::Empty),
+                }
+            }
+        }
+    }
+}
+
+/// The time a worker has spent on its task, for estimating how long the rest will take.
+#[derive(Clone, Copy)]
+struct ProgressClock {
+    started: Instant,
+    /// Time spent held by the pause gate.
+    paused: Duration,
+    /// The fraction first reported, which the clock restarts at.
+    from: Option<f32>,
+}
+
+impl ProgressClock {
+    fn new() -> Self {
+        Self { started: Instant::now(), paused: Duration::ZERO, from: None }
+    }
+}
+
+/// Handed to an AsyncSim worker: the channels to and from the UI plus framework-provided
+/// settings. `C` is the type of the commands the UI can send, if any.
+pub struct WorkerContext<T, C = ()> {
+    tx: StateSender<T>,
+    commands: Receiver<C>,
+    cancel: CancelToken,
+    gate: Arc<PauseGate>,
+    batch: Arc<AtomicUsize>,
+    progress: Arc<Mutex<Option<Progress>>>,
+    clock: Cell<ProgressClock>,
+    failure: Arc<Mutex<Option<SimError>>>,
+    #[cfg(feature = "parallel")]
+    pool: Option<Arc<rayon::ThreadPool>>,
+    checkpoints: Option<Arc<Checkpoints<T>>>,
+    /// Calls to `checkpoint` since one was last saved.
+    unsaved: Cell<u64>,
+    /// Seed chosen in the App; workers should build their RNG from it.
+    pub seed: u64,
+    /// Writes to the App's console, tagged with the sim's name.
+    pub log: SimLogger,
+}
+
+/// Lets a `LatestWins` receiver see that the worker returned, or panicked.
+impl<T, C> Drop for WorkerContext<T, C> {
+    fn drop(&mut self) {
+        if let StateSender::Latest(latest) = &self.tx {
+            latest.closed.store(true, Ordering::Release);
+        }
+    }
+}
+
+impl<T, C> WorkerContext<T, C> {
+    /// Publishes a state to the UI, blocking until it is taken (unless the sim uses
+    /// `ChannelMode::LatestWins`) and then for as long as the App is paused, so a paused
+    /// sim's worker computes nothing. Returns false once the sim has been reset or
+    /// unloaded, at which point the worker should return.
+    pub fn send(&self, state: T) -> bool {
+        self.hand_over(Update::State(state), 1)
+    }
+
+    /// Like `send`, for a state `steps` steps on from the last one sent.
+    pub fn send_batch(&self, state: T, steps: usize) -> bool {
+        self.hand_over(Update::State(state), steps)
+    }
+
+    /// Like `send`, but publishes a change to the last state sent rather than a whole new
+    /// one. The worker should apply the same delta to its own state.
+    pub fn send_delta(&self, delta: impl StateDelta<T>) -> bool {
+        let steps = delta.steps();
+        self.hand_over(Update::Delta(Box::new(|state: &mut T| delta.apply(state))), steps)
+    }
+
+    /// How many steps to take between sends for the App's speed setting: about a frame's
+    /// worth, since each handover to the UI waits for a frame. 1 at low speeds and while
+    /// stepping a paused sim one step at a time. Workers stepping faster than frames are
+    /// drawn should batch their sends with `send_batch`, or a `Vec` of deltas.
+    pub fn batch(&self) -> usize {
+        self.batch.load(Ordering::Relaxed).max(1)
+    }
+
+    fn hand_over(&self, update: Update<T>, steps: usize) -> bool {
+        if self.is_cancelled() {
+            return false;
+        }
+        let sent = match &self.tx {
+            StateSender::Rendezvous(tx) => tx.send((update, steps)).is_ok(),
+            StateSender::Latest(latest) => {
+                let mut pending = latest.pending.lock().unwrap_or_else(PoisonError::into_inner);
+                let update = match pending.0.take() {
+                    Some(earlier) => earlier.then(update),
+                    None => update,
+                };
+                *pending = (Some(update), pending.1 + steps);
+                true
+            }
+        };
+        if !sent {
+            return false;
+        }
+        let waiting = Instant::now();
+        let resumed = self.gate.wait(&self.cancel);
+        let clock = self.clock.get();
+        self.clock.set(ProgressClock { paused: clock.paused + waiting.elapsed(), ..clock });
+        resumed
+    }
+
+    /// Runs `op` on the sim's thread pool, so rayon's parallel iterators inside it use that
+    /// pool instead of the global one. Long loops inside should check a `cancel_token()`,
+    /// which unlike the context can be shared with the pool's threads.
+    #[cfg(feature = "parallel")]
+    pub fn parallel<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Reports an error the worker can't carry on from; it should return right after. The
+    /// App shows the message over the sim and pauses it.
+    pub fn fail(&self, error: impl Into<String>) {
+        let error = SimError::Other(error.into());
+        *self.failure.lock().unwrap_or_else(PoisonError::into_inner) = Some(error);
+    }
+
+    /// Reports how far through its task the worker is, 0 to 1, with a short note for the
+    /// progress bar. The time left is estimated from the pace since the first report,
+    /// leaving out time spent paused.
+    pub fn progress(&self, fraction: f32, message: impl Into<String>) {
+        let mut clock = self.clock.get();
+        let from = match clock.from {
+            Some(from) => from,
+            None => {
+                clock = ProgressClock { from: Some(fraction), ..ProgressClock::new() };
+                self.clock.set(clock);
+                fraction
+            }
+        };
+        let working = clock.started.elapsed().saturating_sub(clock.paused);
+        let done = fraction - from;
+        let eta = (done > 0.0).then(|| working.mul_f32((1.0 - fraction).max(0.0) / done));
+        let progress = Progress { fraction, eta, message: message.into() };
+        *self.progress.lock().unwrap_or_else(PoisonError::into_inner) = Some(progress);
+    }
+
+    /// Offers `state` as a checkpoint, saved on every Nth call when the sim has
+    /// `AsyncSim::with_checkpoints`. Call it wherever the worker could carry on from
+    /// `state` if it were handed it as its starting state.
+    pub fn checkpoint(&self, state: &T) {
+        let Some(checkpoints) = &self.checkpoints else { return };
+        let unsaved = self.unsaved.get() + 1;
+        if unsaved < checkpoints.every || self.is_cancelled() {
+            self.unsaved.set(unsaved);
+            return;
+        }
+        self.unsaved.set(0);
+        if let Err(e) = checkpoints.write(state) {
+            self.log.warn(format!("Could not save checkpoint: {e}"));
+        }
+    }
+
+    /// The commands sent with `AsyncSim::send_command` since the last call, oldest first.
+    /// Workers should check between steps, so settings can change or a search can jump
+    /// ahead without a reset.
+    pub fn commands(&self) -> impl Iterator<Item = C> + '_ {
+        self.commands.try_iter()
+    }
+
+    /// Whether the worker has been told to stop. Workers that compute for a while between
+    /// sends should check this in their inner loops and return promptly once it's set.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// The token behind `is_cancelled`, for threads the worker starts itself.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+}
+
+type Spawner<T, C> = Arc<dyn Fn(T, WorkerContext<T, C>) + Send + Sync>;
+type Stepper<T, C> = Arc<dyn Fn(&mut T, &WorkerContext<T, C>) -> bool + Send + Sync>;
+type Renderer<T> = Box<dyn Fn(&T, &mut [u8]) + Send + Sync>;
+type VectorRenderer<T> = Box<dyn Fn(&T, &egui::Painter, egui::Rect) + Send + Sync>;
+type UiDraw<T> = Box<dyn Fn(&T, &mut egui::Ui) + Send + Sync>;
+type HelpFn = Box<dyn Fn() -> SimHelp + Send + Sync>;
+type Prober<T> = Box<dyn Fn(&T, usize, usize) -> Option<String> + Send + Sync>;
+type MetricsFn<T> = Box<dyn Fn(&T, &mut Metrics) + Send + Sync>;
+type AnnotateFn<T> = Box<dyn Fn(&T, &mut Annotations) + Send + Sync>;
+type CommandUi<T, C> = Box<dyn Fn(&T, &mut egui::Ui, &mut Vec<C>) + Send + Sync>;
+type ProgressFn<T> = Box<dyn Fn(&T) -> Option<Progress> + Send + Sync>;
+
+enum ViewRenderer<T> {
+    Raster(Renderer<T>),
+    Vector(VectorRenderer<T>),
+}
+
+struct View<T> {
+    info: ViewInfo,
+    renderer: ViewRenderer<T>,
+}
+
+type Saver<T> = Box<dyn Fn(&T) -> Result<Vec<u8>, String> + Send + Sync>;
+type Loader<T> = Box<dyn Fn(&[u8]) -> Result<T, String> + Send + Sync>;
+
+/// The folder eframe keeps the App's settings in, for other files it writes on its own,
+/// or the working directory where there's no such folder.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn data_dir() -> std::path::PathBuf {
+    eframe::storage_dir("Simulation Station").unwrap_or_default()
+}
+
+/// Where and how often an AsyncSim's worker saves its progress.
+struct Checkpoints<T> {
+    path: std::path::PathBuf,
+    every: u64,
+    save: Saver<T>,
+    load: Loader<T>,
+}
+
+impl<T> Checkpoints<T> {
+    /// Writes beside the file and renames over it, so a crash mid-write can't leave a
+    /// truncated checkpoint. A state that fails to serialize leaves the last one alone.
+    fn write(&self, state: &T) -> std::io::Result<()> {
+        let data = (self.save)(state).map_err(std::io::Error::other)?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let partial = self.path.with_extension("json.partial");
+        std::fs::write(&partial, data)?;
+        std::fs::rename(partial, &self.path)
+    }
+
+    fn read(&self) -> Option<Result<T, String>> {
+        let data = std::fs::read(&self.path).ok()?;
+        Some((self.load)(&data))
+    }
+}
+
+/// Runs a simulation on a worker thread. The spawner receives the state to start from
+/// (`T::default()` on reset, or a restored snapshot) and streams states back through its
+/// `WorkerContext`, which also delivers any commands of type `C` the UI sends.
+pub struct AsyncSim<T: Clone + Send + 'static + Default, C: Send + 'static = ()> {
     name: String,
+    info: SimInfo,
     config: SimConfig,
     state: T,
-    receiver: Option<Receiver<T>>,
-    spawner: Arc<dyn Fn(SyncSender<T>) + Send + Sync>,
-    renderer: Box<dyn Fn(&T, &mut Vec<u8>) + Send + Sync>,
-    ui_draw: Box<dyn Fn(&T, &mut egui::Ui) + Send + Sync>,
+    receiver: Option<StateReceiver<T>>,
+    channel: ChannelMode,
+    worker: Option<JoinHandle<()>>,
+    /// Stops the current worker; replaced with a fresh token for each new one.
+    cancel: CancelToken,
+    /// Shared with every worker, so one started while paused waits too.
+    gate: Arc<PauseGate>,
+    /// Steps asked for in the last update, read by `WorkerContext::batch`.
+    batch: Arc<AtomicUsize>,
+    /// The current worker's last `WorkerContext::progress` report.
+    progress: Arc<Mutex<Option<Progress>>>,
+    /// Works progress out from the state when the worker doesn't report it.
+    on_progress: Option<ProgressFn<T>>,
+    /// Why the current worker stopped, if it panicked or gave up.
+    failure: Arc<Mutex<Option<SimError>>>,
+    restart_policy: RestartPolicy,
+    /// Restarts in a row after failures, for the policy's limit and backoff.
+    restarts: u32,
+    /// When a failed worker is due to be restarted.
+    restart_at: Option<Instant>,
+    /// Set once the worker returns without failing, which a crash never does.
+    finished: bool,
+    /// When the current worker started.
+    started: Instant,
+    /// Set by `with_parallelism`; otherwise `WorkerContext::parallel` uses rayon's global pool.
+    #[cfg(feature = "parallel")]
+    pool: Option<Arc<rayon::ThreadPool>>,
+    /// The thread count asked for with `with_parallelism`.
+    #[cfg(feature = "parallel")]
+    parallelism: Option<usize>,
+    worker_settings: WorkerSettings,
+    /// Queue to the current worker's `WorkerContext::commands`.
+    commands: Option<SyncSender<C>>,
+    seed: u64,
+    spawner: Spawner<T, C>,
+    /// Set by `AsyncSimBuilder::stepper`, which the web build runs in place of a thread.
+    #[cfg(target_arch = "wasm32")]
+    stepper: Option<Stepper<T, C>>,
+    /// The context a stepper runs with while it has steps left.
+    #[cfg(target_arch = "wasm32")]
+    local: Option<WorkerContext<T, C>>,
+    views: Vec<View<T>>,
+    view: usize,
+    ui_draw: UiDraw<T>,
+    command_ui: Option<CommandUi<T, C>>,
+    /// Bumped for every state the UI side takes in.
+    version: u64,
+    probe: Option<Prober<T>>,
+    metrics: Option<MetricsFn<T>>,
+    annotate: Option<AnnotateFn<T>>,
+    help: Option<HelpFn>,
+    snapshots: Option<(Saver<T>, Loader<T>)>,
+    checkpoints: Option<Arc<Checkpoints<T>>>,
+    /// Whether a reset picks up from the last checkpoint rather than `T::default()`.
+    resume: bool,
 }
 
-impl<T: Send + 'static + Default> AsyncSim<T> {
+impl<T: Clone + Send + 'static + Default, C: Send + 'static> AsyncSim<T, C> {
+    /// Starts a worker from `T::default()`. [`AsyncSim::builder`] takes the same pieces by
+    /// name, along with optional ones.
     pub fn new(
         name: &str,
         config: SimConfig,
-        spawner: impl Fn(SyncSender<T>) + Send + Sync + 'static,
-        renderer: impl Fn(&T, &mut Vec<u8>) + Send + Sync + 'static,
+        spawner: impl Fn(T, WorkerContext<T, C>) + Send + Sync + 'static,
+        renderer: impl Fn(&T, &mut [u8]) + Send + Sync + 'static,
         ui_draw: impl Fn(&T, &mut egui::Ui) + Send + Sync + 'static,
     ) -> Self {
-        let mut sim = Self {
+        Self::builder(name).config(config).spawner(spawner).renderer(renderer).ui(ui_draw).build()
+    }
+
+    /// Describes an AsyncSim piece by piece; no worker runs until `build`. Pieces left out
+    /// do nothing: without a spawner the sim finishes straight away, and without a renderer
+    /// the default view stays blank.
+    pub fn builder(name: &str) -> AsyncSimBuilder<T, C> {
+        let sim = Self {
             name: name.to_owned(),
-            config,
+            info: SimInfo::default(),
+            config: SimConfig::default(),
             state: T::default(),
             receiver: None,
-            spawner: Arc::new(spawner),
-            renderer: Box::new(renderer),
-            ui_draw: Box::new(ui_draw),
+            channel: ChannelMode::default(),
+            worker: None,
+            cancel: CancelToken::default(),
+            gate: Arc::default(),
+            batch: Arc::new(AtomicUsize::new(1)),
+            progress: Arc::default(),
+            on_progress: None,
+            failure: Arc::default(),
+            restart_policy: RestartPolicy::default(),
+            restarts: 0,
+            restart_at: None,
+            finished: false,
+            started: Instant::now(),
+            #[cfg(feature = "parallel")]
+            pool: None,
+            #[cfg(feature = "parallel")]
+            parallelism: None,
+            worker_settings: WorkerSettings::default(),
+            commands: None,
+            seed: 0,
+            spawner: Arc::new(|_: T, _: WorkerContext<T, C>| {}),
+            #[cfg(target_arch = "wasm32")]
+            stepper: None,
+            #[cfg(target_arch = "wasm32")]
+            local: None,
+            views: vec![View {
+                info: ViewInfo { name: "Default".to_owned(), render_mode: RenderMode::Raster },
+                renderer: ViewRenderer::Raster(Box::new(|_: &T, _: &mut [u8]| {})),
+            }],
+            view: 0,
+            ui_draw: Box::new(|_: &T, _: &mut egui::Ui| {}),
+            command_ui: None,
+            version: 0,
+            probe: None,
+            metrics: None,
+            annotate: None,
+            help: None,
+            snapshots: None,
+            checkpoints: None,
+            resume: true,
         };
-        sim.reset();
-        sim
+        AsyncSimBuilder { sim }
     }
-}
 
-impl<T: Send + 'static + Default> Simulation for AsyncSim<T> {
-    fn name(&self) -> &str { &self.name }
+    /// Like `new`, but the worker is an async task on a shared tokio runtime; see
+    /// [`AsyncSimBuilder::task`].
+    #[cfg(feature = "tokio")]
+    pub fn new_task<F>(
+        name: &str,
+        config: SimConfig,
+        task: impl Fn(T, WorkerContext<T, C>) -> F + Send + Sync + 'static,
+        renderer: impl Fn(&T, &mut [u8]) + Send + Sync + 'static,
+        ui_draw: impl Fn(&T, &mut egui::Ui) + Send + Sync + 'static,
+    ) -> Self
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        Self::builder(name).config(config).task(task).renderer(renderer).ui(ui_draw).build()
+    }
 
-    fn config(&self) -> SimConfig { self.config }
+    pub fn with_info(mut self, info: SimInfo) -> Self {
+        self.info = info;
+        self
+    }
 
-    fn update(&mut self) {
-        if let Some(rx) = &self.receiver {
-            if let Ok(new_state) = rx.try_recv() {
-                self.state = new_state;
+    /// Adds a view rasterized into the pixel buffer, after the default one passed to `new`.
+    pub fn with_raster_view(
+        mut self,
+        name: &str,
+        renderer: impl Fn(&T, &mut [u8]) + Send + Sync + 'static,
+    ) -> Self {
+        self.views.push(View {
+            info: ViewInfo { name: name.to_owned(), render_mode: RenderMode::Raster },
+            renderer: ViewRenderer::Raster(Box::new(renderer)),
+        });
+        self
+    }
+
+    /// Adds a view painted with the egui Painter (`RenderMode::Vector`).
+    pub fn with_vector_view(
+        mut self,
+        name: &str,
+        renderer: impl Fn(&T, &egui::Painter, egui::Rect) + Send + Sync + 'static,
+    ) -> Self {
+        self.views.push(View {
+            info: ViewInfo { name: name.to_owned(), render_mode: RenderMode::Vector },
+            renderer: ViewRenderer::Vector(Box::new(renderer)),
+        });
+        self
+    }
+
+    /// Answers `help()` for the help window.
+    pub fn with_help(mut self, help: impl Fn() -> SimHelp + Send + Sync + 'static) -> Self {
+        self.help = Some(Box::new(help));
+        self
+    }
+
+    /// Answers `probe()` for the canvas's hover readout.
+    pub fn with_probe(
+        mut self,
+        probe: impl Fn(&T, usize, usize) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.probe = Some(Box::new(probe));
+        self
+    }
+
+    /// Answers `record_metrics()` from the latest state.
+    pub fn with_metrics(mut self, metrics: impl Fn(&T, &mut Metrics) + Send + Sync + 'static) -> Self {
+        self.metrics = Some(Box::new(metrics));
+        self
+    }
+
+    /// Answers `annotate()` from the latest state.
+    pub fn with_annotations(
+        mut self,
+        annotate: impl Fn(&T, &mut Annotations) + Send + Sync + 'static,
+    ) -> Self {
+        self.annotate = Some(Box::new(annotate));
+        self
+    }
+
+    /// Queues `command` for the worker, which picks it up from `WorkerContext::commands`;
+    /// a paused worker is let through one step to act on it. Returns false if there's no
+    /// worker or it's fallen behind on its queue.
+    pub fn send_command(&self, command: C) -> bool {
+        let sent = self.commands.as_ref().is_some_and(|tx| tx.try_send(command).is_ok());
+        if sent {
+            self.gate.allow(1);
+        }
+        sent
+    }
+
+    /// Draws controls under the `ui_draw` panel that steer the running worker: commands
+    /// pushed onto the Vec are passed to `send_command`.
+    pub fn with_command_ui(
+        mut self,
+        command_ui: impl Fn(&T, &mut egui::Ui, &mut Vec<C>) + Send + Sync + 'static,
+    ) -> Self {
+        self.command_ui = Some(Box::new(command_ui));
+        self
+    }
+
+    /// Has failed workers restarted, after a backoff, from the last checkpoint or else the
+    /// latest state, so long unattended runs survive the odd crash. Each restart is logged.
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Switches how the worker hands over states, restarting it.
+    pub fn with_channel(mut self, mode: ChannelMode) -> Self {
+        self.channel = mode;
+        self.restart_from(T::default());
+        self
+    }
+
+    /// Gives the worker a pool of `threads` threads, or one per core for 0, to fan out over
+    /// with `WorkerContext::parallel`, unless the App's worker settings choose a count.
+    /// Restarts the worker.
+    #[cfg(feature = "parallel")]
+    pub fn with_parallelism(mut self, threads: usize) -> Self {
+        self.parallelism = Some(threads);
+        self.build_pool();
+        self.restart_from(T::default());
+        self
+    }
+
+    /// Builds the pool `WorkerContext::parallel` runs on, sized by the worker settings or
+    /// else `with_parallelism`. With neither, at normal priority, rayon's global pool does.
+    #[cfg(feature = "parallel")]
+    fn build_pool(&mut self) {
+        let WorkerSettings { threads, priority } = self.worker_settings;
+        let threads = if threads > 0 { Some(threads) } else { self.parallelism };
+        if threads.is_none() && priority == WorkerPriority::Normal {
+            self.pool = None;
+            return;
+        }
+        let name = self.name.clone();
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.unwrap_or(0))
+            .thread_name(move |i| format!("{name} #{i}"))
+            // Lowering a priority can't fail for want of permission, so errors are ignored
+            .start_handler(move |_| drop(workers::set_thread_priority(priority)))
+            .build();
+        self.pool = match pool {
+            Ok(pool) => Some(Arc::new(pool)),
+            Err(e) => {
+                SimLogger::new(&self.name).warn(format!("No thread pool, using the global one: {e}"));
+                None
             }
+        };
+    }
+
+    /// Enables `save_state`/`load_state` by serializing the worker state as JSON. A state
+    /// that won't serialize is reported in the console and no snapshot is taken.
+    pub fn with_snapshots(mut self) -> Self
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.snapshots = Some((
+            Box::new(|state| serde_json::to_vec(state).map_err(|e| e.to_string())),
+            Box::new(|data| serde_json::from_slice(data).map_err(|e| e.to_string())),
+        ));
+        self
+    }
+
+    /// Has the worker's `WorkerContext::checkpoint` calls save its state, as JSON, on
+    /// every `every`th call. While "Resume from checkpoint" is ticked in the panel, resets
+    /// and the next launch of the App carry on from the last one instead of starting over.
+    /// Checkpoints are kept in the App's data folder, beside its saved settings, and
+    /// aren't available on the web.
+    pub fn with_checkpoints(mut self, every: u64) -> Self
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.enable_checkpoints(every);
+        self.reset(self.seed);
+        self
+    }
+
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    fn enable_checkpoints(&mut self, every: u64)
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let path = data_dir().join(format!("{}.checkpoint.json", export::slug(&self.name)));
+            self.checkpoints = Some(Arc::new(Checkpoints {
+                path,
+                every: every.max(1),
+                save: Box::new(|state| serde_json::to_vec(state).map_err(|e| e.to_string())),
+                load: Box::new(|data| serde_json::from_slice(data).map_err(|e| e.to_string())),
+            }));
         }
     }
 
-    fn reset(&mut self) {
-        let (tx, rx) = sync_channel(0);
+    /// The last checkpoint, if resuming is on and there is a readable one.
+    fn resume_point(&self) -> Option<T> {
+        let checkpoints = self.checkpoints.as_ref().filter(|_| self.resume)?;
+        let log = SimLogger::new(&self.name);
+        match checkpoints.read()? {
+            Ok(state) => {
+                log.info("Resuming from the last checkpoint");
+                Some(state)
+            }
+            Err(e) => {
+                log.warn(format!("Ignoring unreadable checkpoint: {e}"));
+                None
+            }
+        }
+    }
+
+    /// Cancels the running worker, if any, and starts a new one from `start`.
+    fn restart_from(&mut self, start: T) {
+        self.stop_worker();
+        let (tx, rx) = StateReceiver::new(self.channel);
         self.receiver = Some(rx);
-        self.state = T::default();
+        self.state = start.clone();
+        self.version += 1;
+        self.cancel = CancelToken::default();
+        self.progress = Arc::default();
+        self.failure = Arc::default();
+        self.restart_at = None;
+        self.finished = false;
+        self.started = Instant::now();
+        let (command_tx, command_rx) = sync_channel(64);
+        self.commands = Some(command_tx);
 
-        let spawner = self.spawner.clone();
+        let ctx = WorkerContext {
+            tx,
+            commands: command_rx,
+            cancel: self.cancel.clone(),
+            gate: self.gate.clone(),
+            batch: self.batch.clone(),
+            progress: self.progress.clone(),
+            clock: Cell::new(ProgressClock::new()),
+            failure: self.failure.clone(),
+            #[cfg(feature = "parallel")]
+            pool: self.pool.clone(),
+            checkpoints: self.checkpoints.clone(),
+            unsaved: Cell::new(0),
+            seed: self.seed,
+            log: SimLogger::new(&self.name),
+        };
 
-        std::thread::spawn(move || {
-            (spawner)(tx);
-        });
+        // No threads on the web: a stepper is run from `update` instead, and anything else
+        // fails straight away, the dropped context disconnecting the receiver
+        #[cfg(target_arch = "wasm32")]
+        if self.stepper.is_some() {
+            self.local = Some(ctx);
+        } else {
+            let error = SimError::Other(
+                "The web build can't start worker threads; only sims built with a stepper run here"
+                    .to_owned(),
+            );
+            *self.failure.lock().unwrap_or_else(PoisonError::into_inner) = Some(error);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let spawner = self.spawner.clone();
+            let failure = self.failure.clone();
+            let priority = self.worker_settings.priority;
+            self.worker = Some(std::thread::spawn(move || {
+                if let Err(e) = workers::set_thread_priority(priority) {
+                    ctx.log.warn(format!("Could not lower the worker's priority: {e}"));
+                }
+                let run = std::panic::AssertUnwindSafe(|| (spawner)(start, ctx));
+                if let Err(payload) = std::panic::catch_unwind(run) {
+                    let error = SimError::WorkerPanicked(panic_message(payload.as_ref()));
+                    *failure.lock().unwrap_or_else(PoisonError::into_inner) = Some(error);
+                }
+            }));
+        }
     }
 
-    fn render(&self, buffer: &mut Vec<u8>) {
-        (self.renderer)(&self.state, buffer);
+    /// Runs up to `n` of a stepper's steps on this thread, in the web build. Once it's out
+    /// of steps, reports how it ended like `reap_worker`.
+    #[cfg(target_arch = "wasm32")]
+    fn step_locally(&mut self, n: usize) -> Result<usize, SimError> {
+        let (Some(step), Some(ctx)) = (&self.stepper, &self.local) else { return Ok(0) };
+        self.batch.store(n, Ordering::Relaxed);
+        let mut done = 0;
+        let mut finished = false;
+        while done < n && !ctx.is_cancelled() {
+            if !step(&mut self.state, ctx) {
+                finished = true;
+                break;
+            }
+            done += 1;
+        }
+        if done > 0 {
+            self.version += 1;
+        }
+        if finished || ctx.is_cancelled() {
+            self.local = None;
+            return self.reap_worker().map(|()| done);
+        }
+        Ok(done)
     }
 
-    fn ui(&mut self, ui: &mut egui::Ui) {
-        (self.ui_draw)(&self.state, ui);
+    /// Tells the worker to stop and drops the receiving end, which also wakes it if it's
+    /// blocked in `send`. The thread is detached rather than joined so a worker that's slow
+    /// to notice can't stall the UI.
+    fn stop_worker(&mut self) {
+        self.cancel.cancel();
+        self.gate.wake();
+        self.receiver = None;
+        self.commands = None;
+        self.worker = None;
+        self.restart_at = None;
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.local = None;
+        }
+    }
+
+    /// Called once the channel disconnects: joins the worker and reports how it failed,
+    /// if it panicked or called `WorkerContext::fail`.
+    /// A failure the restart policy covers is logged and a restart scheduled instead.
+    fn reap_worker(&mut self) -> Result<(), SimError> {
+        self.receiver = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        let Some(error) = self.failure.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+            self.finished = true;
+            return Ok(());
+        };
+        let policy = self.restart_policy;
+        if self.started.elapsed() >= policy.healthy_after {
+            self.restarts = 0;
+        }
+        if self.restarts >= policy.max_restarts {
+            return Err(error);
+        }
+        let delay = policy.backoff.saturating_mul(1 << self.restarts.min(16)).min(policy.max_backoff);
+        self.restarts += 1;
+        SimLogger::new(&self.name).warn(format!(
+            "{error}; restarting in {:.0}s ({} of {})",
+            delay.as_secs_f32(),
+            self.restarts,
+            policy.max_restarts
+        ));
+        self.restart_at = Some(Instant::now() + delay);
+        Ok(())
+    }
+
+    /// Starts a failed worker again once its backoff is over.
+    fn restart_if_due(&mut self) {
+        if self.restart_at.is_some_and(|at| Instant::now() >= at) {
+            let checkpoint = self.checkpoints.as_ref().and_then(|checkpoints| checkpoints.read());
+            let start = checkpoint.and_then(Result::ok).unwrap_or_else(|| self.state.clone());
+            self.restart_from(start);
+        }
     }
 }
 
-pub struct App {
-    current_sim: Box<dyn Simulation>,
-    is_paused: bool,
-    updates_per_second: f32,
-    time_accumulator: f32,
-    texture: Option<egui::TextureHandle>,
+/// Puts an [`AsyncSim`] together by name rather than position, from
+/// [`AsyncSim::builder`]. Settings that would restart a running worker are made here
+/// before it first starts; views, help and the rest are added with the `with_*` methods
+/// on the built sim.
+///
+/// ```
+/// use simulation_station::{AsyncSim, SimConfig};
+///
+/// let sim: AsyncSim<u64> = AsyncSim::builder("Counter")
+///     .config(SimConfig { max_speed: 1000.0, ..SimConfig::default() })
+///     .spawner(|mut n, ctx| while n < 100 && ctx.send(n) { n += 1 })
+///     .ui(|n, ui| { ui.label(format!("Count: {n}")); })
+///     .build();
+/// ```
+pub struct AsyncSimBuilder<T: Clone + Send + 'static + Default, C: Send + 'static = ()> {
+    /// Not started until `build`.
+    sim: AsyncSim<T, C>,
 }
 
-impl App {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self {
-            current_sim: Box::new(NoSim),
-            is_paused: false,
-            updates_per_second: 60.0,
-            time_accumulator: 0.0,
-            texture: None,
+impl<T: Clone + Send + 'static + Default, C: Send + 'static> AsyncSimBuilder<T, C> {
+    pub fn config(mut self, config: SimConfig) -> Self {
+        self.sim.config = config;
+        self
+    }
+
+    /// Runs on the worker thread: given the state to start from (`T::default()` on reset,
+    /// or a restored snapshot or checkpoint), it streams states back through the context
+    /// and returns when done or when `send` returns false. The web build has no threads to
+    /// run it on; sims meant to run there too use [`stepper`](Self::stepper) instead.
+    pub fn spawner(mut self, spawner: impl Fn(T, WorkerContext<T, C>) + Send + Sync + 'static) -> Self {
+        self.sim.spawner = Arc::new(spawner);
+        self
+    }
+
+    /// Runs the worker as an async task on a shared tokio runtime instead, for sims fed by
+    /// the network, files or timers that would otherwise tie up a thread waiting. The task
+    /// is dropped as soon as the sim is reset or unloaded, wherever it's waiting.
+    ///
+    /// Also switches to `ChannelMode::LatestWins`, so `send` never holds up the runtime
+    /// waiting for the UI; it still blocks while the App is paused.
+    #[cfg(feature = "tokio")]
+    pub fn task<F>(self, task: impl Fn(T, WorkerContext<T, C>) -> F + Send + Sync + 'static) -> Self
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let spawner = move |start: T, ctx: WorkerContext<T, C>| {
+            use std::task::Poll;
+            let cancel = ctx.cancel_token();
+            let mut task = std::pin::pin!(task(start, ctx));
+            let mut cancelled = std::pin::pin!(cancel.cancelled());
+            runtime().block_on(std::future::poll_fn(|cx| {
+                if task.as_mut().poll(cx).is_ready() || cancelled.as_mut().poll(cx).is_ready() {
+                    Poll::Ready(())
+                } else {
+                    Poll::Pending
+                }
+            }));
+        };
+        self.spawner(spawner).channel(ChannelMode::LatestWins)
+    }
+
+    /// Runs the sim a step at a time rather than in a loop of its own: `step` advances the
+    /// state by one step, or returns false if there are none left. Natively it runs on a
+    /// worker thread like any spawner, sending states in batches as `WorkerContext::batch`
+    /// asks. On the web, which has no threads, the App calls it a frame's worth of steps
+    /// at a time, so long steps hold up drawing.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(unused_mut))]
+    pub fn stepper(
+        mut self,
+        step: impl Fn(&mut T, &WorkerContext<T, C>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let step: Stepper<T, C> = Arc::new(step);
+        #[cfg(target_arch = "wasm32")]
+        {
+            self.sim.stepper = Some(step.clone());
         }
+        self.spawner(move |mut state, ctx| loop {
+            let mut steps = 0;
+            while steps < ctx.batch() && step(&mut state, &ctx) {
+                steps += 1;
+            }
+            if steps == 0 || !ctx.send_batch(state.clone(), steps) {
+                return;
+            }
+        })
+    }
+
+    /// Draws the default view into the pixel buffer.
+    pub fn renderer(mut self, renderer: impl Fn(&T, &mut [u8]) + Send + Sync + 'static) -> Self {
+        self.sim.views[0].renderer = ViewRenderer::Raster(Box::new(renderer));
+        self
+    }
+
+    /// Fills the sim's panel.
+    pub fn ui(mut self, ui_draw: impl Fn(&T, &mut egui::Ui) + Send + Sync + 'static) -> Self {
+        self.sim.ui_draw = Box::new(ui_draw);
+        self
+    }
+
+    /// Works out the progress bar from the latest state, for workers that don't call
+    /// `WorkerContext::progress`. Their own reports win when they do.
+    pub fn on_progress(
+        mut self,
+        progress: impl Fn(&T) -> Option<Progress> + Send + Sync + 'static,
+    ) -> Self {
+        self.sim.on_progress = Some(Box::new(progress));
+        self
     }
 
-    fn load_sim(&mut self, sim: Box<dyn Simulation>) {
-        let cfg = sim.config();
-        self.updates_per_second = cfg.default_speed;
-        self.current_sim = sim;
+    /// Same as `AsyncSim::with_command_ui`.
+    pub fn commands(
+        mut self,
+        command_ui: impl Fn(&T, &mut egui::Ui, &mut Vec<C>) + Send + Sync + 'static,
+    ) -> Self {
+        self.sim.command_ui = Some(Box::new(command_ui));
+        self
+    }
+
+    /// Same as `AsyncSim::with_channel`, without the restart.
+    pub fn channel(mut self, mode: ChannelMode) -> Self {
+        self.sim.channel = mode;
+        self
+    }
+
+    /// Same as `AsyncSim::with_parallelism`, without the restart.
+    #[cfg(feature = "parallel")]
+    pub fn parallelism(mut self, threads: usize) -> Self {
+        self.sim.parallelism = Some(threads);
+        self
+    }
+
+    /// Same as `AsyncSim::with_checkpoints`; the first worker already starts from the last
+    /// checkpoint.
+    pub fn checkpoints(mut self, every: u64) -> Self
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.sim.enable_checkpoints(every);
+        self
+    }
+
+    /// Starts the worker.
+    pub fn build(self) -> AsyncSim<T, C> {
+        let mut sim = self.sim;
+        #[cfg(feature = "parallel")]
+        sim.build_pool();
+        sim.reset(0);
+        sim
     }
 }
 
-impl eframe::App for App {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::SidePanel::left("controls").min_width(200.0).show(ctx, |ui| {
-            ui.heading("Simulation Station");
-            ui.separator();
+impl<T: Clone + Send + 'static + Default, C: Send + 'static> Simulation for AsyncSim<T, C> {
+    fn name(&self) -> &str { &self.name }
 
-            ui.label("Load Simulation:");
-            egui::ComboBox::from_id_salt("sim_select")
-                .selected_text(self.current_sim.name())
-                .show_ui(ui, |ui| {
-                    if ui.selectable_label(false, "None").clicked() {
-                        self.load_sim(Box::new(NoSim));
-                    }
+    fn info(&self) -> SimInfo { self.info }
 
-                    if ui.selectable_label(false, "Simple Pixel Fill").clicked() {
-                        self.load_sim(Box::new(simple_grid::PixelFillSim::new()));
-                    }
+    fn help(&self) -> SimHelp { self.help.as_ref().map(|help| help()).unwrap_or_default() }
 
-                    if ui.selectable_label(false, "Problem 14: Collatz").clicked() {
-                        let sim = AsyncSim::new(
-                            "Problem 14: Collatz",
-                            SimConfig {
-                                min_speed: 1.0,
-                                max_speed: 50_000.0,
-                                default_speed: 10_000.0,
-                            },
-                            p0014::solve,
-                            p0014::render,
-                            p0014::ui,
-                        );
-                        self.load_sim(Box::new(sim));
-                    }
-                });
+    fn config(&self) -> SimConfig { self.config }
 
-            ui.separator();
+    fn capabilities(&self) -> Capabilities {
+        let mut caps = Capabilities::EXPORT | Capabilities::WORKERS;
+        caps.set(Capabilities::SNAPSHOTS, self.snapshots.is_some());
+        caps
+    }
 
-            ui.horizontal(|ui| {
-                if ui.button(if self.is_paused { "Resume" } else { "Pause" }).clicked() {
-                    self.is_paused = !self.is_paused;
-                }
-                if ui.button("Reset").clicked() {
-                    self.current_sim.reset();
+    fn update(&mut self) -> Result<(), SimError> {
+        self.restart_if_due();
+        #[cfg(target_arch = "wasm32")]
+        if self.local.is_some() {
+            return self.step_locally(1).map(drop);
+        }
+        let Some(rx) = &self.receiver else { return Ok(()) };
+        self.gate.allow(1);
+        self.batch.store(1, Ordering::Relaxed);
+        match rx.try_recv() {
+            Ok((update, _)) => {
+                update.apply_to(&mut self.state);
+                self.version += 1;
+                Ok(())
+            }
+            Err(TryRecvError::Empty) => Ok(()),
+            Err(TryRecvError::Disconnected) => self.reap_worker(),
+        }
+    }
+
+    /// Takes whatever update the worker has ready, up to `n` steps' worth, applying deltas
+    /// in order. A `Rendezvous` channel buffers nothing, so this usually takes just the one
+    /// update the worker is blocked sending rather than `n`; a `LatestWins` update counts
+    /// for every step the worker took since the last one.
+    fn update_n(&mut self, n: usize) -> Result<usize, SimError> {
+        self.restart_if_due();
+        #[cfg(target_arch = "wasm32")]
+        if self.local.is_some() {
+            return self.step_locally(n);
+        }
+        let mut received = 0;
+        let mut result = Ok(());
+        if let Some(rx) = &self.receiver {
+            self.gate.allow(n);
+            self.batch.store(n, Ordering::Relaxed);
+            while received < n {
+                match rx.try_recv() {
+                    Ok((update, steps)) => {
+                        update.apply_to(&mut self.state);
+                        received += steps;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        result = self.reap_worker();
+                        break;
+                    }
                 }
-            });
+            }
+        }
+        if received > 0 {
+            self.version += 1;
+        }
+        result.map(|()| received)
+    }
 
-            ui.add(
-                egui::Slider::new(&mut self.updates_per_second, 0.5..=10_000.0)
-                    .text("Hz (Ops/Sec)")
-                    .logarithmic(true)
-            );
+    fn version(&self) -> Option<u64> {
+        Some(self.version)
+    }
 
-            ui.separator();
+    /// Only a worker that returned normally counts; a crash is reported by `update` instead.
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
 
-            self.current_sim.ui(ui);
-        });
+    fn set_running(&mut self, running: bool) {
+        self.gate.set_paused(!running);
+    }
 
-        if !self.is_paused {
-            // 1. Get time passed since last frame (Delta Time)
-            let dt = ctx.input(|i| i.stable_dt);
-            self.time_accumulator += dt;
+    /// Restarts the worker on new threads, carrying on from the latest state.
+    fn set_worker_settings(&mut self, settings: WorkerSettings) {
+        if settings == self.worker_settings {
+            return;
+        }
+        self.worker_settings = settings;
+        #[cfg(feature = "parallel")]
+        self.build_pool();
+        self.restart_from(self.state.clone());
+    }
 
-            // 2. Calculate how long ONE step should take
-            // Example: 10 Hz = 0.1s per step
-            let step_duration = 1.0 / self.updates_per_second;
+    fn on_unload(&mut self) {
+        self.stop_worker();
+    }
 
-            // 3. "Spend" the accumulated time to run updates
-            // If speed is 1000Hz, this loop runs ~16 times per 60Hz frame.
-            // If speed is 1Hz, this loop runs once every 60 frames.
-            let mut loops = 0;
-            while self.time_accumulator >= step_duration && loops < 5000 {
-                self.current_sim.update(); // Allows thread to proceed one step
-                self.time_accumulator -= step_duration;
-                loops += 1;
-            }
+    fn reset(&mut self, seed: u64) {
+        self.seed = seed;
+        let start = self.resume_point().unwrap_or_default();
+        self.restarts = 0;
+        self.restart_from(start);
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        if let ViewRenderer::Raster(renderer) = &self.views[self.view].renderer {
+            renderer(&self.state, buffer);
         }
+    }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let w = 400;
-            let h = 300;
-            let mut pixel_buffer = vec![0; w * h * 3];
+    fn render_vector(&self, painter: &egui::Painter, rect: egui::Rect) {
+        if let ViewRenderer::Vector(renderer) = &self.views[self.view].renderer {
+            renderer(&self.state, painter, rect);
+        }
+    }
 
-            self.current_sim.render(&mut pixel_buffer);
+    fn views(&self) -> Vec<ViewInfo> {
+        self.views.iter().map(|view| view.info.clone()).collect()
+    }
 
-            let image = egui::ColorImage::from_rgb([w, h], &pixel_buffer);
-            self.texture = Some(ctx.load_texture("display", image, egui::TextureOptions::NEAREST));
+    fn select_view(&mut self, index: usize) {
+        self.view = index.min(self.views.len() - 1);
+    }
 
-            if let Some(texture) = &self.texture {
-                ui.image((texture.id(), ui.available_size()));
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        (self.ui_draw)(&self.state, ui);
+        if let Some(command_ui) = &self.command_ui {
+            let mut commands = Vec::new();
+            command_ui(&self.state, ui, &mut commands);
+            for command in commands {
+                self.send_command(command);
             }
-        });
+        }
+        if let Some(checkpoints) = &self.checkpoints {
+            ui.separator();
+            ui.checkbox(&mut self.resume, "Resume from checkpoint")
+                .on_hover_text("Reset carries on from the last saved progress instead of starting over");
+            if checkpoints.path.exists() && ui.button("Discard checkpoint").clicked() {
+                let _ = std::fs::remove_file(&checkpoints.path);
+            }
+        }
+    }
+
+    fn progress(&self) -> Option<Progress> {
+        let reported = self.progress.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        reported.or_else(|| self.on_progress.as_ref().and_then(|progress| progress(&self.state)))
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        self.probe.as_ref().and_then(|probe| probe(&self.state, x, y))
+    }
+
+    fn record_metrics(&self, metrics: &mut Metrics) {
+        if let Some(record) = &self.metrics {
+            record(&self.state, metrics);
+        }
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        if let Some(annotate) = &self.annotate {
+            annotate(&self.state, annotations);
+        }
+    }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        let (save, _) = self.snapshots.as_ref()?;
+        save(&self.state)
+            .map_err(|e| SimLogger::new(&self.name).error(format!("Could not take a snapshot: {e}")))
+            .ok()
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), SimError> {
+        let (_, load) = self
+            .snapshots
+            .as_ref()
+            .ok_or_else(|| SimError::State(format!("{} does not support snapshots", self.name)))?;
+        let state = load(data).map_err(SimError::State)?;
+        self.restart_from(state);
+        Ok(())
+    }
+}
+
+/// Stops the worker when the sim goes away without being unloaded, e.g. at shutdown.
+impl<T: Clone + Send + 'static + Default, C: Send + 'static> Drop for AsyncSim<T, C> {
+    fn drop(&mut self) {
+        self.stop_worker();
+    }
+}
 
-        ctx.request_repaint();
+#[cfg(not(target_arch = "wasm32"))]
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        (*msg).to_owned()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_owned()
     }
 }
@@ -0,0 +1,507 @@
+use eframe::egui;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::{
+    sim_params, BitGrid, Brush, Capabilities, Category, Grid, LifeGrid, LifeRule, Metrics,
+    Neighborhood, Params, Region, SimConfig, SimError, SimHelp, SimInfo, SimInput, Simulation,
+    SparseGrid,
+};
+
+pub const INFO: SimInfo = SimInfo {
+    description: "Conway's Game of Life, or any Life-like rule, on a torus or an endless \
+                  plane: draw cells, seed a random soup or load an RLE pattern.",
+    category: Category::CellularAutomata,
+    tags: &["grid", "life", "cellular automaton", "unbounded"],
+};
+
+const WIDTH: usize = 480;
+const HEIGHT: usize = 360;
+/// The window's `origin` to begin with, putting (0, 0) in the middle.
+const ORIGIN: (i64, i64) = (-(WIDTH as i64) / 2, -(HEIGHT as i64) / 2);
+/// How close the live cells on the plane may come to the window's edge before it slides.
+const MARGIN: i64 = 16;
+
+/// Patterns the stamp picker offers, as RLE.
+const PATTERNS: &[(&str, &str)] = &[
+    ("Glider", "x = 3, y = 3\nbo$2bo$3o!"),
+    ("Lightweight spaceship", "x = 5, y = 4\nbo2bo$o4b$o3bo$4o!"),
+    ("R-pentomino", "x = 3, y = 3\nb2o$2ob$bo!"),
+    ("Acorn", "x = 7, y = 3\nbo5b$3bo3b$2o2b3o!"),
+    ("Pulsar", "x = 13, y = 13\n2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$2b3o3b3o2b$\
+                o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o2b!"),
+    ("Gosper glider gun", "x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+                           2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!"),
+];
+
+sim_params! {
+    pub struct LifeParams {
+        pub density: f32 = 0.3 => 0.0..=1.0,
+        pub wrap: bool = true,
+        pub alive_color: [u8; 3] = [240, 240, 230],
+        pub dead_color: [u8; 3] = [20, 22, 30],
+    }
+}
+
+/// Where the cells live: a torus the size of the window, or a plane going on past it.
+enum Board {
+    Torus(BitGrid),
+    Plane(SparseGrid<bool>),
+}
+
+/// The state kept by `save_state`, with live cells in plane coordinates.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    params: LifeParams,
+    rule: String,
+    generation: u64,
+    origin: (i64, i64),
+    cells: Vec<(i64, i64)>,
+}
+
+/// Life-like automata on a [`BitGrid`] torus, or on a [`SparseGrid`] plane stepped by
+/// counting the neighbors of live cells only. The plane is seen through a window that
+/// starts centered on (0, 0) and slides to keep the live cells in view.
+pub struct LifeSim {
+    board: Board,
+    rule: LifeRule,
+    /// What the rule box holds, which may not parse yet.
+    rule_text: String,
+    params: LifeParams,
+    generation: u64,
+    /// Cells born and cells that died in the last generation.
+    births: usize,
+    deaths: usize,
+    /// The last pattern loaded, which `reset` puts back instead of a random soup.
+    pattern: Option<Grid<bool>>,
+    rng: StdRng,
+    brush: Brush<bool>,
+    /// The entry of `PATTERNS` the brush last picked up as its stamp.
+    stamp: Option<usize>,
+    /// The plane cell shown at the window's top-left corner. The torus is the window.
+    origin: (i64, i64),
+    /// The torus's cells before the last step, kept to reuse its allocation.
+    before: Vec<u64>,
+    version: u64,
+}
+
+impl LifeSim {
+    pub fn new() -> Self {
+        let mut sim = Self {
+            board: Board::Torus(BitGrid::new(WIDTH, HEIGHT).with_wrap(true)),
+            rule: LifeRule::CONWAY,
+            rule_text: LifeRule::CONWAY.to_string(),
+            params: LifeParams::default(),
+            generation: 0,
+            births: 0,
+            deaths: 0,
+            pattern: None,
+            rng: StdRng::seed_from_u64(0),
+            brush: Brush::new(true, false),
+            stamp: None,
+            origin: ORIGIN,
+            before: Vec::new(),
+            version: 0,
+        };
+        sim.reset(0);
+        sim
+    }
+
+    /// Window coordinates of plane cell (x, y), if it's in the window.
+    fn to_window(&self, x: i64, y: i64) -> Option<(usize, usize)> {
+        let (x, y) = (x - self.origin.0, y - self.origin.1);
+        let inside = (0..WIDTH as i64).contains(&x) && (0..HEIGHT as i64).contains(&y);
+        inside.then_some((x as usize, y as usize))
+    }
+
+    /// Every live cell, in plane coordinates.
+    fn live_cells(&self) -> Vec<(i64, i64)> {
+        let origin = self.origin;
+        match &self.board {
+            Board::Torus(bits) => (0..HEIGHT)
+                .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+                .filter(|&(x, y)| bits.alive(x, y))
+                .map(|(x, y)| (x as i64 + origin.0, y as i64 + origin.1))
+                .collect(),
+            Board::Plane(cells) => cells.iter().map(|(pos, _)| pos).collect(),
+        }
+    }
+
+    fn population(&self) -> usize {
+        match &self.board {
+            Board::Torus(bits) => bits.population(),
+            Board::Plane(cells) => cells.iter().count(),
+        }
+    }
+
+    /// Sets plane cell (x, y), wrapping it onto the torus if the board is one.
+    fn set_cell(&mut self, x: i64, y: i64, alive: bool) {
+        match &mut self.board {
+            Board::Torus(bits) => {
+                let x = (x - self.origin.0).rem_euclid(WIDTH as i64) as usize;
+                let y = (y - self.origin.1).rem_euclid(HEIGHT as i64) as usize;
+                bits.set_alive(x, y, alive);
+            }
+            Board::Plane(cells) => {
+                if *cells.get(x, y) != alive {
+                    cells.set(x, y, alive);
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        match &mut self.board {
+            Board::Torus(bits) => bits.clear(),
+            Board::Plane(cells) => cells.clear(),
+        }
+        self.generation = 0;
+        (self.births, self.deaths) = (0, 0);
+        self.version += 1;
+    }
+
+    /// Swaps the board for the kind `params.wrap` asks for, keeping the cells in the window.
+    fn sync_board(&mut self) {
+        if matches!(self.board, Board::Torus(_)) == self.params.wrap {
+            return;
+        }
+        let mut cells = self.live_cells();
+        cells.retain(|&(x, y)| self.to_window(x, y).is_some());
+        self.board = if self.params.wrap {
+            Board::Torus(BitGrid::new(WIDTH, HEIGHT).with_wrap(true))
+        } else {
+            Board::Plane(SparseGrid::new())
+        };
+        for (x, y) in cells {
+            self.set_cell(x, y, true);
+        }
+        self.version += 1;
+    }
+
+    /// Fills the window with a random soup at `params.density`.
+    fn randomize(&mut self) {
+        self.clear();
+        for y in 0..HEIGHT as i64 {
+            for x in 0..WIDTH as i64 {
+                if self.rng.random::<f32>() < self.params.density {
+                    self.set_cell(x + self.origin.0, y + self.origin.1, true);
+                }
+            }
+        }
+    }
+
+    /// Clears the board and puts `pattern` around (0, 0), back in the middle of the window,
+    /// cropped to the window on a torus.
+    fn place(&mut self, pattern: &Grid<bool>) {
+        self.clear();
+        self.origin = ORIGIN;
+        let (left, top) = (-(pattern.width() as i64) / 2, -(pattern.height() as i64) / 2);
+        let torus = matches!(self.board, Board::Torus(_));
+        for (i, _) in pattern.cells().iter().enumerate().filter(|&(_, &alive)| alive) {
+            let (x, y) = (left + (i % pattern.width()) as i64, top + (i / pattern.width()) as i64);
+            if !torus || self.to_window(x, y).is_some() {
+                self.set_cell(x, y, true);
+            }
+        }
+    }
+
+    /// The live cells as a pattern trimmed to their bounding box.
+    fn to_pattern(&self) -> Grid<bool> {
+        let cells = self.live_cells();
+        if cells.is_empty() {
+            return Grid::new(0, 0);
+        }
+        let (xs, ys) = (cells.iter().map(|&(x, _)| x), cells.iter().map(|&(_, y)| y));
+        let (x0, x1) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0));
+        let (y0, y1) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(0));
+        let mut pattern = Grid::new((x1 - x0 + 1) as usize, (y1 - y0 + 1) as usize);
+        for (x, y) in cells {
+            pattern.set((x - x0) as usize, (y - y0) as usize, true);
+        }
+        pattern
+    }
+}
+
+/// The rule from an RLE header's `rule = ..` field, if it has one.
+fn header_rule(text: &str) -> Option<&str> {
+    let header = text.lines().map(str::trim).find(|line| !line.is_empty() && !line.starts_with('#'))?;
+    let (_, rule) = header.split_once("rule")?;
+    // Golly's bounded-grid suffix (":T100,100") isn't part of the rule itself
+    Some(rule.trim_start().strip_prefix('=')?.trim().split(':').next().unwrap_or_default())
+}
+
+/// One generation on an unbounded plane: only live cells and their neighbors can change.
+/// Returns the next generation with its births and deaths. Births on zero neighbors would
+/// fill the plane, so B0 rules only ever grow cells next to live ones.
+fn step_plane(cells: &SparseGrid<bool>, rule: LifeRule) -> (SparseGrid<bool>, usize, usize) {
+    let live: Vec<(i64, i64)> = cells.iter().map(|(pos, _)| pos).collect();
+    let mut counts: HashMap<(i64, i64), usize> = HashMap::with_capacity(live.len() * 4);
+    for &(x, y) in &live {
+        counts.entry((x, y)).or_default();
+        for &(dx, dy) in Neighborhood::Moore.offsets() {
+            *counts.entry((x + dx as i64, y + dy as i64)).or_default() += 1;
+        }
+    }
+    let mut next = SparseGrid::new();
+    let (mut born, mut alive_next) = (0, 0);
+    for (&(x, y), &neighbors) in &counts {
+        let alive = *cells.get(x, y);
+        if (alive || neighbors > 0) && rule.next(alive, neighbors) {
+            next.set(x, y, true);
+            born += !alive as usize;
+            alive_next += 1;
+        }
+    }
+    (next, born, live.len() + born - alive_next)
+}
+
+impl Simulation for LifeSim {
+    fn name(&self) -> &str {
+        "Game of Life"
+    }
+
+    fn info(&self) -> SimInfo {
+        INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        let [r, g, b] = self.params.alive_color;
+        let [dr, dg, db] = self.params.dead_color;
+        SimHelp {
+            overview: "Every generation, a dead cell with exactly three live neighbors is born \
+                       and a live one with two or three survives; every other cell dies. Other \
+                       Life-like rules can be typed in B/S notation, e.g. B36/S23 for HighLife. \
+                       The board is a torus, or with wrap off an endless plane seen through the \
+                       window, which Follow (🎯) keeps on the live cells.",
+            legend: vec![
+                (egui::Color32::from_rgb(r, g, b), "Live cell"),
+                (egui::Color32::from_rgb(dr, dg, db), "Dead cell"),
+            ],
+            params: vec![
+                ("density", "Share of cells alive in a random soup"),
+                ("wrap", "Join opposite edges into a torus instead of an endless plane"),
+                ("alive_color", "Color of live cells"),
+                ("dead_color", "Color of dead cells"),
+            ],
+            controls: vec![
+                ("Left click / drag", "Bring the cells under the brush to life"),
+                ("Right click / drag", "Kill the cells under the brush"),
+                ("Stamp", "Left clicks place the picked pattern instead"),
+                ("Drop a .rle / .cells", "Load a pattern, centered, with the RLE's rule"),
+            ],
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            max_speed: 1000.0,
+            default_speed: 30.0,
+            speed_unit: "generations/sec",
+            ..SimConfig::default()
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::INPUT | Capabilities::SNAPSHOTS | Capabilities::EXPORT
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (WIDTH, HEIGHT)
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        self.sync_board();
+        match &mut self.board {
+            Board::Torus(bits) => {
+                self.before.clear();
+                self.before.extend_from_slice(bits.words());
+                bits.step_life(self.rule);
+                let changes = self.before.iter().zip(bits.words());
+                self.births = changes.clone().map(|(old, new)| (!old & new).count_ones() as usize).sum();
+                self.deaths = changes.map(|(old, new)| (old & !new).count_ones() as usize).sum();
+            }
+            Board::Plane(cells) => {
+                let next;
+                (next, self.births, self.deaths) = step_plane(cells, self.rule);
+                *cells = next;
+                if let Some(bounds) = cells.bounding_box() {
+                    bounds.keep_in_view(&mut self.origin, (WIDTH, HEIGHT), MARGIN);
+                }
+            }
+        }
+        self.generation += 1;
+        self.version += 1;
+        Ok(())
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.version)
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        for pixel in buffer.chunks_exact_mut(3) {
+            pixel.copy_from_slice(&self.params.dead_color);
+        }
+        for (x, y) in self.live_cells() {
+            if let Some((x, y)) = self.to_window(x, y) {
+                let i = (y * WIDTH + x) * 3;
+                buffer[i..i + 3].copy_from_slice(&self.params.alive_color);
+            }
+        }
+    }
+
+    /// The live cells on the plane, so Follow keeps up with them as the window slides.
+    fn focus(&self) -> Option<Region> {
+        let Board::Plane(cells) = &self.board else { return None };
+        cells.bounding_box()?.to_region(self.origin, self.dimensions())
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.origin = ORIGIN;
+        self.sync_board();
+        match self.pattern.take() {
+            Some(pattern) => {
+                self.place(&pattern);
+                self.pattern = Some(pattern);
+            }
+            None => self.randomize(),
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        self.sync_board();
+        ui.label(format!("Generation: {}", self.generation));
+        ui.label(format!("Population: {}", self.population()));
+        ui.label(format!("Births: {}  Deaths: {}", self.births, self.deaths));
+        ui.horizontal(|ui| {
+            ui.label("Rule");
+            if ui.text_edit_singleline(&mut self.rule_text).changed()
+                && let Ok(rule) = LifeRule::parse(&self.rule_text)
+            {
+                self.rule = rule;
+            }
+        });
+        if let Err(error) = LifeRule::parse(&self.rule_text) {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        ui.horizontal(|ui| {
+            let randomize = ui.button("🎲 Randomize");
+            if randomize.on_hover_text("Fill the window at the density parameter").clicked() {
+                self.pattern = None;
+                self.randomize();
+            }
+            if ui.button("Clear").clicked() {
+                self.pattern = None;
+                self.clear();
+            }
+        });
+        ui.separator();
+        self.brush.ui(ui);
+        // The brush's own ✖ can drop the stamp too
+        let mut stamp = self.stamp.filter(|_| self.brush.stamp.is_some());
+        egui::ComboBox::from_label("Stamp")
+            .selected_text(stamp.map_or("None", |i| PATTERNS[i].0))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut stamp, None, "None");
+                for (i, (name, _)) in PATTERNS.iter().enumerate() {
+                    ui.selectable_value(&mut stamp, Some(i), *name);
+                }
+            });
+        if stamp != self.stamp.filter(|_| self.brush.stamp.is_some()) {
+            self.brush.stamp = stamp.and_then(|i| Grid::from_rle(PATTERNS[i].1).ok());
+        }
+        self.stamp = stamp;
+        let copy = ui.button("📋 Copy as RLE");
+        if copy.on_hover_text("Copy the live cells as a Golly .rle pattern").clicked() {
+            ui.ctx().copy_text(self.to_pattern().to_rle(&self.rule.to_string()));
+        }
+    }
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+
+    fn record_metrics(&self, metrics: &mut Metrics) {
+        metrics.record("population", self.population() as f64);
+        metrics.record("births", self.births as f64);
+        metrics.record("deaths", self.deaths as f64);
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        let (alive, neighbors) = match &self.board {
+            Board::Torus(bits) => (bits.alive(x, y), bits.live_neighbors(x, y)),
+            Board::Plane(cells) => {
+                let (x, y) = (x as i64 + self.origin.0, y as i64 + self.origin.1);
+                let neighbors = cells.count_neighbors_where(x, y, Neighborhood::Moore, |&alive| alive);
+                (*cells.get(x, y), neighbors)
+            }
+        };
+        Some(format!("{}, {neighbors} live neighbors", if alive { "alive" } else { "dead" }))
+    }
+
+    fn file_formats(&self) -> &'static [&'static str] {
+        &["rle", "cells"]
+    }
+
+    /// Replaces the board with the pattern, centered, and switches to the RLE's rule. Nothing
+    /// changes if either fails to parse.
+    fn load_file(&mut self, name: &str, data: &[u8]) -> Result<(), SimError> {
+        let text = String::from_utf8_lossy(data);
+        let (pattern, rule) = if name.to_lowercase().ends_with(".cells") {
+            (Grid::from_cells(&text), Ok(None))
+        } else {
+            (Grid::from_rle(&text), header_rule(&text).map(LifeRule::parse).transpose())
+        };
+        let (pattern, rule) = (pattern.map_err(SimError::State)?, rule.map_err(SimError::State)?);
+        if let Some(rule) = rule {
+            self.rule = rule;
+            self.rule_text = rule.to_string();
+        }
+        self.sync_board();
+        self.place(&pattern);
+        self.pattern = Some(pattern);
+        Ok(())
+    }
+
+    fn handle_input(&mut self, event: SimInput) {
+        self.sync_board();
+        let mut painted = Vec::new();
+        self.brush.apply(event, |x, y, &alive| {
+            painted.push((x as i64 + self.origin.0, y as i64 + self.origin.1, alive));
+        });
+        for &(x, y, alive) in &painted {
+            self.set_cell(x, y, alive);
+        }
+        if !painted.is_empty() {
+            self.version += 1;
+        }
+    }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        let snapshot = Snapshot {
+            params: self.params.clone(),
+            rule: self.rule.to_string(),
+            generation: self.generation,
+            origin: self.origin,
+            cells: self.live_cells(),
+        };
+        serde_json::to_vec(&snapshot).ok()
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), SimError> {
+        let snapshot: Snapshot =
+            serde_json::from_slice(data).map_err(|e| SimError::State(e.to_string()))?;
+        self.rule = LifeRule::parse(&snapshot.rule).map_err(SimError::State)?;
+        self.rule_text = snapshot.rule;
+        self.params = snapshot.params;
+        self.sync_board();
+        self.clear();
+        self.origin = snapshot.origin;
+        for (x, y) in snapshot.cells {
+            self.set_cell(x, y, true);
+        }
+        self.generation = snapshot.generation;
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt::Write;
+use crate::Grid;
+
+/// A cell type that Life pattern files can hold: `bool` for two-state rules, `u8` for
+/// Golly's multi-state RLE, where 0 is dead and 1-255 are live states.
+pub trait PatternCell: Clone + Default {
+    fn from_state(state: u8) -> Self;
+    fn state(&self) -> u8;
+}
+
+impl PatternCell for bool {
+    fn from_state(state: u8) -> Self {
+        state > 0
+    }
+
+    fn state(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl PatternCell for u8 {
+    fn from_state(state: u8) -> Self {
+        state
+    }
+
+    fn state(&self) -> u8 {
+        *self
+    }
+}
+
+/// RLE lines are kept to this many characters, as Golly writes them.
+const RLE_LINE: usize = 70;
+
+/// The most cells a pattern file may ask for, so a bad or hostile header can't exhaust memory.
+const MAX_PATTERN_CELLS: usize = 1 << 26;
+
+/// Checks a pattern's size against [`MAX_PATTERN_CELLS`].
+fn check_size(width: usize, height: usize) -> Result<(), String> {
+    match width.checked_mul(height) {
+        Some(cells) if cells <= MAX_PATTERN_CELLS => Ok(()),
+        _ => Err(format!("a {width} × {height} pattern is too large to load")),
+    }
+}
+
+impl<T: PatternCell> Grid<T> {
+    /// Reads a pattern in Golly's run-length encoded `.rle` format, sized by its
+    /// `x = .., y = ..` header. `#` comment lines and the header's rule are skipped.
+    ///
+    /// ```
+    /// use simulation_station::Grid;
+    ///
+    /// let rle = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!";
+    /// let glider: Grid<bool> = Grid::from_rle(rle).unwrap();
+    /// assert_eq!((glider.width(), glider.height()), (3, 3));
+    /// assert!(*glider.get(1, 0) && !glider.get(0, 1) && *glider.get(2, 2));
+    /// assert_eq!(glider.to_rle("B3/S23"), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    /// ```
+    pub fn from_rle(text: &str) -> Result<Self, String> {
+        let mut lines =
+            text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let header = lines.next().ok_or("the RLE has no header line")?;
+        let mut size = (None, None);
+        // A bounded grid's rule ("B3/S23:T100,100") has commas of its own; skip its pieces
+        for (key, value) in header.split(',').filter_map(|field| field.split_once('=')) {
+            let value =
+                || value.trim().parse::<usize>().map_err(|e| format!("bad RLE size {value:?}: {e}"));
+            match key.trim() {
+                "x" => size.0 = Some(value()?),
+                "y" => size.1 = Some(value()?),
+                _ => {}
+            }
+        }
+        let (Some(width), Some(height)) = size else {
+            return Err("the RLE header has no x = .., y = .. size".to_owned());
+        };
+        check_size(width, height)?;
+
+        let mut grid = Grid::new(width, height);
+        let (mut x, mut y, mut run, mut prefix) = (0, 0usize, 0usize, None);
+        'body: for c in lines.flat_map(str::chars) {
+            let state = match c {
+                '0'..='9' => {
+                    run = run
+                        .checked_mul(10)
+                        .and_then(|run| run.checked_add(c.to_digit(10).unwrap_or(0) as usize))
+                        .ok_or("an RLE run count is too large")?;
+                    continue;
+                }
+                'p'..='y' => {
+                    prefix = Some(c as u8 - b'p' + 1);
+                    continue;
+                }
+                '!' => break 'body,
+                '$' => {
+                    let next = y.checked_add(run.max(1)).ok_or("an RLE run count is too large")?;
+                    (x, y, run) = (0, next, 0);
+                    continue;
+                }
+                'b' | '.' => 0,
+                'o' => 1,
+                'A'..='X' => {
+                    let state = prefix.take().unwrap_or(0) as usize * 24 + (c as u8 - b'A' + 1) as usize;
+                    u8::try_from(state).map_err(|_| format!("RLE state {state} is past 255"))?
+                }
+                c if c.is_whitespace() => continue,
+                c => return Err(format!("unexpected {c:?} in the RLE")),
+            };
+            for _ in 0..run.max(1) {
+                if x >= width || y >= height {
+                    return Err(format!("the RLE pattern doesn't fit its {width} × {height} header"));
+                }
+                if state > 0 {
+                    grid.set(x, y, T::from_state(state));
+                }
+                x += 1;
+            }
+            run = 0;
+        }
+        Ok(grid)
+    }
+
+    /// Writes the grid as Golly RLE with `rule` in the header, e.g. "B3/S23".
+    pub fn to_rle(&self, rule: &str) -> String {
+        let multistate = self.cells().iter().any(|cell| cell.state() > 1);
+        let mut body = String::new();
+        // Blank rows since the last row written, or since the top before any has been
+        let (mut pending_rows, mut written) = (0, false);
+        for y in 0..self.height() {
+            let row = &self.cells()[y * self.width()..][..self.width()];
+            let end = row.iter().rposition(|cell| cell.state() > 0).map_or(0, |last| last + 1);
+            if end == 0 {
+                pending_rows += 1;
+                continue;
+            }
+            match written {
+                true => push_run(&mut body, pending_rows + 1, "$"),
+                false if pending_rows > 0 => push_run(&mut body, pending_rows, "$"),
+                false => {}
+            }
+            (pending_rows, written) = (0, true);
+            let mut x = 0;
+            while x < end {
+                let state = row[x].state();
+                let run = row[x..end].iter().take_while(|cell| cell.state() == state).count();
+                push_run(&mut body, run, &rle_symbol(state, multistate));
+                x += run;
+            }
+        }
+        body.push('!');
+
+        let mut text = format!("x = {}, y = {}, rule = {rule}\n", self.width(), self.height());
+        let mut line = 0;
+        // Break lines between runs, never inside one
+        for token in body.split_inclusive(|c: char| !c.is_ascii_digit() && !('p'..='y').contains(&c)) {
+            if line + token.len() > RLE_LINE {
+                text.push('\n');
+                line = 0;
+            }
+            text.push_str(token);
+            line += token.len();
+        }
+        text.push('\n');
+        text
+    }
+
+    /// Reads a plaintext `.cells` pattern: `!` comment lines, then one line per row with
+    /// `.` for dead cells and `O` (or `*`) for live ones. Short rows are padded dead.
+    ///
+    /// ```
+    /// use simulation_station::Grid;
+    ///
+    /// let blinker: Grid<bool> = Grid::from_cells("!Name: Blinker\nOOO\n").unwrap();
+    /// assert_eq!((blinker.width(), blinker.height()), (3, 1));
+    /// assert_eq!(blinker.to_cells("Blinker"), "!Name: Blinker\nOOO\n");
+    /// ```
+    pub fn from_cells(text: &str) -> Result<Self, String> {
+        let rows: Vec<&str> =
+            text.lines().map(str::trim_end).filter(|line| !line.starts_with('!')).collect();
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        check_size(width, rows.len())?;
+        let mut grid = Grid::new(width, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    'O' | 'o' | '*' => grid.set(x, y, T::from_state(1)),
+                    '.' | ' ' => {}
+                    c => return Err(format!("unexpected {c:?} on row {} of the .cells", y + 1)),
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Writes the grid as a plaintext `.cells` pattern headed `!Name: {name}`. Every live
+    /// state comes out as `O`.
+    pub fn to_cells(&self, name: &str) -> String {
+        let mut text = format!("!Name: {name}\n");
+        for row in self.cells().chunks(self.width().max(1)) {
+            let end = row.iter().rposition(|cell| cell.state() > 0).map_or(0, |last| last + 1);
+            text.extend(row[..end].iter().map(|cell| if cell.state() > 0 { 'O' } else { '.' }));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// The RLE letters for `state`: `b` and `o` in two-state patterns, and in multi-state
+/// ones `.` and Golly's `A`-`X`, with a `p`-`y` prefix for each 24 states beyond.
+fn rle_symbol(state: u8, multistate: bool) -> String {
+    match state {
+        0 if !multistate => "b".to_owned(),
+        1 if !multistate => "o".to_owned(),
+        0 => ".".to_owned(),
+        state => {
+            let (prefix, letter) = ((state - 1) / 24, (state - 1) % 24);
+            let mut symbol = String::new();
+            if prefix > 0 {
+                symbol.push((b'p' + prefix - 1) as char);
+            }
+            symbol.push((b'A' + letter) as char);
+            symbol
+        }
+    }
+}
+
+fn push_run(body: &mut String, run: usize, symbol: &str) {
+    if run > 1 {
+        let _ = write!(body, "{run}");
+    }
+    body.push_str(symbol);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Grid;
+
+    fn pattern(width: usize, height: usize, live: &[(usize, usize)]) -> Grid<bool> {
+        let mut grid = Grid::new(width, height);
+        for &(x, y) in live {
+            grid.set(x, y, true);
+        }
+        grid
+    }
+
+    #[test]
+    fn rle_round_trips() {
+        let glider = pattern(3, 3, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let rle = glider.to_rle("B3/S23");
+        assert_eq!(rle, "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+        assert_eq!(Grid::<bool>::from_rle(&rle).unwrap().cells(), glider.cells());
+    }
+
+    #[test]
+    fn rle_leading_empty_rows() {
+        let corner = pattern(3, 3, &[(0, 2)]);
+        let rle = corner.to_rle("B3/S23");
+        assert!(rle.ends_with("\n2$o!\n"), "{rle}");
+        assert_eq!(Grid::<bool>::from_rle(&rle).unwrap().cells(), corner.cells());
+        let gaps = pattern(4, 6, &[(3, 1), (0, 4)]);
+        let back: Grid<bool> = Grid::from_rle(&gaps.to_rle("B3/S23")).unwrap();
+        assert_eq!(back.cells(), gaps.cells());
+    }
+
+    #[test]
+    fn rle_empty_pattern() {
+        let empty = pattern(5, 4, &[]);
+        let back: Grid<bool> = Grid::from_rle(&empty.to_rle("B3/S23")).unwrap();
+        assert_eq!((back.width(), back.height()), (5, 4));
+        assert!(back.cells().iter().all(|&alive| !alive));
+    }
+
+    #[test]
+    fn rle_multistate_round_trips() {
+        let mut grid: Grid<u8> = Grid::new(4, 3);
+        grid.set(0, 1, 1);
+        grid.set(1, 1, 30);
+        grid.set(3, 2, 255);
+        let back: Grid<u8> = Grid::from_rle(&grid.to_rle("Generations")).unwrap();
+        assert_eq!(back.cells(), grid.cells());
+    }
+
+    #[test]
+    fn rle_rejects_oversized_input() {
+        assert!(Grid::<bool>::from_rle("x = 100000000, y = 100000000\no!").is_err());
+        assert!(Grid::<bool>::from_rle("x = 3, y = 3\n99999999999999999999999o!").is_err());
+        assert!(Grid::<bool>::from_rle("x = 3, y = 3\n4o!").is_err());
+    }
+
+    #[test]
+    fn cells_round_trips() {
+        let blinker = pattern(3, 3, &[(0, 1), (1, 1), (2, 1)]);
+        let text = blinker.to_cells("Blinker");
+        assert_eq!(text, "!Name: Blinker\n\nOOO\n\n");
+        let back: Grid<bool> = Grid::from_cells(&text).unwrap();
+        assert_eq!((back.width(), back.height()), (3, 3));
+        assert_eq!(back.cells(), blinker.cells());
+    }
+}
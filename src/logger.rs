@@ -0,0 +1,164 @@
+use eframe::egui;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use web_time::Instant;
+
+/// Messages from every sim and worker, oldest first, shown in the App's console.
+static LOG: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+static START: OnceLock<Instant> = OnceLock::new();
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 4] = [LogLevel::Debug, LogLevel::Info, LogLevel::Warn, LogLevel::Error];
+
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            LogLevel::Debug => egui::Color32::GRAY,
+            LogLevel::Info => egui::Color32::from_rgb(100, 180, 255),
+            LogLevel::Warn => egui::Color32::YELLOW,
+            LogLevel::Error => egui::Color32::from_rgb(255, 90, 90),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    /// Seconds since the App started.
+    pub time: f64,
+    pub level: LogLevel,
+    /// Who wrote it, usually a sim name.
+    pub source: Arc<str>,
+    pub message: String,
+}
+
+/// Writes to the App's console. Cheap to clone and `Send`, so a sim can hand a copy to
+/// its worker threads; `AsyncSim` workers get one in their `WorkerContext`. Messages
+/// also go to the `log` crate.
+#[derive(Clone, Debug)]
+pub struct SimLogger {
+    source: Arc<str>,
+}
+
+impl SimLogger {
+    /// Entries kept before the oldest are dropped.
+    const CAPACITY: usize = 5000;
+
+    /// A logger whose entries are tagged with `source`.
+    pub fn new(source: &str) -> Self {
+        START.get_or_init(Instant::now);
+        Self { source: source.into() }
+    }
+
+    pub fn log(&self, level: LogLevel, message: impl Into<String>) {
+        let message = message.into();
+        let log_level = match level {
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Error => log::Level::Error,
+        };
+        log::log!(log_level, "[{}] {message}", self.source);
+
+        let time = START.get_or_init(Instant::now).elapsed().as_secs_f64();
+        let mut entries = LOG.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() == Self::CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(LogEntry { time, level, source: self.source.clone(), message });
+    }
+
+    pub fn debug(&self, message: impl Into<String>) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.log(LogLevel::Error, message);
+    }
+}
+
+/// The console panel's filter settings.
+pub(crate) struct LogConsole {
+    query: String,
+    min_level: LogLevel,
+}
+
+impl Default for LogConsole {
+    fn default() -> Self {
+        Self { query: String::new(), min_level: LogLevel::Info }
+    }
+}
+
+impl LogConsole {
+    /// Filter bar above a scrolling list of matching entries that follows new ones.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.query)
+                    .hint_text("Filter")
+                    .desired_width(160.0),
+            );
+            for level in LogLevel::ALL {
+                ui.selectable_value(&mut self.min_level, level, level.label())
+                    .on_hover_text(format!("Show {} and above", level.label()));
+            }
+            if ui.button("Clear").clicked() {
+                LOG.lock().unwrap_or_else(|e| e.into_inner()).clear();
+            }
+        });
+        ui.separator();
+
+        let query = self.query.to_lowercase();
+        let entries: Vec<LogEntry> = LOG
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|entry| entry.level >= self.min_level)
+            .filter(|entry| {
+                query.is_empty()
+                    || entry.message.to_lowercase().contains(&query)
+                    || entry.source.to_lowercase().contains(&query)
+            })
+            .cloned()
+            .collect();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, entries.len(), |ui, rows| {
+                for entry in &entries[rows] {
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("{:>9.3}", entry.time));
+                        ui.colored_label(
+                            entry.level.color(),
+                            egui::RichText::new(format!("{:<5}", entry.level.label())).monospace(),
+                        );
+                        ui.monospace(format!("[{}] {}", entry.source, entry.message));
+                    });
+                }
+            });
+    }
+}
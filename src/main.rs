@@ -1,10 +1,13 @@
 use simulation_station::App;
+#[cfg(not(target_arch = "wasm32"))]
 use eframe::egui;
 
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result<()> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1000.0, 700.0]),
+        #[cfg(feature = "gpu")]
+        renderer: eframe::Renderer::Wgpu,
         ..Default::default()
     };
     eframe::run_native(
@@ -0,0 +1,98 @@
+use eframe::egui;
+use egui_plot::{Legend, Line, Plot};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Values a sim reports for the Metrics panel, one series per name, plotted against the
+/// step count. The App hands it to `Simulation::record_metrics` whenever the sim advances.
+#[derive(Default)]
+pub struct Metrics {
+    step: u64,
+    series: BTreeMap<String, Vec<[f64; 2]>>,
+}
+
+impl Metrics {
+    /// Points kept per series; past this every other point is dropped, so long runs
+    /// keep their whole history at a coarser resolution.
+    const MAX_POINTS: usize = 10_000;
+
+    /// Adds `value` to series `name` at the current step, replacing a value already
+    /// recorded for this step.
+    pub fn record(&mut self, name: &str, value: f64) {
+        let step = self.step as f64;
+        let points = self.series.entry(name.to_owned()).or_default();
+        match points.last_mut() {
+            Some(last) if last[0] == step => last[1] = value,
+            _ => points.push([step, value]),
+        }
+        if points.len() > Self::MAX_POINTS {
+            let mut index = 0;
+            points.retain(|_| {
+                index += 1;
+                index % 2 == 1
+            });
+        }
+    }
+
+    /// The step count values are being recorded at.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    /// Every series as (name, [step, value] points), ordered by name.
+    pub fn series(&self) -> impl Iterator<Item = (&str, &[[f64; 2]])> {
+        self.series.iter().map(|(name, points)| (name.as_str(), points.as_slice()))
+    }
+
+    pub(crate) fn set_step(&mut self, step: u64) {
+        self.step = step;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// One row per recorded step and one column per series, blank where a series has
+    /// no value for that step.
+    pub fn to_csv(&self) -> String {
+        let mut rows: BTreeMap<u64, Vec<Option<f64>>> = BTreeMap::new();
+        for (column, points) in self.series.values().enumerate() {
+            for &[step, value] in points {
+                rows.entry(step as u64).or_insert_with(|| vec![None; self.series.len()])[column] =
+                    Some(value);
+            }
+        }
+        let mut csv = String::from("step");
+        for name in self.series.keys() {
+            let _ = write!(csv, ",{}", name.replace(',', ";"));
+        }
+        csv.push('\n');
+        for (step, values) in rows {
+            let _ = write!(csv, "{step}");
+            for value in values {
+                csv.push(',');
+                if let Some(value) = value {
+                    let _ = write!(csv, "{value}");
+                }
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// A zoomable line plot of every series with a legend that toggles them.
+    pub(crate) fn plot_ui(&self, ui: &mut egui::Ui, id: impl std::hash::Hash) {
+        Plot::new(id)
+            .legend(Legend::default())
+            .x_axis_label("step")
+            .show(ui, |plot| {
+                for (name, points) in self.series() {
+                    plot.line(Line::new(name, points.to_vec()));
+                }
+            });
+    }
+}
@@ -1,56 +1,153 @@
 use eframe::egui;
-use std::sync::mpsc::SyncSender;
+use serde::{Deserialize, Serialize};
+use crate::{
+    Annotations, AsyncSim, Category, Colormap, PixelFormat, RestartPolicy, SimConfig, SimHelp, SimInfo,
+    StateDelta, Theme, WorkerContext,
+};
 
-#[derive(Clone, Default)]
+pub const INFO: SimInfo = SimInfo {
+    description: "Searches for the starting number under one million with the longest Collatz chain. \
+                  Bars show the chain length of the most recently checked numbers.",
+    category: Category::ProjectEuler,
+    tags: &["number theory", "brute force", "async"],
+};
+
+/// The search covers 1 up to here.
+const LIMIT: u64 = 1_000_000;
+
+pub fn sim() -> AsyncSim<CollatzState, CollatzCommand> {
+    let builder = AsyncSim::builder("Problem 14: Collatz").config(SimConfig {
+        min_speed: 1.0,
+        max_speed: 50_000.0,
+        default_speed: 10_000.0,
+        speed_unit: "numbers/sec",
+        pixel_format: PixelFormat::Rgba,
+        ..SimConfig::default()
+    });
+    builder
+        .stepper(check_next)
+        .renderer(render)
+        .ui(ui)
+        .commands(jump_ui)
+        .checkpoints(10_000)
+        .build()
+        .with_vector_view("Record progression", render_records)
+        .with_vector_view("Stopping times", render_scatter)
+        .with_info(INFO)
+        .with_help(help)
+        .with_probe(probe)
+        .with_annotations(annotate)
+        .with_metrics(|state, metrics| {
+            metrics.record("best length", state.best_len as f64);
+            metrics.record("current length", state.current_len as f64);
+        })
+        .with_snapshots()
+        .with_restart_policy(RestartPolicy::retry(5))
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub struct CollatzState {
     pub current_num: u64,
     pub current_len: u64,
     pub best_num: u64,
     pub best_len: u64,
     pub history: Vec<u64>, 
+    /// Every (number, length) pair that set a new record, in order.
+    #[serde(default)]
+    pub records: Vec<(u64, u64)>,
 }
 
-pub fn solve(tx: SyncSender<CollatzState>) {
-    let mut state = CollatzState::default();
-    
-    // Iterate 1 to 1,000,000
-    for i in 1..1_000_000 {
-        let mut n = i;
-        let mut len = 1;
-        
-        // Calculate Collatz Length
-        while n > 1 {
-            if n % 2 == 0 { 
-                n /= 2; 
-            } else { 
-                n = 3 * n + 1; 
-            }
-            len += 1;
+/// One number checked, as a change to the state.
+#[derive(Clone, Copy)]
+pub struct CollatzStep {
+    pub num: u64,
+    pub len: u64,
+}
+
+impl StateDelta<CollatzState> for CollatzStep {
+    fn apply(self, state: &mut CollatzState) {
+        // The bars only make sense for consecutive numbers, so a jump starts them afresh
+        if self.num != state.current_num + 1 {
+            state.history.clear();
         }
+        state.current_num = self.num;
+        state.current_len = self.len;
 
-        // Update State
-        state.current_num = i;
-        state.current_len = len;
-        
         // Add to graph history (keep only last 400 points)
-        state.history.push(len);
-        if state.history.len() > 400 { 
-            state.history.remove(0); 
+        state.history.push(self.len);
+        if state.history.len() > 400 {
+            state.history.remove(0);
         }
 
         // Check for new record
-        if len > state.best_len {
-            state.best_len = len;
-            state.best_num = i;
+        if self.len > state.best_len {
+            state.best_len = self.len;
+            state.best_num = self.num;
+            state.records.push((self.num, self.len));
         }
+    }
+}
+
+/// Sent from the panel to the running search.
+pub enum CollatzCommand {
+    /// Carry on from this number, skipping or rechecking everything in between.
+    JumpTo(u64),
+}
+
+type Context = WorkerContext<CollatzState, CollatzCommand>;
+
+/// Checks the number after the last one checked, or returns false once every number
+/// below `LIMIT` has been.
+fn check_next(state: &mut CollatzState, ctx: &Context) -> bool {
+    if let Some(CollatzCommand::JumpTo(to)) = ctx.commands().last() {
+        // Records found so far stand
+        let next = to.clamp(1, LIMIT - 1);
+        state.current_num = next - 1;
+        state.history.clear();
+        ctx.log.info(format!("Jumped to {next}"));
+    }
+    let num = state.current_num + 1;
+    if num >= LIMIT {
+        return false;
+    }
+    record(state, ctx, CollatzStep { num, len: chain_length(num) });
+    true
+}
+
+/// Applies `step` to the worker's state, with the logging, checkpoints and progress
+/// reports that go with it.
+fn record(state: &mut CollatzState, ctx: &Context, step: CollatzStep) {
+    let CollatzStep { num, len } = step;
+    if len > state.best_len {
+        ctx.log.info(format!("New record: {num} takes {len} steps"));
+    }
+    step.apply(state);
+    ctx.checkpoint(state);
+    if num % 1000 == 0 || num == LIMIT - 1 {
+        ctx.progress(num as f32 / (LIMIT - 1) as f32, format!("Checked {num} of 999,999"));
+    }
+}
 
-        if tx.send(state.clone()).is_err() { break; }
+/// Number of terms in the Collatz chain starting at `start`, including both ends.
+fn chain_length(start: u64) -> u64 {
+    let mut n = start;
+    let mut len = 1;
+    while n > 1 {
+        if n.is_multiple_of(2) { 
+            n /= 2; 
+        } else { 
+            n = 3 * n + 1; 
+        }
+        len += 1;
     }
+    len
 }
 
-pub fn render(state: &CollatzState, buffer: &mut Vec<u8>) {
-    // Clear to black
+pub fn render(state: &CollatzState, buffer: &mut [u8]) {
+    // Clear to transparent; the App composites over its background
     buffer.fill(0);
+    let theme = Theme::current();
+    let colormap = Colormap::Custom(vec![Theme::rgb(theme.muted), Theme::rgb(theme.accent)]);
 
     // Draw the "History Graph"
     // Each pixel column represents one number checked
@@ -68,19 +165,105 @@ pub fn render(state: &CollatzState, buffer: &mut Vec<u8>) {
         for y in 0..bar_height.min(h) {
             // Flip Y so 0 is at bottom
             let pixel_y = h - 1 - y;
-            let idx = (pixel_y * w + x) * 3;
+            let idx = (pixel_y * w + x) * 4;
             
-            if idx + 2 < buffer.len() {
-                // Color gradient based on height (Blue -> Cyan -> White)
-                let intensity = (y as u8).saturating_mul(2);
-                buffer[idx] = 0;                        // R
-                buffer[idx+1] = intensity;              // G
-                buffer[idx+2] = 255 - (intensity / 2);  // B
+            if idx + 3 < buffer.len() {
+                // Color gradient based on height (muted -> accent)
+                buffer[idx..idx + 3].copy_from_slice(&colormap.map(y as f32 / h as f32));
+                buffer[idx+3] = 255;                    // A
             }
         }
     }
 }
 
+fn help() -> SimHelp {
+    let theme = Theme::current();
+    SimHelp {
+        overview: "Each step checks the next starting number and counts how many steps its Collatz \
+                   chain takes to reach 1: halve even numbers, triple odd ones and add one. The \
+                   default view draws the chain lengths of the last 400 numbers checked as bars, \
+                   newest on the right. \"Record progression\" plots every number that set a new \
+                   record, and \"Stopping times\" scatters chain length against starting number.",
+        legend: vec![
+            (theme.muted, "Short chain"),
+            (theme.accent, "Long chain, or a record holder"),
+            (theme.foreground, "Record points in \"Record progression\""),
+        ],
+        params: Vec::new(),
+        controls: vec![("Hover a bar", "Show its number and chain length")],
+    }
+}
+
+/// The number and chain length behind the bar in column `x`.
+fn probe(state: &CollatzState, x: usize, _y: usize) -> Option<String> {
+    let len = *state.history.get(x)?;
+    let num = state.current_num - (state.history.len() - 1 - x) as u64;
+    Some(format!("{num} → {len} steps"))
+}
+
+/// Labels the longest chain among the bars, calling it out if it's the record.
+fn annotate(state: &CollatzState, annotations: &mut Annotations) {
+    let Some((x, &len)) = state.history.iter().enumerate().max_by_key(|&(x, &len)| (len, x)) else {
+        return;
+    };
+    let num = state.current_num - (state.history.len() - 1 - x) as u64;
+    let top = egui::pos2(x as f32 + 0.5, 300.0 * (1.0 - len as f32 / 525.0));
+    let theme = Theme::current();
+    let label = if num == state.best_num { "Record" } else { "Longest here" };
+    let text = format!("{label}: {num} ({len} steps)");
+    annotations.text(top - egui::vec2(0.0, 14.0), text, theme.foreground);
+    annotations.arrow(top - egui::vec2(0.0, 12.0), top, (1.5, theme.foreground));
+}
+
+/// Maps (number, length) into `rect`, with the full search range across and length 525 up.
+fn plot_pos(rect: egui::Rect, num: u64, len: u64) -> egui::Pos2 {
+    egui::pos2(
+        rect.left() + rect.width() * (num as f32 / 1_000_000.0),
+        rect.bottom() - rect.height() * (len as f32 / 525.0),
+    )
+}
+
+pub fn render_records(state: &CollatzState, painter: &egui::Painter, rect: egui::Rect) {
+    // Staircase: each record holds until the next one is found
+    let mut points = Vec::with_capacity(state.records.len() * 2 + 1);
+    for window in state.records.windows(2) {
+        let (num, len) = window[0];
+        points.push(plot_pos(rect, num, len));
+        points.push(plot_pos(rect, window[1].0, len));
+    }
+    if let Some(&(num, len)) = state.records.last() {
+        points.push(plot_pos(rect, num, len));
+        points.push(plot_pos(rect, state.current_num, len));
+    }
+    let theme = Theme::current();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(2.0, theme.accent)));
+
+    for &(num, len) in &state.records {
+        painter.circle_filled(plot_pos(rect, num, len), 3.0, theme.foreground);
+    }
+}
+
+pub fn render_scatter(state: &CollatzState, painter: &egui::Painter, rect: egui::Rect) {
+    // History holds the lengths of the numbers just before (and including) current_num
+    let first = (state.current_num + 1).saturating_sub(state.history.len() as u64);
+    let x_range = state.history.len().max(1) as f32;
+    let theme = Theme::current();
+    for (i, &len) in state.history.iter().enumerate() {
+        let pos = egui::pos2(
+            rect.left() + rect.width() * (i as f32 / x_range),
+            rect.bottom() - rect.height() * (len as f32 / 525.0),
+        );
+        painter.circle_filled(pos, 2.0, theme.accent);
+    }
+    painter.text(
+        rect.left_top() + egui::vec2(8.0, 8.0),
+        egui::Align2::LEFT_TOP,
+        format!("n = {first}..={}", state.current_num),
+        egui::FontId::monospace(12.0),
+        theme.muted,
+    );
+}
+
 pub fn ui(state: &CollatzState, ui: &mut egui::Ui) {
     ui.heading("Problem 14: Collatz");
     
@@ -91,5 +274,19 @@ pub fn ui(state: &CollatzState, ui: &mut egui::Ui) {
     
     ui.heading("Current Record");
     ui.label(format!("Number: {}", state.best_num));
-    ui.colored_label(egui::Color32::GREEN, format!("Length: {}", state.best_len));
+    ui.colored_label(Theme::current().accent, format!("Length: {}", state.best_len));
+}
+
+/// A number box and button that send the search elsewhere without a reset.
+fn jump_ui(_state: &CollatzState, ui: &mut egui::Ui, commands: &mut Vec<CollatzCommand>) {
+    ui.separator();
+    ui.horizontal(|ui| {
+        let id = ui.id().with("collatz_jump");
+        let mut target = ui.data_mut(|data| *data.get_temp_mut_or(id, 500_000u64));
+        ui.add(egui::DragValue::new(&mut target).range(1..=999_999));
+        if ui.button("Jump").on_hover_text("Carry on checking from this number").clicked() {
+            commands.push(CollatzCommand::JumpTo(target));
+        }
+        ui.data_mut(|data| data.insert_temp(id, target));
+    });
 }
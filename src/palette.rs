@@ -0,0 +1,161 @@
+use eframe::egui;
+
+/// Something the command palette can do. The App decides which ones are on offer and
+/// carries them out.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Command {
+    LoadSim(String),
+    TogglePause,
+    Reset,
+    Step,
+    SetSpeed(f32),
+    ToggleTurbo,
+    Compare,
+    NewTab,
+    CloseTab,
+    TogglePresentation,
+    ToggleHud,
+    ResetLayout,
+    SaveSnapshot,
+    LoadSnapshot,
+    SaveFrame,
+    ShowHelp,
+}
+
+/// Ctrl+P popup listing commands, filtered by fuzzy search as you type. Arrow keys move
+/// the selection, Enter runs it and Escape closes the palette.
+#[derive(Default)]
+pub(crate) struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    const MAX_SHOWN: usize = 12;
+
+    /// Handles the shortcut and, while open, draws the palette over `commands`. Returns
+    /// the command the user ran this frame.
+    pub(crate) fn ui(
+        &mut self,
+        ctx: &egui::Context,
+        commands: Vec<(String, Command)>,
+    ) -> Option<Command> {
+        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::P)) {
+            self.open = !self.open;
+            self.query.clear();
+            self.selected = 0;
+        }
+        if !self.open {
+            return None;
+        }
+
+        let mut matches: Vec<(i32, String, Command)> = commands
+            .into_iter()
+            .filter_map(|(label, command)| {
+                fuzzy_score(&self.query, &label).map(|score| (score, label, command))
+            })
+            .collect();
+        matches.sort_by_key(|(score, ..)| -score);
+        if let Some(speed) = parse_speed(&self.query) {
+            matches.insert(
+                0,
+                (0, format!("Set speed to {speed}"), Command::SetSpeed(speed)),
+            );
+        }
+        matches.truncate(Self::MAX_SHOWN);
+
+        let (up, down, enter) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            )
+        });
+        if down {
+            self.selected += 1;
+        }
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let mut chosen = enter
+            .then(|| {
+                matches
+                    .get(self.selected)
+                    .map(|(_, _, command)| command.clone())
+            })
+            .flatten();
+        let modal = egui::Modal::new(egui::Id::new("command_palette"))
+            .area(
+                egui::Modal::default_area(egui::Id::new("command_palette"))
+                    .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0]),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(420.0);
+                let edit = ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command or simulation name…")
+                        .desired_width(f32::INFINITY),
+                );
+                edit.request_focus();
+                if edit.changed() {
+                    self.selected = 0;
+                }
+                ui.separator();
+                if matches.is_empty() {
+                    ui.weak("No matching commands");
+                }
+                for (index, (_, label, command)) in matches.iter().enumerate() {
+                    let row = ui.add(
+                        egui::Button::selectable(index == self.selected, label)
+                            .min_size(egui::vec2(ui.available_width(), 0.0)),
+                    );
+                    if row.clicked() {
+                        chosen = Some(command.clone());
+                    }
+                    if row.hovered() {
+                        self.selected = index;
+                    }
+                }
+            });
+
+        if chosen.is_some() || modal.should_close() {
+            self.open = false;
+        }
+        chosen
+    }
+}
+
+/// Scores `text` against `query` as a case-insensitive subsequence match, favoring
+/// consecutive runs and matches at word starts. `None` if some query character is missing.
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut from = 0;
+    let mut previous: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = from + text[from..].iter().position(|&c| c == q)?;
+        score += 1;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        previous = Some(found);
+        from = found + 1;
+    }
+    // Among equal matches, shorter labels are more likely what was meant
+    Some(score * 100 - text.len() as i32)
+}
+
+/// Reads a speed out of queries like "500" or "speed 2.5".
+fn parse_speed(query: &str) -> Option<f32> {
+    let number = query.trim().trim_start_matches("speed").trim();
+    number
+        .parse()
+        .ok()
+        .filter(|speed: &f32| *speed > 0.0 && speed.is_finite())
+}
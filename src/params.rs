@@ -0,0 +1,278 @@
+use eframe::egui;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// A field type that knows how to draw itself in a parameter panel.
+pub trait ParamWidget: Sized {
+    /// Draws the widget and returns whether the value changed.
+    fn widget(&mut self, ui: &mut egui::Ui, label: &str, range: Option<RangeInclusive<Self>>) -> bool;
+
+    /// The value as a number, for reporting slider bounds in a `ParamSpec`.
+    fn as_f64(&self) -> Option<f64> {
+        None
+    }
+}
+
+/// Describes one named parameter of a `Params` set.
+#[derive(Clone, Debug)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub range: Option<(f64, f64)>,
+}
+
+impl ParamSpec {
+    pub fn new<T: ParamWidget>(name: &'static str, range: Option<RangeInclusive<T>>) -> Self {
+        let range = range.and_then(|r| Some((r.start().as_f64()?, r.end().as_f64()?)));
+        Self { name, range }
+    }
+}
+
+macro_rules! numeric_widget {
+    ($($t:ty),*) => {$(
+        impl ParamWidget for $t {
+            fn widget(&mut self, ui: &mut egui::Ui, label: &str, range: Option<RangeInclusive<Self>>) -> bool {
+                match range {
+                    Some(range) => ui.add(egui::Slider::new(self, range).text(label)).changed(),
+                    None => {
+                        ui.horizontal(|ui| {
+                            let changed = ui.add(egui::DragValue::new(self)).changed();
+                            ui.label(label);
+                            changed
+                        })
+                        .inner
+                    }
+                }
+            }
+
+            fn as_f64(&self) -> Option<f64> {
+                Some(*self as f64)
+            }
+        }
+    )*};
+}
+
+numeric_widget!(f32, f64, i32, i64, u8, u32, u64, usize);
+
+impl ParamWidget for bool {
+    fn widget(&mut self, ui: &mut egui::Ui, label: &str, _range: Option<RangeInclusive<Self>>) -> bool {
+        ui.checkbox(self, label).changed()
+    }
+}
+
+/// An sRGB color.
+impl ParamWidget for [u8; 3] {
+    fn widget(&mut self, ui: &mut egui::Ui, label: &str, _range: Option<RangeInclusive<Self>>) -> bool {
+        ui.horizontal(|ui| {
+            let changed = ui.color_edit_button_srgb(self).changed();
+            ui.label(label);
+            changed
+        })
+        .inner
+    }
+}
+
+/// A serializable parameter struct with a generated egui panel. Implement it with `sim_params!`.
+pub trait SimParams: Default + Serialize + DeserializeOwned {
+    /// Draws one widget per field and returns whether any value changed.
+    fn fields_ui(&mut self, ui: &mut egui::Ui) -> bool;
+
+    fn specs() -> Vec<ParamSpec>;
+
+    /// The field widgets followed by a reset-to-defaults button.
+    fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = self.fields_ui(ui);
+        if ui.button("Reset to defaults").clicked() {
+            *self = Self::default();
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Object-safe view of a sim's parameters, used by the App for the panel and presets.
+pub trait Params {
+    fn specs(&self) -> Vec<ParamSpec>;
+    fn ui(&mut self, ui: &mut egui::Ui) -> bool;
+    fn to_json(&self) -> serde_json::Value;
+    fn set_json(&mut self, value: serde_json::Value) -> Result<(), String>;
+}
+
+impl<P: SimParams> Params for P {
+    fn specs(&self) -> Vec<ParamSpec> {
+        P::specs()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        SimParams::ui(self, ui)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn set_json(&mut self, value: serde_json::Value) -> Result<(), String> {
+        *self = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// Named parameter sets, keyed by simulation name and then preset name.
+#[derive(Default, Serialize, Deserialize)]
+pub struct PresetStore {
+    presets: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+}
+
+impl PresetStore {
+    #[cfg(not(target_arch = "wasm32"))]
+    const PATH: &'static str = "presets.json";
+
+    /// Reads the preset file, starting empty if it is missing or unreadable.
+    pub fn load() -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(data) = std::fs::read(Self::PATH) {
+            return serde_json::from_slice(&data).unwrap_or_default();
+        }
+        Self::default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let data = serde_json::to_vec_pretty(self).map_err(|e| e.to_string())?;
+            std::fs::write(Self::PATH, data).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn names(&self, sim: &str) -> Vec<String> {
+        self.presets.get(sim).map(|p| p.keys().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn get(&self, sim: &str, name: &str) -> Option<&serde_json::Value> {
+        self.presets.get(sim)?.get(name)
+    }
+
+    pub fn insert(&mut self, sim: &str, name: &str, values: serde_json::Value) {
+        self.presets.entry(sim.to_owned()).or_default().insert(name.to_owned(), values);
+    }
+
+    pub fn remove(&mut self, sim: &str, name: &str) {
+        if let Some(presets) = self.presets.get_mut(sim) {
+            presets.remove(name);
+        }
+    }
+}
+
+/// Declares a parameter struct with defaults, serde support and a `SimParams` panel.
+///
+/// ```
+/// use simulation_station::{sim_params, Params};
+///
+/// sim_params! {
+///     pub struct LifeParams {
+///         pub density: f32 = 0.3 => 0.0..=1.0,
+///         wrap: bool = true,
+///     }
+/// }
+///
+/// // Fields left out keep their defaults
+/// let mut params = LifeParams::default();
+/// params.set_json(serde_json::json!({ "density": 0.5 })).unwrap();
+/// assert_eq!(params.density, 0.5);
+/// assert_eq!(params.to_json(), serde_json::json!({ "density": 0.5, "wrap": true }));
+/// ```
+///
+/// Fields with a `=> range` get a slider; other numbers get a drag value, bools a checkbox and
+/// `[u8; 3]` a color picker. Fields keep the visibility they're declared with. The expansion
+/// reaches egui and serde through this crate, so the calling crate needs neither.
+#[macro_export]
+macro_rules! sim_params {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident : $ty:ty = $default:expr $(=> $range:expr)?),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq)]
+        $vis struct $name {
+            $($field_vis $field: $ty,)*
+        }
+
+        // Written out rather than derived, since serde's derives need the calling crate to
+        // depend on serde by that name
+        impl $crate::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: $crate::serde::Serializer,
+            {
+                use $crate::serde::ser::SerializeMap;
+                let mut map = serializer.serialize_map(None)?;
+                $(map.serialize_entry(stringify!($field), &self.$field)?;)*
+                map.end()
+            }
+        }
+
+        /// Fields missing from the input keep their defaults; unknown ones are skipped.
+        impl<'de> $crate::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: $crate::serde::Deserializer<'de>,
+            {
+                struct Fields;
+                impl<'de> $crate::serde::de::Visitor<'de> for Fields {
+                    type Value = $name;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str(concat!("the fields of ", stringify!($name)))
+                    }
+
+                    fn visit_map<A: $crate::serde::de::MapAccess<'de>>(
+                        self,
+                        mut map: A,
+                    ) -> Result<$name, A::Error> {
+                        let mut params = <$name as Default>::default();
+                        while let Some(key) = map.next_key::<String>()? {
+                            match key.as_str() {
+                                $(stringify!($field) => params.$field = map.next_value()?,)*
+                                _ => drop(map.next_value::<$crate::serde::de::IgnoredAny>()?),
+                            }
+                        }
+                        Ok(params)
+                    }
+                }
+                deserializer.deserialize_map(Fields)
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self { $($field: $default,)* }
+            }
+        }
+
+        impl $crate::SimParams for $name {
+            fn fields_ui(&mut self, ui: &mut $crate::eframe::egui::Ui) -> bool {
+                let mut changed = false;
+                $(
+                    changed |= $crate::ParamWidget::widget(
+                        &mut self.$field,
+                        ui,
+                        &stringify!($field).replace('_', " "),
+                        $crate::sim_params!(@range $($range)?),
+                    );
+                )*
+                changed
+            }
+
+            fn specs() -> Vec<$crate::ParamSpec> {
+                vec![$(
+                    $crate::ParamSpec::new::<$ty>(stringify!($field), $crate::sim_params!(@range $($range)?)),
+                )*]
+            }
+        }
+    };
+    (@range $range:expr) => { Some($range) };
+    (@range) => { None };
+}
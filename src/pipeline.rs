@@ -0,0 +1,489 @@
+use eframe::egui;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    sim_params, Capabilities, Category, ColorScale, Colormap, FieldScale, Grid, Neighborhood, Params,
+    SimConfig, SimError, SimHelp, SimInfo, Simulation, TextureFilter, Theme, ViewInfo, Viewport,
+    WorkerSettings,
+};
+
+/// Chains simulations: each step updates every stage in order and feeds a stage's
+/// `export_field()` into the next stage's `import_field()`. Each stage is offered as a view.
+pub struct PipelineSim {
+    name: String,
+    info: SimInfo,
+    stages: Vec<Box<dyn Simulation>>,
+    view: usize,
+    /// Bumped by every step, reset and parameter edit.
+    version: u64,
+}
+
+impl PipelineSim {
+    /// Chains `stages` in order. Fails if there are none, since there'd be nothing to show.
+    pub fn new(name: &str, info: SimInfo, stages: Vec<Box<dyn Simulation>>) -> Result<Self, SimError> {
+        if stages.is_empty() {
+            return Err(SimError::Other(format!("the {name} pipeline has no stages")));
+        }
+        Ok(Self { name: name.to_owned(), info, stages, view: 0, version: 0 })
+    }
+
+    fn shown(&self) -> &dyn Simulation {
+        self.stages[self.view].as_ref()
+    }
+}
+
+impl Simulation for PipelineSim {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn info(&self) -> SimInfo {
+        self.info
+    }
+
+    fn config(&self) -> SimConfig {
+        self.shown().config()
+    }
+
+    /// The shown stage's help, with every stage's parameters since the sim's panel shows
+    /// them all.
+    fn help(&self) -> SimHelp {
+        let mut help = self.shown().help();
+        help.params = self.stages.iter().flat_map(|stage| stage.help().params).collect();
+        help
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        let mut caps = Capabilities::EXPORT;
+        for stage in &self.stages {
+            caps |= stage.capabilities() & Capabilities::WORKERS;
+        }
+        caps
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        self.shown().dimensions()
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        for i in 0..self.stages.len() {
+            self.stages[i].update()?;
+            if let Some(field) = self.stages[i].export_field()
+                && let Some(next) = self.stages.get_mut(i + 1)
+            {
+                next.import_field(&field);
+            }
+        }
+        self.version += 1;
+        Ok(())
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.version)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.stages.iter().all(|stage| stage.is_finished())
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        self.shown().render(buffer);
+    }
+
+    fn render_at(&self, width: usize, height: usize, buffer: &mut Vec<u8>) {
+        self.shown().render_at(width, height, buffer);
+    }
+
+    fn color_scale(&self) -> Option<ColorScale> {
+        self.shown().color_scale()
+    }
+
+    fn render_vector(&self, painter: &egui::Painter, rect: egui::Rect) {
+        self.shown().render_vector(painter, rect);
+    }
+
+    fn render_world(&self, painter: &egui::Painter, viewport: &Viewport) {
+        self.shown().render_world(painter, viewport);
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        self.shown().probe(x, y)
+    }
+
+    fn views(&self) -> Vec<ViewInfo> {
+        self.stages
+            .iter()
+            .map(|stage| ViewInfo {
+                name: stage.name().to_owned(),
+                render_mode: stage.config().render_mode,
+            })
+            .collect()
+    }
+
+    fn select_view(&mut self, index: usize) {
+        self.view = index.min(self.stages.len() - 1);
+    }
+
+    fn set_running(&mut self, running: bool) {
+        for stage in &mut self.stages {
+            stage.set_running(running);
+        }
+    }
+
+    fn set_worker_settings(&mut self, settings: WorkerSettings) {
+        for stage in &mut self.stages {
+            stage.set_worker_settings(settings);
+        }
+    }
+
+    fn reset(&mut self, seed: u64) {
+        for (i, stage) in self.stages.iter_mut().enumerate() {
+            stage.reset(seed.wrapping_add(i as u64));
+        }
+        self.version += 1;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        for (i, stage) in self.stages.iter_mut().enumerate() {
+            egui::CollapsingHeader::new(format!("{}. {}", i + 1, stage.name()))
+                .default_open(true)
+                .show(ui, |ui| {
+                    stage.ui(ui);
+                    if let Some(params) = stage.params()
+                        && params.ui(ui)
+                    {
+                        self.version += 1;
+                    }
+                });
+        }
+    }
+
+    fn export_field(&self) -> Option<Grid<f32>> {
+        self.stages.last()?.export_field()
+    }
+
+    fn import_field(&mut self, field: &Grid<f32>) {
+        self.stages[0].import_field(field);
+        self.version += 1;
+    }
+}
+
+pub const NOISE_INFO: SimInfo = SimInfo {
+    description: "Noise is blurred into smooth blobs, then thresholded and cleaned up with a \
+                  majority-vote cellular automaton into cave-like regions. Views show each stage.",
+    category: Category::CellularAutomata,
+    tags: &["pipeline", "noise", "caves"],
+};
+
+const FIELD_W: usize = 200;
+const FIELD_H: usize = 150;
+
+pub fn noise_caves() -> PipelineSim {
+    PipelineSim::new(
+        "Noise Caves Pipeline",
+        NOISE_INFO,
+        vec![Box::new(NoiseStage::new()), Box::new(SmoothStage::new()), Box::new(ThresholdStage::new())],
+    )
+    .expect("the noise caves pipeline has three stages")
+}
+
+const NOISE_SCALE: FieldScale = FieldScale::fixed(0.0, 1.0);
+
+sim_params! {
+    pub struct NoiseParams {
+        pub churn: f32 = 0.02 => 0.0..=1.0,
+        pub colormap: Colormap = Colormap::Grayscale,
+    }
+}
+
+/// White noise in [0, 1], with a fraction of cells re-rolled every step.
+pub struct NoiseStage {
+    field: Grid<f32>,
+    rng: StdRng,
+    params: NoiseParams,
+}
+
+impl NoiseStage {
+    pub fn new() -> Self {
+        let mut stage = Self {
+            field: Grid::new(FIELD_W, FIELD_H),
+            rng: StdRng::seed_from_u64(0),
+            params: NoiseParams::default(),
+        };
+        stage.reset(0);
+        stage
+    }
+}
+
+impl Simulation for NoiseStage {
+    fn name(&self) -> &str {
+        "Noise"
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "Random values between 0 and 1, a few of which are re-rolled every step. \
+                       This is the raw material the later stages shape into caves.",
+            legend: self.params.colormap.legend("0", "1"),
+            params: vec![
+                ("churn", "Fraction of cells re-rolled each step"),
+                ("colormap", "Colors the values from 0 to 1"),
+            ],
+            controls: Vec::new(),
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.field.width(), self.field.height())
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        let churn = self.params.churn;
+        for v in self.field.cells_mut() {
+            if self.rng.random::<f32>() < churn {
+                *v = self.rng.random();
+            }
+        }
+        Ok(())
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        self.params.colormap.render_field(&self.field, &NOISE_SCALE, buffer);
+    }
+
+    fn color_scale(&self) -> Option<ColorScale> {
+        Some(ColorScale::for_field(&self.params.colormap, &NOISE_SCALE, &self.field, ""))
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        Some(format!("{:.3}", self.field.get(x, y)))
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        for v in self.field.cells_mut() {
+            *v = self.rng.random();
+        }
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui) {}
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+
+    fn export_field(&self) -> Option<Grid<f32>> {
+        Some(self.field.clone())
+    }
+}
+
+sim_params! {
+    pub struct SmoothParams {
+        pub passes: usize = 4 => 0..=16,
+        pub colormap: Colormap = Colormap::Viridis,
+        pub scale: FieldScale = FieldScale::fixed(0.0, 1.0),
+    }
+}
+
+/// Repeated 3x3 box blur of the imported field.
+pub struct SmoothStage {
+    field: Grid<f32>,
+    params: SmoothParams,
+}
+
+impl SmoothStage {
+    pub fn new() -> Self {
+        Self { field: Grid::new(FIELD_W, FIELD_H), params: SmoothParams::default() }
+    }
+}
+
+impl Simulation for SmoothStage {
+    fn name(&self) -> &str {
+        "Smooth"
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "The noise after repeated 3x3 box blurs, which merge it into smooth blobs.",
+            legend: self.params.colormap.legend("Low", "High"),
+            params: vec![
+                ("passes", "Blurs applied each step; more passes make larger blobs"),
+                ("colormap", "Colors the values across the scale"),
+                ("scale", "Values at the ends of the colormap; auto stretches it over the field"),
+            ],
+            controls: Vec::new(),
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig { texture_filter: TextureFilter::Linear, upscale: 2, ..SimConfig::default() }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.field.width(), self.field.height())
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        for _ in 0..self.params.passes {
+            // The mean of the 3x3 block, or what's left of it at the edges
+            self.field.step(Neighborhood::Moore, |&v, neighbors| {
+                let (sum, count) =
+                    neighbors.iter().fold((v, 1.0), |(sum, count), &n| (sum + n, count + 1.0));
+                sum / count
+            });
+        }
+        Ok(())
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        self.params.colormap.render_field(&self.field, &self.params.scale, buffer);
+    }
+
+    fn color_scale(&self) -> Option<ColorScale> {
+        Some(ColorScale::for_field(&self.params.colormap, &self.params.scale, &self.field, ""))
+    }
+
+    /// The field is smooth, so larger exports interpolate it bilinearly instead of
+    /// enlarging its cells.
+    fn render_at(&self, width: usize, height: usize, buffer: &mut Vec<u8>) {
+        let (w, h) = self.dimensions();
+        let mut field = Grid::new(width, height);
+        for y in 0..height {
+            let fy = ((y as f32 + 0.5) * h as f32 / height as f32 - 0.5).clamp(0.0, (h - 1) as f32);
+            let (y0, ty) = (fy as usize, fy.fract());
+            let y1 = (y0 + 1).min(h - 1);
+            for x in 0..width {
+                let fx = ((x as f32 + 0.5) * w as f32 / width as f32 - 0.5).clamp(0.0, (w - 1) as f32);
+                let (x0, tx) = (fx as usize, fx.fract());
+                let x1 = (x0 + 1).min(w - 1);
+                let top = self.field.get(x0, y0) * (1.0 - tx) + self.field.get(x1, y0) * tx;
+                let bottom = self.field.get(x0, y1) * (1.0 - tx) + self.field.get(x1, y1) * tx;
+                field.set(x, y, top * (1.0 - ty) + bottom * ty);
+            }
+        }
+        buffer.resize(width * height * 3, 0);
+        // Fit an auto scale to the original field so the export matches the live view
+        let range = self.params.scale.range(self.field.cells());
+        let scale = FieldScale { auto: false, min: range.0, max: range.1, ..self.params.scale };
+        self.params.colormap.render_field(&field, &scale, buffer);
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        Some(format!("{:.3}", self.field.get(x, y)))
+    }
+
+    fn reset(&mut self, _seed: u64) {
+        self.field.cells_mut().fill(0.0);
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui) {}
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+
+    fn export_field(&self) -> Option<Grid<f32>> {
+        Some(self.field.clone())
+    }
+
+    fn import_field(&mut self, field: &Grid<f32>) {
+        self.field = field.clone();
+    }
+}
+
+sim_params! {
+    pub struct ThresholdParams {
+        pub level: f32 = 0.5 => 0.0..=1.0,
+        pub iterations: usize = 3 => 0..=10,
+    }
+}
+
+/// Thresholds the imported field into walls/floor, then applies a majority-vote CA.
+pub struct ThresholdStage {
+    input: Grid<f32>,
+    cells: Grid<bool>,
+    params: ThresholdParams,
+}
+
+impl ThresholdStage {
+    pub fn new() -> Self {
+        Self {
+            input: Grid::new(FIELD_W, FIELD_H),
+            cells: Grid::new(FIELD_W, FIELD_H),
+            params: ThresholdParams::default(),
+        }
+    }
+}
+
+impl Simulation for ThresholdStage {
+    fn name(&self) -> &str {
+        "Threshold CA"
+    }
+
+    fn help(&self) -> SimHelp {
+        let theme = Theme::current();
+        SimHelp {
+            overview: "The blurred field cut into wall and floor at a threshold, then cleaned up by \
+                       a cellular automaton where each cell takes the majority of its 3x3 block.",
+            legend: vec![(theme.foreground, "Wall"), (theme.background, "Floor")],
+            params: vec![
+                ("level", "Values above this become wall"),
+                ("iterations", "Majority-vote passes; more passes give smoother caves"),
+            ],
+            controls: Vec::new(),
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.cells.width(), self.cells.height())
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        let level = self.params.level;
+        for (cell, &v) in self.cells.cells_mut().iter_mut().zip(self.input.cells()) {
+            *cell = v > level;
+        }
+        for _ in 0..self.params.iterations {
+            // Majority of the 3x3 block, counting the cell itself
+            self.cells.step(Neighborhood::Moore, |&wall, neighbors| {
+                wall as usize + neighbors.count_where(|&n| n) >= 5
+            });
+        }
+        Ok(())
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        let theme = Theme::current();
+        let (wall, open) = (Theme::rgb(theme.foreground), Theme::rgb(theme.background));
+        let (w, h) = self.dimensions();
+        self.cells.render_into(buffer, w, h, |&is_wall| if is_wall { wall } else { open });
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        let kind = if *self.cells.get(x, y) { "wall" } else { "floor" };
+        Some(format!("{kind} (input {:.3})", self.input.get(x, y)))
+    }
+
+    fn reset(&mut self, _seed: u64) {
+        self.input.cells_mut().fill(0.0);
+        self.cells.cells_mut().fill(false);
+    }
+
+    fn ui(&mut self, _ui: &mut egui::Ui) {}
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+
+    fn export_field(&self) -> Option<Grid<f32>> {
+        let mut field = Grid::new(self.cells.width(), self.cells.height());
+        for (v, &wall) in field.cells_mut().iter_mut().zip(self.cells.cells()) {
+            *v = if wall { 1.0 } else { 0.0 };
+        }
+        Some(field)
+    }
+
+    fn import_field(&mut self, field: &Grid<f32>) {
+        self.input = field.clone();
+    }
+}
@@ -0,0 +1,112 @@
+use eframe::egui;
+use crate::{
+    sim_params, Category, Colormap, Palette, Params, PixelFormat, SimConfig, SimError, SimHelp, SimInfo,
+    Simulation,
+};
+
+pub const INFO: SimInfo = SimInfo {
+    description: "The demoscene plasma: overlapping sine waves drawn as palette indices, so the \
+                  colors can be swapped and cycled without redrawing anything.",
+    category: Category::Demo,
+    tags: &["palette", "demoscene", "demo"],
+};
+
+sim_params! {
+    pub struct PlasmaParams {
+        pub scale: f32 = 24.0 => 4.0..=100.0,
+        pub drift: f32 = 0.05 => 0.0..=0.5,
+    }
+}
+
+/// Renders `PixelFormat::Indexed` pixels; the App's palette supplies the colors.
+pub struct PlasmaSim {
+    time: f32,
+    params: PlasmaParams,
+}
+
+impl PlasmaSim {
+    const WIDTH: usize = 320;
+    const HEIGHT: usize = 200;
+
+    pub fn new() -> Self {
+        Self { time: 0.0, params: PlasmaParams::default() }
+    }
+}
+
+impl Simulation for PlasmaSim {
+    fn name(&self) -> &str {
+        "Plasma"
+    }
+
+    fn info(&self) -> SimInfo {
+        INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "Each pixel's palette index is the sum of four sine waves over its position, \
+                       two of which move as the sim steps. Open Palette to pick the colors and set \
+                       them cycling, which animates the plasma even while paused.",
+            legend: Vec::new(),
+            params: vec![
+                ("scale", "Wavelength of the sine waves, in pixels"),
+                ("drift", "How far the waves move each step"),
+            ],
+            controls: Vec::new(),
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            max_speed: 240.0,
+            default_speed: 30.0,
+            pixel_format: PixelFormat::Indexed,
+            ..SimConfig::default()
+        }
+    }
+
+    fn palette(&self) -> Palette {
+        Palette::new(Colormap::Custom(vec![
+            [0, 0, 64],
+            [0, 160, 255],
+            [255, 255, 255],
+            [255, 64, 160],
+            [0, 0, 64],
+        ]))
+        .cycling(40.0)
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (Self::WIDTH, Self::HEIGHT)
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        self.time += self.params.drift;
+        Ok(())
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        let (t, scale) = (self.time, self.params.scale);
+        let (cx, cy) = (Self::WIDTH as f32 / 2.0, Self::HEIGHT as f32 / 2.0);
+        for (i, index) in buffer.iter_mut().enumerate() {
+            let (x, y) = ((i % Self::WIDTH) as f32, (i / Self::WIDTH) as f32);
+            let v = (x / scale).sin()
+                + (y / scale * 0.5 + t).sin()
+                + ((x + y) / scale * 0.5).sin()
+                + ((x - cx).hypot(y - cy) / scale - t).sin();
+            *index = ((v + 4.0) * 32.0).min(255.0) as u8;
+        }
+    }
+
+    fn reset(&mut self, _seed: u64) {
+        self.time = 0.0;
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!("Phase: {:.2}", self.time));
+    }
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+}
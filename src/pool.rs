@@ -0,0 +1,88 @@
+use eframe::egui;
+use std::sync::Arc;
+
+/// Frames [`FramePool`] holds on to at most, waiting for egui or the history to let go.
+const MAX_SPARES: usize = 4;
+
+/// A slot's pixel storage, kept between paints so that steady-state rendering doesn't
+/// allocate: the byte buffers sims render into, and recent frames' images, which are
+/// written over in place once egui and the history are done with them.
+#[derive(Default)]
+pub(crate) struct FramePool {
+    pixels: Vec<u8>,
+    layer: Vec<u8>,
+    spares: Vec<Arc<egui::ColorImage>>,
+}
+
+impl FramePool {
+    /// A zeroed buffer of `len` bytes for `render()`.
+    pub(crate) fn pixels(&mut self, len: usize) -> &mut [u8] {
+        zeroed(&mut self.pixels, len)
+    }
+
+    /// A zeroed buffer of `len` bytes for `render_layer()`.
+    pub(crate) fn layer(&mut self, len: usize) -> &mut [u8] {
+        zeroed(&mut self.layer, len)
+    }
+
+    /// An image of `size` to fill, reusing one handed to [`recycle`](Self::recycle) that
+    /// nothing else holds anymore. Its pixels are whatever they were.
+    pub(crate) fn image(&mut self, size: [usize; 2]) -> egui::ColorImage {
+        self.spares.retain(|spare| spare.size == size);
+        let free = self.spares.iter().position(|spare| Arc::strong_count(spare) == 1);
+        match free.and_then(|index| Arc::try_unwrap(self.spares.swap_remove(index)).ok()) {
+            Some(image) => image,
+            None => egui::ColorImage::filled(size, egui::Color32::BLACK),
+        }
+    }
+
+    /// Keeps a handle on a frame that's been shown or that the history let go of, to reuse
+    /// its pixels once it's dropped everywhere else.
+    pub(crate) fn recycle(&mut self, image: Arc<egui::ColorImage>) {
+        if self.spares.len() >= MAX_SPARES {
+            self.spares.remove(0);
+        }
+        self.spares.push(image);
+    }
+}
+
+fn zeroed(buffer: &mut Vec<u8>, len: usize) -> &mut [u8] {
+    buffer.clear();
+    buffer.resize(len, 0);
+    buffer
+}
+
+/// Copies packed RGB bytes into `image`, which must have a pixel for every three.
+pub(crate) fn copy_rgb(image: &mut egui::ColorImage, rgb: &[u8]) {
+    for (pixel, px) in image.pixels.iter_mut().zip(rgb.chunks_exact(3)) {
+        *pixel = egui::Color32::from_rgb(px[0], px[1], px[2]);
+    }
+}
+
+/// Looks up palette indices in `lut` into `image`.
+pub(crate) fn copy_indexed(image: &mut egui::ColorImage, indices: &[u8], lut: &[[u8; 3]; 256]) {
+    for (pixel, &index) in image.pixels.iter_mut().zip(indices) {
+        let [r, g, b] = lut[index as usize];
+        *pixel = egui::Color32::from_rgb(r, g, b);
+    }
+}
+
+/// Blends straight-alpha RGBA bytes over `background` into `image`, which must have a
+/// pixel for every four.
+pub(crate) fn copy_rgba_over(image: &mut egui::ColorImage, rgba: &[u8], background: egui::Color32) {
+    image.as_raw_mut().copy_from_slice(rgba);
+    blend_over(image, background);
+}
+
+/// Blends straight-alpha RGBA written directly into `image`'s bytes over `background`,
+/// leaving opaque pixels, usually most of them, as they are.
+pub(crate) fn blend_over(image: &mut egui::ColorImage, background: egui::Color32) {
+    let bg = [background.r(), background.g(), background.b()];
+    for px in image.as_raw_mut().chunks_exact_mut(4).filter(|px| px[3] != 255) {
+        let a = px[3] as u32;
+        for (c, b) in px[..3].iter_mut().zip(bg) {
+            *c = ((*c as u32 * a + b as u32 * (255 - a)) / 255) as u8;
+        }
+        px[3] = 255;
+    }
+}
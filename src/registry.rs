@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    chunked_grid, diffusion3d, hex_grid, life, p0014, pipeline, plasma, simple_grid, sparse_grid, wator,
+    Category, SimInfo, Simulation,
+};
+
+type SimFactory = Box<dyn Fn() -> Box<dyn Simulation>>;
+
+/// A loadable simulation: its display name, metadata, and how to build a fresh instance.
+pub struct SimEntry {
+    pub name: String,
+    pub info: SimInfo,
+    factory: SimFactory,
+}
+
+impl SimEntry {
+    pub fn create(&self) -> Box<dyn Simulation> {
+        (self.factory)()
+    }
+}
+
+/// Every simulation the App can load. Built-ins register themselves in `Default`;
+/// embedders can add their own before handing the registry to `App::with_registry`.
+pub struct SimRegistry {
+    entries: Vec<SimEntry>,
+}
+
+impl SimRegistry {
+    pub fn empty() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    pub fn register(
+        &mut self,
+        name: &str,
+        info: SimInfo,
+        factory: impl Fn() -> Box<dyn Simulation> + 'static,
+    ) -> &mut Self {
+        self.entries.push(SimEntry {
+            name: name.to_owned(),
+            info,
+            factory: Box::new(factory),
+        });
+        self
+    }
+
+    pub fn entries(&self) -> &[SimEntry] {
+        &self.entries
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SimEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// Entries grouped by category, in registration order within each group.
+    pub fn by_category(&self) -> BTreeMap<Category, Vec<&SimEntry>> {
+        let mut groups: BTreeMap<Category, Vec<&SimEntry>> = BTreeMap::new();
+        for entry in &self.entries {
+            groups.entry(entry.info.category).or_default().push(entry);
+        }
+        groups
+    }
+}
+
+impl Default for SimRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register("Simple Pixel Fill", simple_grid::INFO, || {
+                Box::new(simple_grid::PixelFillSim::new())
+            })
+            .register("Problem 14: Collatz", p0014::INFO, || Box::new(p0014::sim()))
+            .register("Noise Caves Pipeline", pipeline::NOISE_INFO, || {
+                Box::new(pipeline::noise_caves())
+            })
+            .register("Plasma", plasma::INFO, || Box::new(plasma::PlasmaSim::new()))
+            .register("3D Heat Diffusion", diffusion3d::INFO, || {
+                Box::new(diffusion3d::Diffusion3D::new())
+            })
+            .register("Wa-Tor", wator::INFO, || Box::new(wator::WatorSim::new()))
+            .register("Snowflake", hex_grid::SNOWFLAKE_INFO, || Box::new(hex_grid::SnowflakeSim::new()))
+            .register("Game of Life", life::INFO, || Box::new(life::LifeSim::new()))
+            .register("Langton's Ant", sparse_grid::LANGTON_INFO, || {
+                Box::new(sparse_grid::LangtonSim::new())
+            })
+            .register("Falling Sand", chunked_grid::SAND_INFO, || {
+                Box::new(chunked_grid::SandSim::new())
+            });
+        #[cfg(feature = "gpu")]
+        registry.register("GPU Life", crate::gpu::LIFE_INFO, || {
+            Box::new(crate::GpuSim::new(crate::gpu::GpuLife))
+        });
+        registry
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+use crate::{SimStats, Simulation};
+
+pub(crate) struct Checkpoint {
+    pub(crate) stats: SimStats,
+    pub(crate) data: Vec<u8>,
+}
+
+/// Rolling buffer of `save_state()` checkpoints taken every [`Rewind::INTERVAL`] seconds
+/// of run time, oldest first. Once the buffer outgrows its byte budget the oldest
+/// checkpoints are dropped.
+#[derive(Default)]
+pub(crate) struct Rewind {
+    checkpoints: VecDeque<Checkpoint>,
+    bytes: usize,
+    /// The checkpoint the sim was rewound to, while the user is inspecting it.
+    pub(crate) cursor: Option<usize>,
+}
+
+impl Rewind {
+    /// Seconds of run time between checkpoints.
+    const INTERVAL: f64 = 0.25;
+    /// Memory one sim's checkpoints may take up.
+    const BUDGET: usize = 64 * 1024 * 1024;
+
+    /// Takes a checkpoint if one is due. Running again after a rewind forgets the
+    /// checkpoints that came after it, so the timeline branches from the rewound state.
+    pub(crate) fn record(&mut self, sim: &dyn Simulation, stats: SimStats) {
+        if let Some(cursor) = self.cursor.take() {
+            for dropped in self.checkpoints.drain(cursor + 1..) {
+                self.bytes -= dropped.data.len();
+            }
+        }
+        let due = self
+            .checkpoints
+            .back()
+            .is_none_or(|last| stats.wall_time - last.stats.wall_time >= Self::INTERVAL);
+        if !due {
+            return;
+        }
+        let Some(data) = sim.save_state() else { return };
+        self.bytes += data.len();
+        self.checkpoints.push_back(Checkpoint { stats, data });
+        while self.bytes > Self::BUDGET && self.checkpoints.len() > 1 {
+            if let Some(oldest) = self.checkpoints.pop_front() {
+                self.bytes -= oldest.data.len();
+            }
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    pub(crate) fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&Checkpoint> {
+        self.checkpoints.get(index)
+    }
+
+    /// The checkpoint taken closest to `wall_time` seconds into the run.
+    pub(crate) fn nearest(&self, wall_time: f64) -> Option<usize> {
+        (0..self.checkpoints.len()).min_by(|&a, &b| {
+            let distance = |i: usize| (self.checkpoints[i].stats.wall_time - wall_time).abs();
+            distance(a).total_cmp(&distance(b))
+        })
+    }
+}
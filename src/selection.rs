@@ -0,0 +1,209 @@
+use eframe::egui;
+use crate::{Annotations, Grid, Region, SimInput, Theme};
+
+/// What pointer input on the canvas does while a sim's [`GridSelection`] is in use.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SelectionTool {
+    /// Input goes to the sim as usual.
+    #[default]
+    Off,
+    /// Dragging outlines the selection.
+    Select,
+    /// Clicking pastes the clipboard with its top-left corner on the clicked cell.
+    Paste,
+}
+
+/// Rectangle select, copy, cut and paste for a sim built on a [`Grid`], with the clipboard
+/// turned and mirrored before pasting. The sim keeps one next to its grid and hands it the
+/// grid from `handle_input`, `ui` and `annotate`:
+///
+/// ```
+/// use eframe::egui::PointerButton;
+/// use simulation_station::{Grid, GridSelection, SelectionTool, SimInput};
+///
+/// let mut grid = Grid::new(8, 8);
+/// grid.set(1, 1, true);
+/// grid.set(2, 1, true);
+/// let mut selection = GridSelection::default();
+/// selection.tool = SelectionTool::Select;
+/// let (button, pos) = (PointerButton::Primary, Default::default());
+/// selection.handle_input(&mut grid, SimInput::PointerDown { x: 1, y: 1, button, pos });
+/// selection.handle_input(&mut grid, SimInput::PointerUp { x: 2, y: 1, button, pos });
+/// selection.copy(&grid);
+/// selection.rotate();
+/// selection.paste(&mut grid, 5, 5);
+/// assert!(*grid.get(5, 5) && *grid.get(5, 6) && !grid.get(6, 5));
+/// ```
+#[derive(Clone, Default)]
+pub struct GridSelection<T> {
+    pub tool: SelectionTool,
+    region: Option<Region>,
+    /// The cell the drag outlining `region` started on.
+    drag_start: Option<(usize, usize)>,
+    clipboard: Option<Grid<T>>,
+}
+
+impl<T: Clone + Default> GridSelection<T> {
+    pub fn region(&self) -> Option<Region> {
+        self.region
+    }
+
+    pub fn clipboard(&self) -> Option<&Grid<T>> {
+        self.clipboard.as_ref()
+    }
+
+    /// Puts `pattern` on the clipboard and switches to pasting it, e.g. for a loaded file.
+    pub fn set_clipboard(&mut self, pattern: Grid<T>) {
+        self.clipboard = Some(pattern);
+        self.tool = SelectionTool::Paste;
+    }
+
+    /// Copies the selected cells to the clipboard. Returns false with nothing selected.
+    pub fn copy(&mut self, grid: &Grid<T>) -> bool {
+        let Some(region) = self.region else { return false };
+        self.clipboard = Some(grid.copy_region(region));
+        true
+    }
+
+    /// Copies the selected cells, then empties them.
+    pub fn cut(&mut self, grid: &mut Grid<T>) -> bool {
+        self.copy(grid) && self.clear(grid)
+    }
+
+    /// Sets the selected cells to their default.
+    pub fn clear(&mut self, grid: &mut Grid<T>) -> bool {
+        let Some(region) = self.region else { return false };
+        grid.fill_region(region, T::default());
+        true
+    }
+
+    /// Writes the clipboard into `grid` at (x, y) and selects where it went.
+    pub fn paste(&mut self, grid: &mut Grid<T>, x: usize, y: usize) -> bool {
+        let Some(clipboard) = &self.clipboard else { return false };
+        grid.paste(clipboard, x as isize, y as isize);
+        let (width, height) = (clipboard.width(), clipboard.height());
+        self.region = Some(Region { x, y, width, height }).filter(|_| width > 0 && height > 0);
+        true
+    }
+
+    /// Turns the clipboard a quarter turn clockwise.
+    pub fn rotate(&mut self) {
+        self.clipboard = self.clipboard.as_ref().map(Grid::rotated);
+    }
+
+    /// Mirrors the clipboard left to right.
+    pub fn mirror(&mut self) {
+        self.clipboard = self.clipboard.as_ref().map(Grid::mirrored);
+    }
+
+    /// Flips the clipboard top to bottom.
+    pub fn flip(&mut self) {
+        self.clipboard = self.clipboard.as_ref().map(Grid::flipped);
+    }
+
+    /// Applies `event` to the selection or the grid if a tool is on, returning whether it
+    /// did; the sim handles the event itself otherwise. Besides the pointer, C copies, X
+    /// cuts, V starts pasting, Delete clears, R, M and F rotate, mirror and flip the
+    /// clipboard, and Escape turns the tool off.
+    pub fn handle_input(&mut self, grid: &mut Grid<T>, event: SimInput) -> bool {
+        if self.tool == SelectionTool::Off {
+            return false;
+        }
+        let primary = egui::PointerButton::Primary;
+        match (self.tool, event) {
+            (SelectionTool::Select, SimInput::PointerDown { x, y, button, .. }) if button == primary => {
+                self.drag_start = Some((x, y));
+                self.region = Some(Region { x, y, width: 1, height: 1 });
+            }
+            (
+                SelectionTool::Select,
+                SimInput::PointerDrag { x, y, .. } | SimInput::PointerUp { x, y, .. },
+            ) => {
+                if let Some((x0, y0)) = self.drag_start {
+                    let (left, top) = (x0.min(x), y0.min(y));
+                    let (width, height) = (x0.max(x) - left + 1, y0.max(y) - top + 1);
+                    self.region = Some(Region { x: left, y: top, width, height });
+                }
+                if matches!(event, SimInput::PointerUp { .. }) {
+                    self.drag_start = None;
+                }
+            }
+            (SelectionTool::Paste, SimInput::PointerDown { x, y, button, .. }) if button == primary => {
+                self.paste(grid, x, y);
+            }
+            (_, SimInput::Key { key, pressed: true }) => match key {
+                egui::Key::C => _ = self.copy(grid),
+                egui::Key::X => _ = self.cut(grid),
+                egui::Key::V if self.clipboard.is_some() => self.tool = SelectionTool::Paste,
+                egui::Key::Delete | egui::Key::Backspace => _ = self.clear(grid),
+                egui::Key::R => self.rotate(),
+                egui::Key::M => self.mirror(),
+                egui::Key::F => self.flip(),
+                egui::Key::Escape => self.tool = SelectionTool::Off,
+                _ => {}
+            },
+            _ => {}
+        }
+        true
+    }
+
+    /// The tool picker and the edit buttons. Returns whether the grid changed.
+    pub fn ui(&mut self, ui: &mut egui::Ui, grid: &mut Grid<T>) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.tool, SelectionTool::Off, "✏ Draw");
+            ui.selectable_value(&mut self.tool, SelectionTool::Select, "⬚ Select")
+                .on_hover_text("Drag out a rectangle of cells");
+            let paste = egui::Button::selectable(self.tool == SelectionTool::Paste, "📋 Paste");
+            let paste = ui.add_enabled(self.clipboard.is_some(), paste);
+            if paste.on_hover_text("Click the canvas to paste the clipboard there").clicked() {
+                self.tool = SelectionTool::Paste;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(self.region.is_some(), |ui| {
+                if ui.button("Copy").on_hover_text("C").clicked() {
+                    self.copy(grid);
+                }
+                if ui.button("Cut").on_hover_text("X").clicked() {
+                    changed |= self.cut(grid);
+                }
+                if ui.button("Clear").on_hover_text("Delete").clicked() {
+                    changed |= self.clear(grid);
+                }
+            });
+        });
+        if let Some((width, height)) = self.clipboard.as_ref().map(|c| (c.width(), c.height())) {
+            ui.horizontal(|ui| {
+                ui.label(format!("Clipboard: {width} × {height}"));
+                if ui.button("⟳").on_hover_text("Rotate clockwise (R)").clicked() {
+                    self.rotate();
+                }
+                if ui.button("↔").on_hover_text("Mirror (M)").clicked() {
+                    self.mirror();
+                }
+                if ui.button("↕").on_hover_text("Flip (F)").clicked() {
+                    self.flip();
+                }
+            });
+        }
+        changed
+    }
+
+    /// Outlines the selection while a tool is on.
+    pub fn annotate(&self, annotations: &mut Annotations) {
+        let Some(region) = self.region.filter(|_| self.tool != SelectionTool::Off) else { return };
+        let (left, top) = (region.x as f32, region.y as f32);
+        let (right, bottom) = (left + region.width as f32, top + region.height as f32);
+        let corners = [
+            egui::pos2(left, top),
+            egui::pos2(right, top),
+            egui::pos2(right, bottom),
+            egui::pos2(left, bottom),
+        ];
+        let stroke = (1.5, Theme::current().accent);
+        for (i, &corner) in corners.iter().enumerate() {
+            annotations.line(corner, corners[(i + 1) % 4], stroke);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use crate::SimInput;
+
+/// Something that changed the course of a recorded run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum SessionAction {
+    /// New parameter values, as `Params::to_json` wrote them.
+    Params(Value),
+    Speed(f32),
+    Input(SimInput),
+    Reset(u64),
+}
+
+/// An action and the step count it happened at, counted from the last reset.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct SessionEvent {
+    pub(crate) step: u64,
+    pub(crate) action: SessionAction,
+}
+
+/// Everything needed to reproduce a run: the sim, how it started, and what the user did
+/// to it along the way. Saved as JSON. Actions are keyed by step count, so sims stepped
+/// at a set speed replay exactly; realtime sims get their actions at the same frames but
+/// advance by this machine's frame times.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SessionFile {
+    /// Registry name of the sim.
+    pub(crate) sim: String,
+    pub(crate) seed: u64,
+    pub(crate) params: Option<Value>,
+    pub(crate) speed: f32,
+    pub(crate) events: Vec<SessionEvent>,
+    /// Step count the recording stopped at.
+    pub(crate) end_step: u64,
+}
+
+impl SessionFile {
+    pub(crate) fn from_json(data: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(data).map_err(|e| e.to_string())
+    }
+}
+
+/// Builds a [`SessionFile`] while the user interacts with a sim that was just reset.
+pub(crate) struct SessionRecorder {
+    file: SessionFile,
+    last_params: Option<Value>,
+    last_speed: f32,
+}
+
+// Recordings are only started, counted and saved by the native session panel
+impl SessionRecorder {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn new(sim: &str, seed: u64, params: Option<Value>, speed: f32) -> Self {
+        Self {
+            file: SessionFile {
+                sim: sim.to_owned(),
+                seed,
+                params: params.clone(),
+                speed,
+                events: Vec::new(),
+                end_step: 0,
+            },
+            last_params: params,
+            last_speed: speed,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn len(&self) -> usize {
+        self.file.events.len()
+    }
+
+    pub(crate) fn record(&mut self, step: u64, action: SessionAction) {
+        self.file.events.push(SessionEvent { step, action });
+    }
+
+    /// Records the parameters and speed if they changed since the last look.
+    pub(crate) fn observe(&mut self, step: u64, params: Option<Value>, speed: f32) {
+        if params != self.last_params {
+            self.last_params = params.clone();
+            if let Some(params) = params {
+                self.record(step, SessionAction::Params(params));
+            }
+        }
+        if speed != self.last_speed {
+            self.last_speed = speed;
+            self.record(step, SessionAction::Speed(speed));
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn finish(mut self, end_step: u64) -> SessionFile {
+        self.file.end_step = end_step;
+        self.file
+    }
+}
+
+/// Hands a recorded session's actions back out as the replaying sim reaches their steps.
+pub(crate) struct SessionPlayback {
+    events: VecDeque<SessionEvent>,
+    end_step: u64,
+}
+
+impl SessionPlayback {
+    pub(crate) fn new(file: &SessionFile) -> Self {
+        Self { events: file.events.iter().cloned().collect(), end_step: file.end_step }
+    }
+
+    /// The next action due at `step`, if any.
+    pub(crate) fn pop_due(&mut self, step: u64) -> Option<SessionAction> {
+        if self.events.front()?.step > step {
+            return None;
+        }
+        self.events.pop_front().map(|event| event.action)
+    }
+
+    /// Steps that can run before the next action is due, or before the recording ends.
+    pub(crate) fn steps_until_next(&self, step: u64) -> u64 {
+        self.events.front().map_or(self.end_step, |event| event.step).saturating_sub(step)
+    }
+
+    pub(crate) fn is_finished(&self, step: u64) -> bool {
+        self.events.is_empty() && step >= self.end_step
+    }
+}
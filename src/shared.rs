@@ -0,0 +1,72 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// State an AsyncSim's worker and the UI hold in common, for states too big to copy on
+/// every step (multi-megabyte grids stepped thousands of times a second). Use `Shared<S>`
+/// as the sim's state type: the worker steps it under [`write`](Self::write) and `send`s a
+/// clone of the handle, which copies nothing, and renderers [`read`](Self::read) it.
+///
+/// Clones share the state. A worker stopped mid-step may finish that step, so a sim
+/// restarted from its current state can see one extra; snapshots, checkpoints and resets
+/// start from a state of their own.
+///
+/// ```
+/// use simulation_station::{AsyncSim, Shared};
+///
+/// let sim = AsyncSim::<Shared<Vec<u32>>>::builder("Counters")
+///     .spawner(|cells, ctx| {
+///         cells.write().resize(1 << 20, 0);
+///         loop {
+///             cells.write().iter_mut().for_each(|cell| *cell += 1);
+///             if !ctx.send(cells.clone()) {
+///                 break;
+///             }
+///         }
+///     })
+///     .ui(|cells, ui| {
+///         ui.label(format!("Steps: {}", cells.read().first().copied().unwrap_or(0)));
+///     })
+///     .build();
+/// ```
+pub struct Shared<S>(Arc<RwLock<S>>);
+
+impl<S> Shared<S> {
+    pub fn new(state: S) -> Self {
+        Self(Arc::new(RwLock::new(state)))
+    }
+
+    /// Waits out a step in progress. A worker that panicked mid-step leaves the state as
+    /// it was when it did.
+    pub fn read(&self) -> RwLockReadGuard<'_, S> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, S> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Another handle to the same state.
+impl<S> Clone for Shared<S> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<S: Default> Default for Shared<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: Serialize> Serialize for Shared<S> {
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.read().serialize(serializer)
+    }
+}
+
+impl<'de, S: Deserialize<'de>> Deserialize<'de> for Shared<S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        S::deserialize(deserializer).map(Self::new)
+    }
+}
@@ -1,11 +1,118 @@
 use eframe::egui;
-use crate::Simulation;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::{
+    sim_params, Annotations, Axis, Brush, Capabilities, Category, GridSelection, LayerInfo, Metrics,
+    Params, Region, SimConfig, SimError, SimHelp, SimInfo, SimInput, Simulation, SpeedCurve, StateDelta,
+    Theme,
+};
 
-#[derive(Clone)]
+pub const INFO: SimInfo = SimInfo {
+    description: "Fills the grid pixel by pixel, row by row or in a seeded random order. \
+                  Click or drag to paint pixels.",
+    category: Category::Demo,
+    tags: &["grid", "demo"],
+};
+
+/// Which cells around a cell count as its neighbors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Neighborhood {
+    /// The eight cells sharing an edge or a corner.
+    #[default]
+    Moore,
+    /// The four cells sharing an edge.
+    VonNeumann,
+}
+
+impl Neighborhood {
+    /// (dx, dy) of each neighbor, clockwise from the one above.
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Self::Moore => &[(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)],
+            Self::VonNeumann => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+        }
+    }
+
+    /// (dx, dy, dz) of each neighbor in a [`Grid3`]: Moore's 26 cells sharing a face, edge
+    /// or corner, or von Neumann's 6 sharing a face. Layer by layer from dz = -1, each
+    /// layer row by row.
+    pub fn offsets_3d(self) -> &'static [(isize, isize, isize)] {
+        match self {
+            Self::Moore => &[
+                (-1, -1, -1), (0, -1, -1), (1, -1, -1),
+                (-1, 0, -1), (0, 0, -1), (1, 0, -1),
+                (-1, 1, -1), (0, 1, -1), (1, 1, -1),
+                (-1, -1, 0), (0, -1, 0), (1, -1, 0),
+                (-1, 0, 0), (1, 0, 0),
+                (-1, 1, 0), (0, 1, 0), (1, 1, 0),
+                (-1, -1, 1), (0, -1, 1), (1, -1, 1),
+                (-1, 0, 1), (0, 0, 1), (1, 0, 1),
+                (-1, 1, 1), (0, 1, 1), (1, 1, 1),
+            ],
+            Self::VonNeumann => &[(0, 0, -1), (0, -1, 0), (-1, 0, 0), (1, 0, 0), (0, 1, 0), (0, 0, 1)],
+        }
+    }
+}
+
+/// Which part of a [`Grid`] stays put when [`Grid::resize`] grows or shrinks it: new
+/// columns and rows are added, or old ones cut, on the opposite sides.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    /// Grows or crops evenly on every side.
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// How many halves of the width and height added or removed go on the left and top.
+    fn halves(self) -> (isize, isize) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        }
+    }
+}
+
+/// What lies past the edges of a [`Grid`], for [`Grid::at`] and neighbor queries.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Boundary<T> {
+    /// Nothing: edge cells have fewer neighbors.
+    #[default]
+    Open,
+    /// The edge cells again, as if each edge were stretched outwards.
+    Clamp,
+    /// The opposite edge, making the grid a torus.
+    Wrap,
+    /// This value, everywhere off the grid: dead cells, a cold wall, empty water.
+    Constant(T),
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Grid<T> {
     width: usize,
     height: usize,
     cells: Vec<T>,
+    #[serde(default)]
+    boundary: Boundary<T>,
+    /// The generation [`step`](Self::step) is writing, kept to reuse its allocation.
+    #[serde(skip)]
+    back: Vec<T>,
 }
 
 impl<T: Clone + Default> Grid<T> {
@@ -14,13 +121,706 @@ impl<T: Clone + Default> Grid<T> {
             width,
             height,
             cells: vec![T::default(); width * height],
+            boundary: Boundary::default(),
+            back: Vec::new(),
+        }
+    }
+
+    /// Changes the grid to `width` × `height` keeping its cells where `anchor` says: growing
+    /// adds default cells around them, shrinking crops the ones that no longer fit.
+    ///
+    /// ```
+    /// use simulation_station::{Anchor, Grid};
+    ///
+    /// let mut grid = Grid::new(2, 2);
+    /// grid.set(1, 1, 7);
+    /// grid.resize(4, 4, Anchor::Center);
+    /// assert_eq!(*grid.get(2, 2), 7);
+    /// grid.resize(3, 2, Anchor::BottomRight);
+    /// assert_eq!(grid.cells(), [0, 7, 0, 0, 0, 0]);
+    /// ```
+    pub fn resize(&mut self, width: usize, height: usize, anchor: Anchor) {
+        let (halves_x, halves_y) = anchor.halves();
+        // Where the old cell (0, 0) lands, possibly off the new grid
+        let left = (width as isize - self.width as isize) * halves_x / 2;
+        let top = (height as isize - self.height as isize) * halves_y / 2;
+        let (x0, x1) = (left.max(0), (left + self.width as isize).min(width as isize));
+        let mut cells = vec![T::default(); width * height];
+        for y in 0..self.height as isize {
+            let new_y = y + top;
+            if x0 >= x1 || !(0..height as isize).contains(&new_y) {
+                continue;
+            }
+            let old = &self.cells[y as usize * self.width..][(x0 - left) as usize..(x1 - left) as usize];
+            cells[new_y as usize * width..][x0 as usize..x1 as usize].clone_from_slice(old);
+        }
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+        self.back = Vec::new();
+    }
+}
+
+impl<T> Grid<T> {
+    /// A grid the size of the PNG or JPEG at `path`, one cell per pixel, each made by
+    /// `pixel` from its RGBA color: walls from dark pixels, a seed from the red channel and
+    /// so on, so starting states can be drawn in any paint program.
+    pub fn from_image(path: impl AsRef<Path>, pixel: impl FnMut([u8; 4]) -> T) -> Result<Self, String> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(|e| format!("can't read {}: {e}", path.display()))?;
+        Self::from_image_bytes(&data, pixel)
+    }
+
+    /// [`from_image`](Self::from_image) for an image already in memory, e.g. one dropped
+    /// onto the App.
+    ///
+    /// ```
+    /// use simulation_station::Grid;
+    ///
+    /// let mut png = Vec::new();
+    /// let image = image::RgbImage::from_fn(3, 2, |x, y| image::Rgb([if x == y { 255 } else { 0 }; 3]));
+    /// image.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png).unwrap();
+    ///
+    /// let walls = Grid::from_image_bytes(&png, |[r, g, b, _]| r.max(g).max(b) > 127).unwrap();
+    /// assert_eq!((walls.width(), walls.height()), (3, 2));
+    /// assert_eq!(walls.cells(), [true, false, false, false, true, false]);
+    /// ```
+    pub fn from_image_bytes(data: &[u8], pixel: impl FnMut([u8; 4]) -> T) -> Result<Self, String> {
+        let image = image::load_from_memory(data).map_err(|e| e.to_string())?.to_rgba8();
+        Ok(Self {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            cells: image.pixels().map(|rgba| rgba.0).map(pixel).collect(),
+            boundary: Boundary::Open,
+            back: Vec::new(),
+        })
+    }
+}
+
+/// Leaves out `step`'s spare buffer, so snapshots cost one copy of the cells.
+impl<T: Clone> Clone for Grid<T> {
+    fn clone(&self) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.clone(),
+            boundary: self.boundary.clone(),
+            back: Vec::new(),
+        }
+    }
+}
+
+/// The cells around one cell of a [`Grid`], as [`Grid::step`] hands them to its rule.
+pub struct Neighbors<'a, T> {
+    grid: &'a Grid<T>,
+    x: usize,
+    y: usize,
+    neighborhood: Neighborhood,
+}
+
+impl<'a, T> Neighbors<'a, T> {
+    /// Where the cell these surround is.
+    pub fn position(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    /// The neighbors, in `Neighborhood::offsets` order, under the grid's boundary.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> + 'a {
+        self.grid.neighbors(self.x, self.y, self.neighborhood)
+    }
+
+    pub fn count_where(&self, pred: impl Fn(&T) -> bool) -> usize {
+        self.iter().filter(|cell| pred(cell)).count()
+    }
+
+    /// The cell `dx` across and `dy` down from this one, whether or not it's in the
+    /// neighborhood; see [`Grid::at`].
+    pub fn at(&self, dx: isize, dy: isize) -> Option<&'a T> {
+        self.grid.at(self.x as isize + dx, self.y as isize + dy)
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &T {
+        &self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: T) {
+        self.cells[y * self.width + x] = value;
+    }
+
+    /// Row-major cell storage.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn with_boundary(self, boundary: Boundary<T>) -> Self {
+        Self { boundary, ..self }
+    }
+
+    pub fn boundary(&self) -> &Boundary<T> {
+        &self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary<T>) {
+        self.boundary = boundary;
+    }
+
+    /// The cell that (x, y) lands on under the grid's boundary, which may be off the grid
+    /// by any amount; `None` past an open or constant edge, where there's no cell.
+    pub fn locate(&self, x: isize, y: isize) -> Option<(usize, usize)> {
+        let (w, h) = (self.width as isize, self.height as isize);
+        if w == 0 || h == 0 {
+            return None;
         }
+        let inside = (0..w).contains(&x) && (0..h).contains(&y);
+        match self.boundary {
+            Boundary::Open | Boundary::Constant(_) => inside.then_some((x as usize, y as usize)),
+            Boundary::Clamp => Some((x.clamp(0, w - 1) as usize, y.clamp(0, h - 1) as usize)),
+            Boundary::Wrap => Some((x.rem_euclid(w) as usize, y.rem_euclid(h) as usize)),
+        }
+    }
+
+    /// The value at (x, y) under the grid's boundary: a cell, the constant past a constant
+    /// edge, or `None` past an open one.
+    ///
+    /// ```
+    /// use simulation_station::{Boundary, Grid};
+    ///
+    /// let mut grid = Grid::new(4, 4).with_boundary(Boundary::Constant(-1));
+    /// grid.set(3, 0, 7);
+    /// assert_eq!(grid.at(-1, 0), Some(&-1));
+    /// grid.set_boundary(Boundary::Wrap);
+    /// assert_eq!(grid.at(-1, 0), Some(&7));
+    /// grid.set_boundary(Boundary::Open);
+    /// assert_eq!(grid.at(-1, 0), None);
+    /// ```
+    pub fn at(&self, x: isize, y: isize) -> Option<&T> {
+        match (self.locate(x, y), &self.boundary) {
+            (Some((x, y)), _) => Some(self.get(x, y)),
+            (None, Boundary::Constant(value)) => Some(value),
+            (None, _) => None,
+        }
+    }
+
+    /// Where the neighbors of (x, y) are, in `Neighborhood::offsets` order, leaving out
+    /// those past an open or constant edge. On a small wrapped or clamped grid the same
+    /// cell can come up more than once.
+    pub fn neighbor_positions(
+        &self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let (x, y) = (x as isize, y as isize);
+        neighborhood.offsets().iter().filter_map(move |&(dx, dy)| self.locate(x + dx, y + dy))
+    }
+
+    /// The neighbors of (x, y), in `Neighborhood::offsets` order, including the constant
+    /// for each one past a constant edge.
+    pub fn neighbors(
+        &self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = &T> + '_ {
+        let (x, y) = (x as isize, y as isize);
+        neighborhood.offsets().iter().filter_map(move |&(dx, dy)| self.at(x + dx, y + dy))
+    }
+
+    /// Draws the grid into a `width` × `height` RGB `buffer`, coloring each cell with
+    /// `color`. A grid smaller than the buffer is drawn in blocks and a larger one is
+    /// sampled, one cell per pixel; either way `color` runs at most once per pixel.
+    ///
+    /// ```
+    /// use simulation_station::Grid;
+    ///
+    /// let mut grid = Grid::new(2, 1);
+    /// grid.set(1, 0, true);
+    /// let mut buffer = [0; 4 * 2 * 3];
+    /// grid.render_into(&mut buffer, 4, 2, |&on| if on { [255; 3] } else { [0; 3] });
+    /// assert_eq!(buffer[..12], [0, 0, 0, 0, 0, 0, 255, 255, 255, 255, 255, 255]);
+    /// assert_eq!(buffer[..12], buffer[12..]);
+    /// ```
+    pub fn render_into(
+        &self,
+        buffer: &mut [u8],
+        width: usize,
+        height: usize,
+        color: impl Fn(&T) -> [u8; 3],
+    ) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let columns: Vec<usize> = (0..width).map(|x| x * self.width / width).collect();
+        for (y, row) in buffer.chunks_exact_mut(width * 3).take(height).enumerate() {
+            let cells = &self.cells[y * self.height / height * self.width..][..self.width];
+            for (pixel, &x) in row.chunks_exact_mut(3).zip(&columns) {
+                pixel.copy_from_slice(&color(&cells[x]));
+            }
+        }
+    }
+
+    /// Moves every cell on a generation at once: `rule` gets each cell and its neighbors as
+    /// they are now and returns the cell's next state, and the new generation replaces the
+    /// old only once every cell has been computed. The generation is built in a spare
+    /// buffer that's kept between steps, so stepping doesn't allocate.
+    ///
+    /// ```
+    /// use simulation_station::{Boundary, Grid, Neighborhood};
+    ///
+    /// // A blinker in the Game of Life flips between a row and a column
+    /// let mut life = Grid::new(5, 5).with_boundary(Boundary::Wrap);
+    /// for x in 1..4 {
+    ///     life.set(x, 2, true);
+    /// }
+    /// let rule = |&alive: &bool, neighbors: simulation_station::Neighbors<bool>| {
+    ///     matches!((alive, neighbors.count_where(|&n| n)), (true, 2) | (_, 3))
+    /// };
+    /// life.step(Neighborhood::Moore, rule);
+    /// assert!((1..4).all(|y| *life.get(2, y)) && !life.get(1, 2));
+    /// life.step(Neighborhood::Moore, rule);
+    /// assert!((1..4).all(|x| *life.get(x, 2)) && !life.get(2, 1));
+    /// ```
+    pub fn step(
+        &mut self,
+        neighborhood: Neighborhood,
+        mut rule: impl FnMut(&T, Neighbors<'_, T>) -> T,
+    ) {
+        let mut next = std::mem::take(&mut self.back);
+        next.clear();
+        let width = self.width;
+        next.extend(self.cells.iter().enumerate().map(|(i, cell)| {
+            let (x, y) = (i % width, i / width);
+            rule(cell, Neighbors { grid: self, x, y, neighborhood })
+        }));
+        self.back = std::mem::replace(&mut self.cells, next);
+    }
+
+    /// [`step`](Self::step) spread over rayon's current pool: the next generation is split
+    /// into bands of rows, each computed on whichever thread is free, reading only the
+    /// current generation. Gives the same result as `step` for any `rule`, which therefore
+    /// can't keep state between cells. Inside an AsyncSim, call it within
+    /// `WorkerContext::parallel` to run on the sim's pool.
+    #[cfg(feature = "parallel")]
+    pub fn par_step(
+        &mut self,
+        neighborhood: Neighborhood,
+        rule: impl Fn(&T, Neighbors<'_, T>) -> T + Sync,
+    ) where
+        T: Send + Sync + Clone,
+    {
+        use rayon::prelude::*;
+
+        if self.cells.is_empty() {
+            return;
+        }
+        let mut next = std::mem::take(&mut self.back);
+        // Every cell is overwritten below; the values only size the buffer
+        next.resize(self.cells.len(), self.cells[0].clone());
+        let width = self.width;
+        // A few bands per thread, so one slow band doesn't leave the others idle
+        let band_rows = self.height.div_ceil(rayon::current_num_threads() * 4).max(1);
+        let grid = &*self;
+        next.par_chunks_mut(band_rows * width).enumerate().for_each(|(band, cells)| {
+            for (i, cell) in cells.iter_mut().enumerate() {
+                let index = band * band_rows * width + i;
+                let (x, y) = (index % width, index / width);
+                *cell = rule(&grid.cells[index], Neighbors { grid, x, y, neighborhood });
+            }
+        });
+        self.back = std::mem::replace(&mut self.cells, next);
+    }
+
+    /// How many neighbors of (x, y) pass `pred`, e.g. live cells for the Game of Life.
+    ///
+    /// ```
+    /// use simulation_station::{Boundary, Grid, Neighborhood};
+    ///
+    /// let mut grid = Grid::new(3, 3);
+    /// grid.set(0, 0, true);
+    /// grid.set(2, 2, true);
+    /// assert_eq!(grid.count_neighbors_where(1, 1, Neighborhood::Moore, |&alive| alive), 2);
+    /// assert_eq!(grid.count_neighbors_where(0, 0, Neighborhood::Moore, |&alive| alive), 0);
+    ///
+    /// let torus = grid.with_boundary(Boundary::Wrap);
+    /// assert_eq!(torus.count_neighbors_where(0, 0, Neighborhood::Moore, |&alive| alive), 1);
+    ///
+    /// let walled = torus.with_boundary(Boundary::Constant(true));
+    /// assert_eq!(walled.count_neighbors_where(0, 0, Neighborhood::Moore, |&alive| alive), 5);
+    /// ```
+    pub fn count_neighbors_where(
+        &self,
+        x: usize,
+        y: usize,
+        neighborhood: Neighborhood,
+        pred: impl Fn(&T) -> bool,
+    ) -> usize {
+        self.neighbors(x, y, neighborhood).filter(|cell| pred(cell)).count()
+    }
+}
+
+/// The cells that differ between two same-sized grids, as runs of consecutive changed
+/// cells in row-major order. Made by [`Grid::diff`]; applying it to the first grid turns it
+/// into the second, so a worker can send a patch per step instead of a whole grid.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GridPatch<T> {
+    width: usize,
+    height: usize,
+    /// The index of each run's first cell and the run's new values.
+    runs: Vec<(usize, Vec<T>)>,
+}
+
+impl<T> GridPatch<T> {
+    /// How many cells the patch changes.
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|(_, values)| values.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// The changed cells' positions and new values, in row-major order.
+    pub fn changes(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.runs.iter().flat_map(move |(start, values)| {
+            values.iter().enumerate().map(move |(i, value)| {
+                let index = start + i;
+                ((index % self.width, index / self.width), value)
+            })
+        })
     }
 }
 
+/// Copying, pasting and turning blocks of cells, for editing tools such as
+/// [`GridSelection`](crate::GridSelection).
+impl<T: Clone> Grid<T> {
+    /// A grid of `width` × `height` cells taken row by row from `cells`.
+    fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Self {
+        Self { width, height, cells, boundary: Boundary::Open, back: Vec::new() }
+    }
+
+    /// A copy of the cells in `region`, cut down to the part on the grid.
+    pub fn copy_region(&self, region: Region) -> Grid<T> {
+        let columns = region.x.min(self.width)..(region.x + region.width).min(self.width);
+        let rows = region.y.min(self.height)..(region.y + region.height).min(self.height);
+        let row = |y: usize| self.cells[y * self.width..][columns.clone()].iter().cloned();
+        Grid::from_vec(columns.len(), rows.len(), rows.clone().flat_map(row).collect())
+    }
+
+    /// Writes `source` over this grid with its top-left cell at (x, y). Cells landing past
+    /// an edge wrap around a [`Boundary::Wrap`] grid and are dropped on any other.
+    ///
+    /// ```
+    /// use simulation_station::{Boundary, Grid, Region};
+    ///
+    /// let mut grid = Grid::new(4, 3);
+    /// grid.set(0, 0, 1);
+    /// grid.set(1, 0, 2);
+    /// let block = grid.copy_region(Region { x: 0, y: 0, width: 2, height: 1 });
+    /// grid.paste(&block.rotated(), 3, 1);
+    /// assert_eq!(grid.cells(), [1, 2, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2]);
+    ///
+    /// let mut torus = Grid::new(3, 1).with_boundary(Boundary::Wrap);
+    /// torus.paste(&block, 2, 0);
+    /// assert_eq!(torus.cells(), [2, 0, 1]);
+    /// ```
+    pub fn paste(&mut self, source: &Grid<T>, x: isize, y: isize) {
+        let wrap = matches!(self.boundary, Boundary::Wrap);
+        for (i, cell) in source.cells.iter().enumerate() {
+            let (cx, cy) = (x + (i % source.width) as isize, y + (i / source.width) as isize);
+            let inside = cx >= 0 && cy >= 0 && (cx as usize) < self.width && (cy as usize) < self.height;
+            if let Some((cx, cy)) = self.locate(cx, cy).filter(|_| inside || wrap) {
+                self.cells[cy * self.width + cx] = cell.clone();
+            }
+        }
+    }
+
+    /// Sets every cell in `region` on the grid to `value`.
+    pub fn fill_region(&mut self, region: Region, value: T) {
+        for y in region.rows().take_while(|&y| y < self.height) {
+            let columns = region.x.min(self.width)..(region.x + region.width).min(self.width);
+            self.cells[y * self.width..][columns].fill(value.clone());
+        }
+    }
+
+    /// The grid turned a quarter turn clockwise, so a `width` × `height` grid comes back
+    /// `height` × `width`.
+    pub fn rotated(&self) -> Grid<T> {
+        let (w, h) = (self.height, self.width);
+        let cells = (0..w * h).map(|i| self.get(i / w, self.height - 1 - i % w).clone()).collect();
+        Grid::from_vec(w, h, cells)
+    }
+
+    /// The grid mirrored left to right.
+    pub fn mirrored(&self) -> Grid<T> {
+        let cells = self.cells.chunks(self.width.max(1)).flat_map(|row| row.iter().rev().cloned());
+        Grid::from_vec(self.width, self.height, cells.collect())
+    }
+
+    /// The grid flipped top to bottom.
+    pub fn flipped(&self) -> Grid<T> {
+        let cells = self.cells.rchunks(self.width.max(1)).flat_map(|row| row.iter().cloned());
+        Grid::from_vec(self.width, self.height, cells.collect())
+    }
+}
+
+impl<T: Clone + PartialEq> Grid<T> {
+    /// The patch that turns this grid into `other`, holding only the cells that differ.
+    /// Panics if the grids aren't the same size.
+    ///
+    /// ```
+    /// use simulation_station::Grid;
+    ///
+    /// let before = Grid::<u8>::new(100, 100);
+    /// let mut after = before.clone();
+    /// after.set(10, 5, 3);
+    /// after.set(11, 5, 4);
+    /// after.set(0, 99, 1);
+    ///
+    /// let patch = before.diff(&after);
+    /// assert_eq!(patch.len(), 3);
+    /// let mut copy = before.clone();
+    /// copy.apply(&patch);
+    /// assert_eq!(copy.cells(), after.cells());
+    /// ```
+    pub fn diff(&self, other: &Grid<T>) -> GridPatch<T> {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "can't diff grids of different sizes"
+        );
+        let mut runs: Vec<(usize, Vec<T>)> = Vec::new();
+        for (i, (old, new)) in self.cells.iter().zip(&other.cells).enumerate() {
+            if old == new {
+                continue;
+            }
+            match runs.last_mut() {
+                Some((start, values)) if *start + values.len() == i => values.push(new.clone()),
+                _ => runs.push((i, vec![new.clone()])),
+            }
+        }
+        GridPatch { width: self.width, height: self.height, runs }
+    }
+
+    /// Writes `patch`'s cells into this grid. Panics if the patch was made for a grid of
+    /// another size.
+    pub fn apply(&mut self, patch: &GridPatch<T>) {
+        assert_eq!(
+            (self.width, self.height),
+            (patch.width, patch.height),
+            "patch is for a grid of another size"
+        );
+        for (start, values) in &patch.runs {
+            self.cells[*start..*start + values.len()].clone_from_slice(values);
+        }
+    }
+}
+
+/// Lets an AsyncSim whose state is a grid send `WorkerContext::send_delta(before.diff(&after))`
+/// in place of the whole grid.
+impl<T: Clone + PartialEq + Send + 'static> StateDelta<Grid<T>> for GridPatch<T> {
+    fn apply(self, grid: &mut Grid<T>) {
+        assert_eq!(
+            (grid.width, grid.height),
+            (self.width, self.height),
+            "patch is for a grid of another size"
+        );
+        for (start, values) in self.runs {
+            grid.cells.splice(start..start + values.len(), values);
+        }
+    }
+}
+
+/// A `width` × `height` × `depth` volume of cells, stored x-fastest, then y, then z.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Grid3<T> {
+    width: usize,
+    height: usize,
+    depth: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone + Default> Grid3<T> {
+    pub fn new(width: usize, height: usize, depth: usize) -> Self {
+        Self {
+            width,
+            height,
+            depth,
+            cells: vec![T::default(); width * height * depth],
+        }
+    }
+
+    /// A copy of the layer of cells `index` along `axis`, laid out the way a
+    /// [`VolumeView`](crate::VolumeView) shows it: x-y along Z, x-z along Y (z down) and
+    /// z-y along X (z across). Panics if `index` is past the volume.
+    ///
+    /// ```
+    /// use simulation_station::{Axis, Grid3};
+    ///
+    /// let mut grid = Grid3::new(4, 3, 2);
+    /// grid.set(3, 2, 1, 9);
+    /// assert_eq!(*grid.slice(Axis::Z, 1).get(3, 2), 9);
+    /// assert_eq!(*grid.slice(Axis::Y, 2).get(3, 1), 9);
+    /// let side = grid.slice(Axis::X, 3);
+    /// assert_eq!((side.width(), side.height(), *side.get(1, 2)), (2, 3, 9));
+    /// ```
+    pub fn slice(&self, axis: Axis, index: usize) -> Grid<T> {
+        let (w, h, depth) = match axis {
+            Axis::X => (self.depth, self.height, self.width),
+            Axis::Y => (self.width, self.depth, self.height),
+            Axis::Z => (self.width, self.height, self.depth),
+        };
+        assert!(index < depth, "slice {index} is past the volume's {depth} along {axis:?}");
+        let mut slice = Grid::new(w, h);
+        match axis {
+            // A z layer is already contiguous
+            Axis::Z => slice.cells.clone_from_slice(&self.cells[index * w * h..][..w * h]),
+            Axis::Y => {
+                for (z, row) in slice.cells.chunks_exact_mut(w).enumerate() {
+                    row.clone_from_slice(&self.cells[self.index(0, index, z)..][..w]);
+                }
+            }
+            Axis::X => {
+                for (i, cell) in slice.cells.iter_mut().enumerate() {
+                    *cell = self.get(index, i / w, i % w).clone();
+                }
+            }
+        }
+        slice
+    }
+}
+
+impl<T> Grid3<T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.height + y) * self.width + x
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> &T {
+        &self.cells[self.index(x, y, z)]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, value: T) {
+        let index = self.index(x, y, z);
+        self.cells[index] = value;
+    }
+
+    /// (x, y, z) as a cell position, or `None` off the volume; it has no boundary modes.
+    pub fn locate(&self, x: isize, y: isize, z: isize) -> Option<(usize, usize, usize)> {
+        let inside = (0..self.width as isize).contains(&x)
+            && (0..self.height as isize).contains(&y)
+            && (0..self.depth as isize).contains(&z);
+        inside.then_some((x as usize, y as usize, z as usize))
+    }
+
+    /// Where the neighbors of (x, y, z) inside the volume are, in
+    /// `Neighborhood::offsets_3d` order: up to 26 for Moore, 6 for von Neumann.
+    pub fn neighbor_positions(
+        &self,
+        x: usize,
+        y: usize,
+        z: usize,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        let (x, y, z) = (x as isize, y as isize, z as isize);
+        let offsets = neighborhood.offsets_3d().iter();
+        offsets.filter_map(move |&(dx, dy, dz)| self.locate(x + dx, y + dy, z + dz))
+    }
+
+    /// The neighbors of (x, y, z) inside the volume; cells on a face have fewer.
+    pub fn neighbors(
+        &self,
+        x: usize,
+        y: usize,
+        z: usize,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = &T> + '_ {
+        self.neighbor_positions(x, y, z, neighborhood).map(|(x, y, z)| self.get(x, y, z))
+    }
+
+    /// How many neighbors of (x, y, z) pass `pred`.
+    ///
+    /// ```
+    /// use simulation_station::{Grid3, Neighborhood};
+    ///
+    /// let mut grid = Grid3::new(3, 3, 3);
+    /// grid.set(0, 0, 0, true);
+    /// grid.set(1, 1, 0, true);
+    /// assert_eq!(grid.count_neighbors_where(1, 1, 1, Neighborhood::Moore, |&on| on), 2);
+    /// assert_eq!(grid.count_neighbors_where(1, 1, 1, Neighborhood::VonNeumann, |&on| on), 1);
+    /// assert_eq!(grid.neighbors(0, 0, 0, Neighborhood::Moore).count(), 7);
+    /// ```
+    pub fn count_neighbors_where(
+        &self,
+        x: usize,
+        y: usize,
+        z: usize,
+        neighborhood: Neighborhood,
+        pred: impl Fn(&T) -> bool,
+    ) -> usize {
+        self.neighbors(x, y, z, neighborhood).filter(|cell| pred(cell)).count()
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+}
+
+sim_params! {
+    pub struct PixelFillParams {
+        pub pixels_per_step: usize = 1 => 1..=1000,
+        pub random_order: bool = false,
+        pub fill_color: [u8; 3] = [0, 255, 255],
+        pub empty_color: [u8; 3] = [20, 20, 20],
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct PixelFillSim {
     grid: Grid<u8>,
     cursor_idx: usize,
+    params: PixelFillParams,
+    seed: u64,
+    /// Cell indices in fill order; rebuilt from `seed` rather than serialized.
+    #[serde(skip)]
+    order: Vec<usize>,
+    /// The `random_order` that `order` was built for, to notice when the param changes.
+    #[serde(skip)]
+    shuffled: bool,
+    #[serde(skip)]
+    version: u64,
+    #[serde(skip)]
+    selection: GridSelection<u8>,
+    #[serde(skip, default = "PixelFillSim::brush")]
+    brush: Brush<u8>,
 }
 
 impl PixelFillSim {
@@ -28,10 +828,31 @@ impl PixelFillSim {
         let mut sim = Self {
             grid: Grid::new(400, 300),
             cursor_idx: 0,
+            params: PixelFillParams::default(),
+            seed: 0,
+            order: Vec::new(),
+            shuffled: false,
+            version: 0,
+            selection: GridSelection::default(),
+            brush: Self::brush(),
         };
-        sim.reset();
+        sim.reset(0);
         sim
     }
+
+    fn brush() -> Brush<u8> {
+        Brush::new(255, 0)
+    }
+
+    /// Rebuilds `order` for the grid's size and the current `random_order`. Cells already
+    /// filled stay filled; the cursor carries on through the new order.
+    fn rebuild_order(&mut self) {
+        self.order = (0..self.grid.cells.len()).collect();
+        self.shuffled = self.params.random_order;
+        if self.shuffled {
+            self.order.shuffle(&mut StdRng::seed_from_u64(self.seed));
+        }
+    }
 }
 
 impl Simulation for PixelFillSim {
@@ -39,25 +860,113 @@ impl Simulation for PixelFillSim {
         "Simple Pixel Fill"
     }
 
-    fn reset(&mut self) {
+    fn info(&self) -> SimInfo {
+        INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        let [r, g, b] = self.params.fill_color;
+        let [er, eg, eb] = self.params.empty_color;
+        SimHelp {
+            overview: "Each step fills the next batch of cells, row by row or in a shuffled order \
+                       drawn from the seed, until the whole grid is filled.",
+            legend: vec![
+                (egui::Color32::from_rgb(r, g, b), "Filled cell"),
+                (egui::Color32::from_rgb(er, eg, eb), "Empty cell"),
+                (Theme::current().accent, "Filled next, in the \"Up next\" layer"),
+            ],
+            params: vec![
+                ("pixels_per_step", "Cells filled by each step"),
+                ("random_order", "Fill in a shuffled order instead of row by row"),
+                ("fill_color", "Color of filled cells"),
+                ("empty_color", "Color of cells still waiting"),
+            ],
+            controls: vec![
+                ("Left click / drag", "Fill the cells under the brush"),
+                ("Right click / drag", "Empty the cells under the brush"),
+                ("⬚ Select, then drag", "Select cells to copy (C), cut (X) or clear (Delete)"),
+                ("📋 Paste, then click", "Paste the copied cells; R, M and F turn them first"),
+                ("Drop a PNG or JPEG", "Fill the cells under its light pixels"),
+                ("Drop a .rle / .cells", "Replace the grid with a Life pattern, centered"),
+            ],
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            speed_unit: "fills/sec",
+            speed_curve: SpeedCurve::Steps(&[1.0, 10.0, 60.0, 250.0, 1000.0, 5000.0]),
+            ..SimConfig::default()
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::INPUT
+            | Capabilities::SNAPSHOTS
+            | Capabilities::EXPORT
+            | Capabilities::RESIZE
+            | Capabilities::CULLING
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.grid.width, self.grid.height)
+    }
+
+    fn resize(&mut self, width: usize, height: usize) -> Result<(), SimError> {
+        if width == 0 || height == 0 {
+            return Err(SimError::State(format!("can't fill a {width}×{height} grid")));
+        }
+        self.grid.resize(width, height, Anchor::Center);
+        self.reset(self.seed);
+        Ok(())
+    }
+
+    fn reset(&mut self, seed: u64) {
         self.grid.cells.fill(0);
         self.cursor_idx = 0;
+        self.seed = seed;
+        self.rebuild_order();
+        self.version += 1;
     }
 
-    fn update(&mut self) {
-        if self.cursor_idx < self.grid.cells.len() {
-            self.grid.cells[self.cursor_idx] = 255;
-            self.cursor_idx += 1;
+    fn update(&mut self) -> Result<(), SimError> {
+        if self.shuffled != self.params.random_order {
+            self.rebuild_order();
         }
+        let end = (self.cursor_idx + self.params.pixels_per_step).min(self.order.len());
+        for &idx in &self.order[self.cursor_idx..end] {
+            self.grid.cells[idx] = 255;
+        }
+        self.cursor_idx = end;
+        self.version += 1;
+        Ok(())
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.version)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.cursor_idx >= self.grid.cells.len()
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        let (fill, empty) = (self.params.fill_color, self.params.empty_color);
+        let (w, h) = self.dimensions();
+        self.grid.render_into(buffer, w, h, |&v| if v > 0 { fill } else { empty });
     }
 
-    fn render(&self, buffer: &mut Vec<u8>) {
-        buffer.clear();
-        for &val in &self.grid.cells {
-            if val > 0 {
-                buffer.extend_from_slice(&[0, 255, 255]);
-            } else {
-                buffer.extend_from_slice(&[20, 20, 20]);
+    fn render_region(&self, visible: Region, buffer: &mut [u8]) {
+        let w = self.grid.width;
+        for y in visible.rows() {
+            let cells = &self.grid.cells[y * w + visible.x..y * w + visible.x + visible.width];
+            let pixels = &mut buffer[(y * w + visible.x) * 3..(y * w + visible.x + visible.width) * 3];
+            for (pixel, &val) in pixels.chunks_exact_mut(3).zip(cells) {
+                if val > 0 {
+                    pixel.copy_from_slice(&self.params.fill_color);
+                } else {
+                    pixel.copy_from_slice(&self.params.empty_color);
+                }
             }
         }
     }
@@ -68,9 +977,222 @@ impl Simulation for PixelFillSim {
 
         if ui.button("Fill 1000x").clicked() {
             for _ in 0..1000 {
-                self.update();
+                let _ = self.update();
+            }
+        }
+        ui.separator();
+        self.brush.ui(ui);
+        if self.selection.ui(ui, &mut self.grid) {
+            self.version += 1;
+        }
+        let copy = ui.button("📋 Copy as RLE");
+        if copy.on_hover_text("Copy the filled cells as a Golly .rle pattern").clicked() {
+            let mut pattern = Grid::new(self.grid.width, self.grid.height);
+            pattern.cells_mut().iter_mut().zip(&self.grid.cells).for_each(|(alive, &v)| *alive = v > 0);
+            ui.ctx().copy_text(pattern.to_rle("B3/S23"));
+        }
+    }
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+
+    fn record_metrics(&self, metrics: &mut Metrics) {
+        metrics.record("filled %", 100.0 * self.cursor_idx as f64 / self.grid.cells.len().max(1) as f64);
+    }
+
+    fn layers(&self) -> Vec<LayerInfo> {
+        vec![LayerInfo { name: "Up next".to_owned(), visible: false }]
+    }
+
+    /// Marks the cells the next step will fill.
+    fn render_layer(&self, _index: usize, buffer: &mut [u8]) {
+        let end = (self.cursor_idx + self.params.pixels_per_step.max(1)).min(self.order.len());
+        let [r, g, b] = Theme::rgb(Theme::current().accent);
+        for &idx in &self.order[self.cursor_idx..end] {
+            buffer[idx * 4..idx * 4 + 4].copy_from_slice(&[r, g, b, 255]);
+        }
+    }
+
+    fn probe(&self, x: usize, y: usize) -> Option<String> {
+        let filled = self.grid.cells[y * self.grid.width + x] > 0;
+        Some(if filled { "filled" } else { "empty" }.to_owned())
+    }
+
+    fn file_formats(&self) -> &'static [&'static str] {
+        &["png", "jpg", "jpeg", "rle", "cells"]
+    }
+
+    /// Fills the cells under the image's light pixels, with the image stretched to the grid,
+    /// or the live cells of a Life pattern, centered and cropped to the grid.
+    fn load_file(&mut self, name: &str, data: &[u8]) -> Result<(), SimError> {
+        let extension = name.rsplit('.').next().unwrap_or_default().to_lowercase();
+        if extension == "rle" || extension == "cells" {
+            let text = String::from_utf8_lossy(data);
+            let mut pattern: Grid<bool> = match extension.as_str() {
+                "rle" => Grid::from_rle(&text),
+                _ => Grid::from_cells(&text),
             }
+            .map_err(SimError::State)?;
+            pattern.resize(self.grid.width, self.grid.height, Anchor::Center);
+            for (cell, &alive) in self.grid.cells.iter_mut().zip(pattern.cells()) {
+                *cell = if alive { 255 } else { 0 };
+            }
+            self.version += 1;
+            return Ok(());
+        }
+        let image = image::load_from_memory(data).map_err(|e| SimError::State(e.to_string()))?;
+        let (w, h) = (self.grid.width as u32, self.grid.height as u32);
+        let filter = image::imageops::FilterType::Nearest;
+        let luma = image::imageops::resize(&image.to_luma8(), w, h, filter);
+        for (cell, pixel) in self.grid.cells.iter_mut().zip(luma.pixels()) {
+            *cell = if pixel.0[0] > 127 { 255 } else { 0 };
+        }
+        self.version += 1;
+        Ok(())
+    }
+
+    fn annotate(&self, annotations: &mut Annotations) {
+        self.selection.annotate(annotations);
+    }
+
+    fn handle_input(&mut self, event: SimInput) {
+        if self.selection.handle_input(&mut self.grid, event) || self.brush.paint(&mut self.grid, event) {
+            self.version += 1;
         }
     }
+
+    fn save_state(&self) -> Option<Vec<u8>> {
+        serde_json::to_vec(self).ok()
+    }
+
+    fn load_state(&mut self, data: &[u8]) -> Result<(), SimError> {
+        let version = self.version;
+        *self = serde_json::from_slice(data).map_err(|e| SimError::State(e.to_string()))?;
+        self.rebuild_order();
+        self.version = version + 1;
+        Ok(())
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_fill_follows_random_order_changes() {
+        let mut sim = PixelFillSim::new();
+        sim.update().unwrap();
+        assert_eq!(sim.grid.cells[0], 255);
+        sim.params.random_order = true;
+        sim.update().unwrap();
+        let mut shuffled: Vec<usize> = (0..sim.grid.cells.len()).collect();
+        shuffled.shuffle(&mut StdRng::seed_from_u64(sim.seed));
+        assert_eq!(sim.order, shuffled);
+        assert_eq!(sim.grid.cells[shuffled[1]], 255);
+    }
+
+    /// A `width` × `height` grid numbered 1, 2, 3... row by row.
+    fn numbered(width: usize, height: usize) -> Grid<u32> {
+        let mut grid = Grid::new(width, height);
+        grid.cells_mut().iter_mut().zip(1..).for_each(|(cell, n)| *cell = n);
+        grid
+    }
+
+    #[test]
+    fn diff_apply_round_trips() {
+        let before = numbered(7, 5);
+        let mut after = before.clone();
+        for (x, y) in [(0, 0), (6, 0), (0, 1), (3, 2), (4, 2), (6, 4)] {
+            after.set(x, y, 0);
+        }
+        let patch = before.diff(&after);
+        assert_eq!(patch.len(), 6);
+        // (6, 0) and (0, 1) are neighbors in row-major order, as are (3, 2) and (4, 2)
+        assert_eq!(patch.runs.len(), 4);
+        let changed: Vec<_> = patch.changes().map(|(at, _)| at).collect();
+        assert_eq!(changed, [(0, 0), (6, 0), (0, 1), (3, 2), (4, 2), (6, 4)]);
+        let mut copy = before.clone();
+        copy.apply(&patch);
+        assert_eq!(copy.cells(), after.cells());
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "different sizes")]
+    fn diff_rejects_other_sizes() {
+        numbered(3, 2).diff(&numbered(2, 3));
+    }
+
+    #[test]
+    fn resize_keeps_cells_at_anchor() {
+        let mut grid = numbered(3, 2);
+        grid.resize(5, 4, Anchor::TopLeft);
+        assert_eq!(grid.cells(), [1, 2, 3, 0, 0, 4, 5, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut grid = numbered(3, 2);
+        grid.resize(5, 4, Anchor::BottomRight);
+        assert_eq!(grid.cells(), [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 0, 0, 4, 5, 6]);
+
+        let mut grid = numbered(3, 2);
+        grid.resize(5, 4, Anchor::Center);
+        assert_eq!((*grid.get(1, 1), *grid.get(3, 2)), (1, 6));
+        assert_eq!(grid.cells().iter().filter(|&&cell| cell > 0).count(), 6);
+    }
+
+    #[test]
+    fn resize_crops_and_round_trips() {
+        let mut grid = numbered(4, 4);
+        grid.resize(2, 2, Anchor::Center);
+        assert_eq!(grid.cells(), [6, 7, 10, 11]);
+
+        let mut grid = numbered(4, 4);
+        grid.resize(2, 3, Anchor::Right);
+        assert_eq!(grid.cells(), [3, 4, 7, 8, 11, 12]);
+
+        // Growing then shrinking about the same anchor gives the grid back
+        let mut grid = numbered(5, 3);
+        grid.resize(9, 8, Anchor::Bottom);
+        grid.resize(5, 3, Anchor::Bottom);
+        assert_eq!(grid.cells(), numbered(5, 3).cells());
+
+        grid.resize(0, 3, Anchor::Center);
+        assert!(grid.cells().is_empty());
+        grid.resize(2, 2, Anchor::Center);
+        assert_eq!(grid.cells(), [0; 4]);
+    }
+
+    #[test]
+    fn turns_round_trip() {
+        let grid = numbered(3, 2);
+        let turned = grid.rotated();
+        assert_eq!((turned.width(), turned.height()), (2, 3));
+        assert_eq!(turned.cells(), [4, 1, 5, 2, 6, 3]);
+        assert_eq!(turned.rotated().rotated().rotated().cells(), grid.cells());
+        assert_eq!(grid.mirrored().cells(), [3, 2, 1, 6, 5, 4]);
+        assert_eq!(grid.flipped().cells(), [4, 5, 6, 1, 2, 3]);
+        // A half turn is a mirror and a flip
+        assert_eq!(grid.rotated().rotated().cells(), grid.mirrored().flipped().cells());
+    }
+
+    #[test]
+    fn copy_and_paste() {
+        let grid = numbered(4, 3);
+        let block = grid.copy_region(Region { x: 2, y: 1, width: 5, height: 5 });
+        assert_eq!((block.width(), block.height()), (2, 2));
+        assert_eq!(block.cells(), [7, 8, 11, 12]);
+
+        let mut open = Grid::new(3, 3);
+        open.paste(&block, -1, 2);
+        assert_eq!(open.cells(), [0, 0, 0, 0, 0, 0, 8, 0, 0]);
+
+        let mut torus = Grid::new(3, 3).with_boundary(Boundary::Wrap);
+        torus.paste(&block, -1, 2);
+        assert_eq!(torus.cells(), [12, 0, 11, 0, 0, 0, 8, 0, 7]);
+
+        let mut back = Grid::new(4, 3);
+        back.paste(&grid.copy_region(Region { x: 0, y: 0, width: 4, height: 3 }), 0, 0);
+        assert_eq!(back.cells(), grid.cells());
+    }
+}
@@ -0,0 +1,322 @@
+use eframe::egui;
+use std::collections::HashMap;
+use crate::{Category, Neighborhood, Region, SimConfig, SimError, SimHelp, SimInfo, Simulation};
+
+pub const LANGTON_INFO: SimInfo = SimInfo {
+    description: "Langton's ant: one ant flipping cells on an endless plane, which after ten \
+                  thousand steps of chaos builds a highway and leaves for good.",
+    category: Category::CellularAutomata,
+    tags: &["grid", "turmite", "unbounded"],
+};
+
+/// Cells per side of a [`SparseGrid`] chunk.
+const CHUNK: i64 = 64;
+
+/// A rectangle of cells on an unbounded grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub x: i64,
+    pub y: i64,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl Bounds {
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        (self.x..self.x + self.width as i64).contains(&x)
+            && (self.y..self.y + self.height as i64).contains(&y)
+    }
+
+    /// The part of these bounds inside a `dims` world whose cell (0, 0) is cell `origin`
+    /// here, in that world's cells; `None` if none of it is.
+    pub fn to_region(&self, origin: (i64, i64), dims: (usize, usize)) -> Option<Region> {
+        let (x0, y0) = ((self.x - origin.0).max(0), (self.y - origin.1).max(0));
+        let x1 = (self.x + self.width as i64 - origin.0).min(dims.0 as i64);
+        let y1 = (self.y + self.height as i64 - origin.1).min(dims.1 as i64);
+        (x0 < x1 && y0 < y1).then(|| Region {
+            x: x0 as usize,
+            y: y0 as usize,
+            width: (x1 - x0) as usize,
+            height: (y1 - y0) as usize,
+        })
+    }
+
+    /// Slides a `dims` window over the plane, whose top-left corner is cell `origin`, so
+    /// these bounds stay in view: once they come within `margin` cells of an edge or past
+    /// it, the window is centered on them. Returns whether it moved.
+    ///
+    /// ```
+    /// use simulation_station::Bounds;
+    ///
+    /// let mut origin = (0, 0);
+    /// let ant = Bounds { x: 50, y: 10, width: 1, height: 1 };
+    /// assert!(!ant.keep_in_view(&mut origin, (100, 100), 8));
+    /// let ant = Bounds { x: 95, y: 10, width: 1, height: 1 };
+    /// assert!(ant.keep_in_view(&mut origin, (100, 100), 8));
+    /// assert_eq!(origin, (45, -40));
+    /// ```
+    pub fn keep_in_view(&self, origin: &mut (i64, i64), dims: (usize, usize), margin: i64) -> bool {
+        let fits = |start: i64, size: u64, origin: i64, span: usize| {
+            start - margin >= origin && start + size as i64 + margin <= origin + span as i64
+        };
+        if fits(self.x, self.width, origin.0, dims.0) && fits(self.y, self.height, origin.1, dims.1) {
+            return false;
+        }
+        let centered = |start: i64, size: u64, span: usize| start + size as i64 / 2 - span as i64 / 2;
+        let moved = (centered(self.x, self.width, dims.0), centered(self.y, self.height, dims.1));
+        std::mem::replace(origin, moved) != moved
+    }
+}
+
+/// A grid without edges, for patterns that wander off (ants, gliders). Cells live in
+/// 64 × 64 chunks allocated the first time one of their cells is written; every other cell
+/// reads as the grid's `empty` value.
+///
+/// ```
+/// use simulation_station::SparseGrid;
+///
+/// let mut grid = SparseGrid::new();
+/// grid.set(-1_000_000, 5, true);
+/// grid.set(20, -3, true);
+/// assert!(*grid.get(20, -3) && !*grid.get(21, -3));
+/// let bounds = grid.bounding_box().unwrap();
+/// assert_eq!((bounds.x, bounds.y, bounds.width, bounds.height), (-1_000_000, -3, 1_000_021, 9));
+/// assert_eq!(grid.chunk_count(), 2);
+/// ```
+#[derive(Clone)]
+pub struct SparseGrid<T> {
+    chunks: HashMap<(i64, i64), Vec<T>>,
+    empty: T,
+}
+
+impl<T: Clone + Default> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self::filled(T::default())
+    }
+}
+
+impl<T: Clone + Default> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// A grid reading `empty` everywhere.
+    pub fn filled(empty: T) -> Self {
+        Self { chunks: HashMap::new(), empty }
+    }
+
+    /// The chunk holding (x, y) and the cell's index in it.
+    fn split(x: i64, y: i64) -> ((i64, i64), usize) {
+        let chunk = (x.div_euclid(CHUNK), y.div_euclid(CHUNK));
+        (chunk, (y.rem_euclid(CHUNK) * CHUNK + x.rem_euclid(CHUNK)) as usize)
+    }
+
+    pub fn empty(&self) -> &T {
+        &self.empty
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> &T {
+        let (chunk, index) = Self::split(x, y);
+        self.chunks.get(&chunk).map_or(&self.empty, |cells| &cells[index])
+    }
+
+    /// Allocates the cell's chunk if it has none yet.
+    pub fn get_mut(&mut self, x: i64, y: i64) -> &mut T {
+        let (chunk, index) = Self::split(x, y);
+        let empty = &self.empty;
+        let size = (CHUNK * CHUNK) as usize;
+        &mut self.chunks.entry(chunk).or_insert_with(|| vec![empty.clone(); size])[index]
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, value: T) {
+        *self.get_mut(x, y) = value;
+    }
+
+    /// Empties every cell and frees all chunks.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// The neighbors of (x, y), in `Neighborhood::offsets` order.
+    pub fn neighbors(
+        &self,
+        x: i64,
+        y: i64,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = &T> + '_ {
+        neighborhood.offsets().iter().map(move |&(dx, dy)| self.get(x + dx as i64, y + dy as i64))
+    }
+
+    pub fn count_neighbors_where(
+        &self,
+        x: i64,
+        y: i64,
+        neighborhood: Neighborhood,
+        pred: impl Fn(&T) -> bool,
+    ) -> usize {
+        self.neighbors(x, y, neighborhood).filter(|cell| pred(cell)).count()
+    }
+}
+
+impl<T: Clone + PartialEq> SparseGrid<T> {
+    /// Every cell that isn't empty, with its position, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((i64, i64), &T)> + '_ {
+        self.chunks.iter().flat_map(move |(&(cx, cy), cells)| {
+            cells.iter().enumerate().filter(move |&(_, cell)| *cell != self.empty).map(move |(i, cell)| {
+                let (x, y) = (i as i64 % CHUNK, i as i64 / CHUNK);
+                ((cx * CHUNK + x, cy * CHUNK + y), cell)
+            })
+        })
+    }
+
+    /// The smallest rectangle holding every cell that isn't empty, or `None` if they all
+    /// are. Scans every allocated chunk, so call it once a frame rather than per cell.
+    pub fn bounding_box(&self) -> Option<Bounds> {
+        let (mut min, mut max) = ((i64::MAX, i64::MAX), (i64::MIN, i64::MIN));
+        for ((x, y), _) in self.iter() {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        (min.0 <= max.0).then(|| Bounds {
+            x: min.0,
+            y: min.1,
+            width: (max.0 - min.0 + 1) as u64,
+            height: (max.1 - min.1 + 1) as u64,
+        })
+    }
+
+    /// Frees chunks whose cells are all empty again.
+    pub fn prune(&mut self) {
+        let empty = &self.empty;
+        self.chunks.retain(|_, cells| cells.iter().any(|cell| cell != empty));
+    }
+}
+
+/// Langton's ant on a SparseGrid, seen through a window that starts centered on where it
+/// started and slides along to keep the ant in view. The ant's trail is kept wherever it
+/// goes, shown whenever the window passes over it.
+pub struct LangtonSim {
+    /// True for black cells.
+    cells: SparseGrid<bool>,
+    ant: (i64, i64),
+    /// 0 is up, counting clockwise.
+    heading: u8,
+    steps: u64,
+    /// The plane cell shown at the window's top-left corner.
+    origin: (i64, i64),
+}
+
+impl LangtonSim {
+    const WIDTH: usize = 400;
+    const HEIGHT: usize = 300;
+    /// The window's `origin` to begin with, centered on the ant.
+    const ORIGIN: (i64, i64) = (-(Self::WIDTH as i64) / 2, -(Self::HEIGHT as i64) / 2);
+
+    pub fn new() -> Self {
+        Self { cells: SparseGrid::new(), ant: (0, 0), heading: 0, steps: 0, origin: Self::ORIGIN }
+    }
+
+    /// The ant and the cells around it, which the window keeps in view.
+    fn surroundings(&self) -> Bounds {
+        Bounds { x: self.ant.0 - 8, y: self.ant.1 - 8, width: 17, height: 17 }
+    }
+}
+
+impl Simulation for LangtonSim {
+    fn name(&self) -> &str {
+        "Langton's Ant"
+    }
+
+    fn info(&self) -> SimInfo {
+        LANGTON_INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "The ant turns right on a white cell and left on a black one, flips the \
+                       cell's color and steps forward. After about 10,000 steps of apparent \
+                       chaos it settles into a highway repeating every 104 steps and marches \
+                       off for good; the plane is unbounded, so it keeps going past the edge \
+                       of the view. Follow (🎯) keeps the camera on the ant.",
+            legend: vec![
+                (egui::Color32::from_rgb(30, 30, 40), "Black cell"),
+                (egui::Color32::from_rgb(230, 230, 220), "White cell"),
+                (egui::Color32::RED, "Ant"),
+            ],
+            params: Vec::new(),
+            controls: Vec::new(),
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            max_speed: 20_000.0,
+            default_speed: 500.0,
+            speed_unit: "steps/sec",
+            ..SimConfig::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (Self::WIDTH, Self::HEIGHT)
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        let cell = self.cells.get_mut(self.ant.0, self.ant.1);
+        self.heading = if *cell { (self.heading + 3) % 4 } else { (self.heading + 1) % 4 };
+        *cell = !*cell;
+        let (dx, dy) = [(0, -1), (1, 0), (0, 1), (-1, 0)][self.heading as usize];
+        self.ant = (self.ant.0 + dx, self.ant.1 + dy);
+        self.steps += 1;
+        let dims = self.dimensions();
+        self.surroundings().keep_in_view(&mut self.origin, dims, 0);
+        Ok(())
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.steps)
+    }
+
+    fn render(&self, buffer: &mut [u8]) {
+        for pixel in buffer.chunks_exact_mut(3) {
+            pixel.copy_from_slice(&[230, 230, 220]);
+        }
+        let mut paint = |(x, y): (i64, i64), color: [u8; 3]| {
+            let (x, y) = (x - self.origin.0, y - self.origin.1);
+            if (0..Self::WIDTH as i64).contains(&x) && (0..Self::HEIGHT as i64).contains(&y) {
+                let i = (y as usize * Self::WIDTH + x as usize) * 3;
+                buffer[i..i + 3].copy_from_slice(&color);
+            }
+        };
+        for (pos, _) in self.cells.iter() {
+            paint(pos, [30, 30, 40]);
+        }
+        paint(self.ant, [255, 0, 0]);
+    }
+
+    /// The ant and the cells around it, so the camera moves before the ant reaches the edge.
+    /// The window slides to keep them on it, so this is never empty.
+    fn focus(&self) -> Option<Region> {
+        self.surroundings().to_region(self.origin, self.dimensions())
+    }
+
+    fn reset(&mut self, _seed: u64) {
+        *self = Self::new();
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!("Steps: {}", self.steps));
+        ui.label(format!("Ant: ({}, {})", self.ant.0, self.ant.1));
+        ui.label(format!("View: ({}, {}) at top left", self.origin.0, self.origin.1));
+        if let Some(trail) = self.cells.bounding_box() {
+            ui.label(format!("Trail: {} × {}", trail.width, trail.height));
+        }
+        ui.label(format!("Chunks: {}", self.cells.chunk_count()));
+    }
+}
@@ -0,0 +1,192 @@
+use eframe::egui;
+use egui::epaint::{ColorMode, PathStroke};
+use std::fmt::Write;
+use crate::{Simulation, Viewport};
+
+/// Runs `sim.render_world()` into a `size` rect at the origin on a scratch layer and
+/// takes back the shapes it painted, so they never reach the screen.
+pub(crate) fn capture(
+    ctx: &egui::Context,
+    sim: &dyn Simulation,
+    size: egui::Vec2,
+) -> Vec<egui::Shape> {
+    let layer = egui::LayerId::new(egui::Order::Background, egui::Id::new("svg_capture"));
+    let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, size);
+    let viewport = Viewport::fixed(rect, sim.dimensions());
+    sim.render_world(&egui::Painter::new(ctx.clone(), layer, rect), &viewport);
+    ctx.graphics_mut(|graphics| {
+        let list = std::mem::take(graphics.entry(layer));
+        list.all_entries().map(|clipped| clipped.shape.clone()).collect()
+    })
+}
+
+/// An SVG document of `size` with `background` behind `shapes`. Images, textured meshes
+/// and backend callbacks have no vector form and are left out.
+pub(crate) fn document(
+    shapes: &[egui::Shape],
+    size: egui::Vec2,
+    background: egui::Color32,
+) -> String {
+    let (w, h) = (size.x, size.y);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n"
+    );
+    let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" {}/>", fill(background));
+    for shape in shapes {
+        write_shape(&mut svg, shape);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_shape(svg: &mut String, shape: &egui::Shape) {
+    use egui::Shape;
+    let _ = match shape {
+        Shape::Vec(shapes) => {
+            shapes.iter().for_each(|shape| write_shape(svg, shape));
+            Ok(())
+        }
+        Shape::Circle(circle) => writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {} {}/>",
+            circle.center.x,
+            circle.center.y,
+            circle.radius,
+            fill(circle.fill),
+            stroke(circle.stroke),
+        ),
+        Shape::Ellipse(ellipse) => writeln!(
+            svg,
+            "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\" {} {}/>",
+            ellipse.center.x,
+            ellipse.center.y,
+            ellipse.radius.x,
+            ellipse.radius.y,
+            fill(ellipse.fill),
+            stroke(ellipse.stroke),
+        ),
+        Shape::LineSegment { points, stroke: line } => writeln!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>",
+            points[0].x,
+            points[0].y,
+            points[1].x,
+            points[1].y,
+            stroke(*line),
+        ),
+        Shape::Path(path) => {
+            let tag = if path.closed { "polygon" } else { "polyline" };
+            let fill = if path.closed { fill(path.fill) } else { "fill=\"none\"".to_owned() };
+            let stroke = path_stroke(&path.stroke);
+            writeln!(svg, "<{tag} points=\"{}\" {fill} {stroke}/>", points(&path.points))
+        }
+        Shape::Rect(rect) => writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\" {} {}/>",
+            rect.rect.left(),
+            rect.rect.top(),
+            rect.rect.width(),
+            rect.rect.height(),
+            rect.corner_radius.average(),
+            fill(rect.fill),
+            stroke(rect.stroke),
+        ),
+        Shape::Text(text) => {
+            let job = &text.galley.job;
+            let size = job.sections.first().map_or(14.0, |section| section.format.font_id.size);
+            let color = text
+                .override_text_color
+                .or_else(|| job.sections.first().map(|section| section.format.color))
+                .filter(|&color| color != egui::Color32::PLACEHOLDER)
+                .unwrap_or(text.fallback_color);
+            writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"{size}\" font-family=\"sans-serif\" \
+                 dominant-baseline=\"hanging\" {}>{}</text>",
+                text.pos.x,
+                text.pos.y,
+                fill(color),
+                escape(text.galley.text()),
+            )
+        }
+        // Untextured meshes are flat-colored triangles
+        Shape::Mesh(mesh) if mesh.texture_id == egui::TextureId::default() => {
+            for triangle in mesh.indices.chunks_exact(3) {
+                let vertices: [egui::epaint::Vertex; 3] =
+                    std::array::from_fn(|k| mesh.vertices[triangle[k] as usize]);
+                let corners = vertices.map(|vertex| vertex.pos);
+                let fill = fill(vertices[0].color);
+                let _ = writeln!(svg, "<polygon points=\"{}\" {fill}/>", points(&corners));
+            }
+            Ok(())
+        }
+        Shape::QuadraticBezier(curve) => {
+            let [a, b, c] = curve.points;
+            let d = format!("M {} {} Q {} {} {} {}", a.x, a.y, b.x, b.y, c.x, c.y);
+            write_curve(svg, &d, curve.closed, curve.fill, &curve.stroke)
+        }
+        Shape::CubicBezier(curve) => {
+            let [a, b, c, e] = curve.points;
+            let d = format!("M {} {} C {} {} {} {} {} {}", a.x, a.y, b.x, b.y, c.x, c.y, e.x, e.y);
+            write_curve(svg, &d, curve.closed, curve.fill, &curve.stroke)
+        }
+        Shape::Noop | Shape::Mesh(_) | Shape::Callback(_) => Ok(()),
+    };
+}
+
+fn write_curve(
+    svg: &mut String,
+    d: &str,
+    closed: bool,
+    color: egui::Color32,
+    line: &PathStroke,
+) -> std::fmt::Result {
+    let (d, fill) = match closed {
+        true => (format!("{d} Z"), fill(color)),
+        false => (d.to_owned(), "fill=\"none\"".to_owned()),
+    };
+    writeln!(svg, "<path d=\"{d}\" {fill} {}/>", path_stroke(line))
+}
+
+fn points(points: &[egui::Pos2]) -> String {
+    let pairs: Vec<String> = points.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+    pairs.join(" ")
+}
+
+/// `rgb(...)` and a separate opacity, since egui colors are premultiplied.
+fn color(color: egui::Color32) -> (String, f32) {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    (format!("rgb({r},{g},{b})"), a as f32 / 255.0)
+}
+
+fn fill(fill: egui::Color32) -> String {
+    if fill.a() == 0 {
+        return "fill=\"none\"".to_owned();
+    }
+    let (rgb, opacity) = color(fill);
+    format!("fill=\"{rgb}\" fill-opacity=\"{opacity}\"")
+}
+
+fn stroke(stroke: egui::Stroke) -> String {
+    if stroke.is_empty() {
+        return String::new();
+    }
+    let (rgb, opacity) = color(stroke.color);
+    format!("stroke=\"{rgb}\" stroke-opacity=\"{opacity}\" stroke-width=\"{}\"", stroke.width)
+}
+
+fn path_stroke(line: &PathStroke) -> String {
+    match line.color {
+        ColorMode::Solid(solid) => stroke(egui::Stroke::new(line.width, solid)),
+        // Gradients along a path come out in the color of their start
+        ColorMode::UV(ref uv) => {
+            let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::splat(1.0));
+            stroke(egui::Stroke::new(line.width, uv(rect, egui::Pos2::ZERO)))
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,110 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::DARK);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ThemeKind {
+    Dark,
+    Light,
+    Custom,
+}
+
+/// The palette shared by the App's chrome and the sims' output. Sims read it through
+/// [`Theme::current`] from `render()`, `render_vector()` or `ui()` instead of hardcoding
+/// colors, so their output follows the user's choice.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Theme {
+    pub kind: ThemeKind,
+    /// Canvas color behind transparent pixels and vector views.
+    pub background: egui::Color32,
+    /// Main color for whatever a sim draws on the background.
+    pub foreground: egui::Color32,
+    /// Highlight color for records, selections and other things worth noticing.
+    pub accent: egui::Color32,
+    /// De-emphasized color for axes, labels and secondary data.
+    pub muted: egui::Color32,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        kind: ThemeKind::Dark,
+        background: egui::Color32::from_rgb(20, 20, 20),
+        foreground: egui::Color32::from_rgb(220, 220, 220),
+        accent: egui::Color32::from_rgb(0, 200, 255),
+        muted: egui::Color32::from_rgb(110, 110, 110),
+    };
+
+    pub const LIGHT: Theme = Theme {
+        kind: ThemeKind::Light,
+        background: egui::Color32::from_rgb(245, 245, 245),
+        foreground: egui::Color32::from_rgb(30, 30, 30),
+        accent: egui::Color32::from_rgb(0, 110, 200),
+        muted: egui::Color32::from_rgb(150, 150, 150),
+    };
+
+    /// The theme the App is currently showing.
+    pub fn current() -> Theme {
+        *CURRENT.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Splits a color into the bytes a raster `render()` writes.
+    pub fn rgb(color: egui::Color32) -> [u8; 3] {
+        [color.r(), color.g(), color.b()]
+    }
+
+    /// Whether the background is dark enough to want light widgets on it.
+    pub fn is_dark(&self) -> bool {
+        let [r, g, b] = Self::rgb(self.background).map(u32::from);
+        (r * 299 + g * 587 + b * 114) / 1000 < 128
+    }
+
+    /// Makes this the current theme and restyles egui to match.
+    pub(crate) fn apply(&self, ctx: &egui::Context) {
+        let mut visuals = if self.is_dark() { egui::Visuals::dark() } else { egui::Visuals::light() };
+        visuals.selection.bg_fill = self.accent;
+        visuals.hyperlink_color = self.accent;
+        ctx.set_visuals(visuals);
+        *CURRENT.write().unwrap_or_else(|e| e.into_inner()) = *self;
+    }
+
+    /// Theme picker; custom themes get a color button per role. Returns whether anything
+    /// changed.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let before = *self;
+        ui.horizontal(|ui| {
+            ui.label("Theme:");
+            if ui.selectable_label(self.kind == ThemeKind::Dark, "Dark").clicked() {
+                *self = Theme::DARK;
+            }
+            if ui.selectable_label(self.kind == ThemeKind::Light, "Light").clicked() {
+                *self = Theme::LIGHT;
+            }
+            if ui.selectable_label(self.kind == ThemeKind::Custom, "Custom").clicked() {
+                self.kind = ThemeKind::Custom;
+            }
+        });
+        if self.kind == ThemeKind::Custom {
+            egui::Grid::new("theme_colors").num_columns(2).show(ui, |ui| {
+                for (label, color) in [
+                    ("Background", &mut self.background),
+                    ("Foreground", &mut self.foreground),
+                    ("Accent", &mut self.accent),
+                    ("Muted", &mut self.muted),
+                ] {
+                    ui.label(label);
+                    ui.color_edit_button_srgba(color);
+                    ui.end_row();
+                }
+            });
+        }
+        *self != before
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
@@ -0,0 +1,227 @@
+use eframe::egui;
+use std::sync::OnceLock;
+
+use crate::{SimError, Viewport};
+
+/// An image cut into equal sprites, numbered left to right and then top to bottom. The
+/// texture is uploaded the first time the atlas is drawn.
+pub struct SpriteAtlas {
+    image: egui::ColorImage,
+    tile: [usize; 2],
+    texture: OnceLock<egui::TextureHandle>,
+}
+
+impl SpriteAtlas {
+    /// Cuts `image` into sprites of `tile` pixels; a partial row or column at the edge is
+    /// left out.
+    pub fn new(image: egui::ColorImage, tile: [usize; 2]) -> Self {
+        let tile = [tile[0].max(1), tile[1].max(1)];
+        Self { image, tile, texture: OnceLock::new() }
+    }
+
+    /// Decodes a PNG sprite sheet, e.g. one embedded with `include_bytes!`.
+    pub fn from_png(bytes: &[u8], tile: [usize; 2]) -> Result<Self, SimError> {
+        let image = image::load_from_memory_with_format(bytes, image::ImageFormat::Png)
+            .map_err(|err| SimError::State(format!("Could not read sprite sheet: {err}")))?
+            .to_rgba8();
+        let size = [image.width() as usize, image.height() as usize];
+        Ok(Self::new(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()), tile))
+    }
+
+    /// `count` sprites side by side, colored pixel by pixel by `paint(sprite, x, y)`. Handy
+    /// for sims that draw their sprites in code rather than shipping a sheet.
+    pub fn from_fn(
+        count: usize,
+        tile: [usize; 2],
+        paint: impl Fn(usize, usize, usize) -> egui::Color32,
+    ) -> Self {
+        let [w, h] = tile;
+        let image = egui::ColorImage::new(
+            [w * count, h],
+            (0..w * count * h).map(|i| paint(i % (w * count) / w, i % w, i / (w * count))).collect(),
+        );
+        Self::new(image, tile)
+    }
+
+    pub fn len(&self) -> usize {
+        (self.image.size[0] / self.tile[0]) * (self.image.size[1] / self.tile[1])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Height over width of one sprite.
+    fn aspect(&self) -> f32 {
+        self.tile[1] as f32 / self.tile[0] as f32
+    }
+
+    /// Where `sprite` sits in the texture, in 0..1 coordinates.
+    fn uv(&self, sprite: usize) -> egui::Rect {
+        let columns = (self.image.size[0] / self.tile[0]).max(1);
+        let size = egui::vec2(
+            self.tile[0] as f32 / self.image.size[0] as f32,
+            self.tile[1] as f32 / self.image.size[1] as f32,
+        );
+        let min = egui::pos2((sprite % columns) as f32 * size.x, (sprite / columns) as f32 * size.y);
+        egui::Rect::from_min_size(min, size)
+    }
+
+    fn texture(&self, ctx: &egui::Context) -> egui::TextureId {
+        let options = egui::TextureOptions::NEAREST;
+        self.texture.get_or_init(|| ctx.load_texture("sprite_atlas", self.image.clone(), options)).id()
+    }
+}
+
+/// How a [`TileRenderer`] lays the grid out on screen.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TileProjection {
+    /// Cells are squares, as in raster views.
+    #[default]
+    TopDown,
+    /// Cells are diamonds twice as wide as they're tall, with x running down to the
+    /// right and y down to the left.
+    Isometric,
+}
+
+/// One sprite to draw, at a position in world units (cell (x, y) spans `x..x + 1` by
+/// `y..y + 1`), so agents between cells can use fractional positions.
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub pos: egui::Pos2,
+    pub sprite: usize,
+    /// Higher layers are drawn over lower ones; within a layer, nearer tiles win.
+    pub z: i32,
+    pub tint: egui::Color32,
+    /// Lies on the ground, covering its cell, rather than standing upright on it.
+    pub flat: bool,
+}
+
+impl Tile {
+    /// An upright sprite at cell (x, y), on layer 1.
+    pub fn new(x: usize, y: usize, sprite: usize) -> Self {
+        let pos = egui::pos2(x as f32, y as f32);
+        Self { pos, sprite, z: 1, tint: egui::Color32::WHITE, flat: false }
+    }
+
+    /// A ground sprite covering cell (x, y), on layer 0.
+    pub fn ground(x: usize, y: usize, sprite: usize) -> Self {
+        Self { z: 0, flat: true, ..Self::new(x, y, sprite) }
+    }
+
+    pub fn z(self, z: i32) -> Self {
+        Self { z, ..self }
+    }
+
+    pub fn tint(self, tint: egui::Color32) -> Self {
+        Self { tint, ..self }
+    }
+}
+
+/// Draws grids of sprites for agent-based sims, so fish, cars or ants can be told apart by
+/// their picture instead of hand-placed pixel colors. Call [`draw`](Self::draw) from
+/// `Simulation::render_world` in a `RenderMode::Vector` view; the whole batch goes to egui
+/// as a single textured mesh.
+///
+/// ```
+/// use simulation_station::{SpriteAtlas, Tile, TileProjection, TileRenderer};
+/// use eframe::egui::Color32;
+///
+/// let grass = Color32::from_rgb(40, 140, 60);
+/// let atlas = SpriteAtlas::from_fn(2, [16, 16], |sprite, _, _| {
+///     if sprite == 0 { grass } else { Color32::RED }
+/// });
+/// let tiles = TileRenderer::new(atlas).projection(TileProjection::Isometric);
+/// assert_eq!(tiles.atlas().len(), 2);
+/// let _ant = Tile::new(3, 4, 1);
+/// ```
+pub struct TileRenderer {
+    atlas: SpriteAtlas,
+    projection: TileProjection,
+}
+
+impl TileRenderer {
+    pub fn new(atlas: SpriteAtlas) -> Self {
+        Self { atlas, projection: TileProjection::default() }
+    }
+
+    pub fn projection(self, projection: TileProjection) -> Self {
+        Self { projection, ..self }
+    }
+
+    pub fn set_projection(&mut self, projection: TileProjection) {
+        self.projection = projection;
+    }
+
+    pub fn atlas(&self) -> &SpriteAtlas {
+        &self.atlas
+    }
+
+    /// Where world point `pos` lands on screen. Top-down this is `viewport.to_screen`;
+    /// isometric grids are fitted to the width of the viewport's world rect.
+    pub fn to_screen(&self, viewport: &Viewport, pos: egui::Pos2) -> egui::Pos2 {
+        match self.projection {
+            TileProjection::TopDown => viewport.to_screen(pos),
+            TileProjection::Isometric => {
+                let world = viewport.world_rect();
+                let (w, h) = (viewport.dims.0 as f32, viewport.dims.1 as f32);
+                let half_w = world.width() / (w + h).max(1.0);
+                let half_h = half_w / 2.0;
+                let top = world.center().y - (w + h) * half_h / 2.0;
+                let origin = egui::pos2(world.left() + h * half_w, top);
+                origin + egui::vec2((pos.x - pos.y) * half_w, (pos.x + pos.y) * half_h)
+            }
+        }
+    }
+
+    /// Draws `tiles` by layer and then back to front, skipping any outside the painter's
+    /// clip rect. Sprites out of the atlas's range are ignored.
+    pub fn draw(
+        &self,
+        painter: &egui::Painter,
+        viewport: &Viewport,
+        tiles: impl IntoIterator<Item = Tile>,
+    ) {
+        let depth = |tile: &Tile| match self.projection {
+            TileProjection::TopDown => tile.pos.y,
+            TileProjection::Isometric => tile.pos.x + tile.pos.y,
+        };
+        let count = self.atlas.len();
+        let mut tiles: Vec<Tile> = tiles.into_iter().filter(|tile| tile.sprite < count).collect();
+        tiles.sort_by(|a, b| a.z.cmp(&b.z).then(depth(a).total_cmp(&depth(b))));
+
+        let clip = painter.clip_rect();
+        let mut mesh = egui::Mesh::with_texture(self.atlas.texture(painter.ctx()));
+        for tile in tiles {
+            let uv = self.atlas.uv(tile.sprite);
+            let corners = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]
+                .map(|corner| self.to_screen(viewport, tile.pos + egui::Vec2::from(corner)));
+            if tile.flat {
+                if !clip.intersects(egui::Rect::from_points(&corners)) {
+                    continue;
+                }
+                let base = mesh.vertices.len() as u32;
+                let uvs = [uv.left_top(), uv.right_top(), uv.right_bottom(), uv.left_bottom()];
+                for (pos, uv) in corners.into_iter().zip(uvs) {
+                    mesh.vertices.push(egui::epaint::Vertex { pos, uv, color: tile.tint });
+                }
+                mesh.add_triangle(base, base + 1, base + 2);
+                mesh.add_triangle(base, base + 2, base + 3);
+            } else {
+                // Stands on the middle of its cell, as wide as the cell is on screen
+                let width = corners[1].x.max(corners[2].x) - corners[0].x.min(corners[3].x);
+                let foot = self.to_screen(viewport, tile.pos + egui::vec2(0.5, 0.5));
+                let foot = match self.projection {
+                    TileProjection::TopDown => egui::pos2(foot.x, corners[2].y),
+                    TileProjection::Isometric => foot,
+                };
+                let size = egui::vec2(width, width * self.atlas.aspect());
+                let rect = egui::Rect::from_min_size(foot - egui::vec2(size.x / 2.0, size.y), size);
+                if clip.intersects(rect) {
+                    mesh.add_rect_with_uv(rect, uv, tile.tint);
+                }
+            }
+        }
+        painter.add(mesh);
+    }
+}
@@ -0,0 +1,192 @@
+use eframe::egui;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, sync_channel, Receiver, SyncSender, TryRecvError};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Container {
+    Mp4,
+    WebM,
+}
+
+impl Container {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Container::Mp4 => "mp4",
+            Container::WebM => "webm",
+        }
+    }
+
+    fn codec(self) -> &'static str {
+        match self {
+            Container::Mp4 => "libx264",
+            Container::WebM => "libvpx-vp9",
+        }
+    }
+}
+
+/// An ffmpeg process being fed raw frames by a writer thread.
+struct Encoder {
+    /// Frame size; every frame is scaled to the size of the first one.
+    size: (u32, u32),
+    frames: SyncSender<Vec<u8>>,
+    done: Receiver<Result<PathBuf, String>>,
+}
+
+/// Records frames into a video by piping them to an `ffmpeg` found on the `PATH`. Frames
+/// go to ffmpeg from a separate thread, and the file is finished off in the background.
+pub(crate) struct VideoRecorder {
+    pub(crate) container: Container,
+    pub(crate) fps: u32,
+    pub(crate) bitrate_kbps: u32,
+    /// Record the canvas as shown on screen, overlays included, instead of the sim's frames.
+    pub(crate) overlay: bool,
+    path: Option<PathBuf>,
+    encoder: Option<Encoder>,
+    frames: usize,
+    next_capture: f64,
+    finishing: Option<Receiver<Result<PathBuf, String>>>,
+}
+
+impl VideoRecorder {
+    pub(crate) fn is_recording(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub(crate) fn is_finishing(&self) -> bool {
+        self.finishing.is_some()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.frames
+    }
+
+    /// Starts a recording into `path`; ffmpeg is launched once the first frame arrives.
+    pub(crate) fn start(&mut self, path: PathBuf) {
+        self.path = Some(path);
+        self.encoder = None;
+        self.frames = 0;
+        self.next_capture = 0.0;
+    }
+
+    /// Whether a frame should be captured at `time` seconds. Claims the frame, so asking
+    /// twice at the same time answers `true` only once.
+    pub(crate) fn due(&mut self, time: f64) -> bool {
+        if !self.is_recording() || time < self.next_capture {
+            return false;
+        }
+        self.next_capture = time.max(self.next_capture) + 1.0 / self.fps as f64;
+        true
+    }
+
+    /// Sends `image` to the encoder. A failure ends the recording and is reported by
+    /// `poll` like any other outcome.
+    pub(crate) fn push(&mut self, image: &egui::ColorImage) {
+        let Some(path) = self.path.clone() else { return };
+        if self.encoder.is_none() {
+            // yuv420p needs even dimensions
+            let [w, h] = image.size.map(|n| (n as u32 & !1).max(2));
+            match self.spawn(path, (w, h)) {
+                Ok(encoder) => self.encoder = Some(encoder),
+                Err(e) => {
+                    self.fail(e);
+                    return;
+                }
+            }
+        }
+        let Some(encoder) = &self.encoder else { return };
+        let [w, h] = image.size;
+        let pixels = image.pixels.iter().flat_map(|c| c.to_srgba_unmultiplied()).collect();
+        let Some(frame) = image::RgbaImage::from_raw(w as u32, h as u32, pixels) else { return };
+        let frame = if frame.dimensions() == encoder.size {
+            frame
+        } else {
+            let (w, h) = encoder.size;
+            image::imageops::resize(&frame, w, h, image::imageops::FilterType::Nearest)
+        };
+        // The writer only hangs up once ffmpeg has died
+        if encoder.frames.send(frame.into_raw()).is_ok() {
+            self.frames += 1;
+        } else {
+            self.stop();
+        }
+    }
+
+    /// Ends the recording; ffmpeg finishes the file in the background.
+    pub(crate) fn stop(&mut self) {
+        self.path = None;
+        if let Some(encoder) = self.encoder.take() {
+            drop(encoder.frames);
+            self.finishing = Some(encoder.done);
+        }
+    }
+
+    /// The outcome of the last recording, once ffmpeg has exited.
+    pub(crate) fn poll(&mut self) -> Option<Result<PathBuf, String>> {
+        let result = match self.finishing.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("the encoder stopped unexpectedly".to_owned()),
+        };
+        self.finishing = None;
+        Some(result)
+    }
+
+    fn fail(&mut self, error: String) {
+        self.path = None;
+        let (tx, rx) = channel();
+        let _ = tx.send(Err(error));
+        self.finishing = Some(rx);
+    }
+
+    fn spawn(&self, path: PathBuf, (w, h): (u32, u32)) -> Result<Encoder, String> {
+        let mut child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{w}x{h}"), "-r", &self.fps.to_string(), "-i", "-"])
+            .args(["-c:v", self.container.codec(), "-pix_fmt", "yuv420p"])
+            .args(["-b:v", &format!("{}k", self.bitrate_kbps)])
+            .arg(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("couldn't start ffmpeg: {e}"))?;
+        let mut stdin = child.stdin.take().ok_or("ffmpeg has no stdin")?;
+
+        // A few frames of slack so a slow encoder doesn't stall the UI right away
+        let (frames, rx) = sync_channel::<Vec<u8>>(8);
+        let (tx, done) = channel();
+        std::thread::spawn(move || {
+            for frame in rx {
+                if stdin.write_all(&frame).is_err() {
+                    break;
+                }
+            }
+            drop(stdin);
+            let result = match child.wait_with_output() {
+                Ok(output) if output.status.success() => Ok(path),
+                Ok(output) => Err(String::from_utf8_lossy(&output.stderr).trim().to_owned()),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = tx.send(result);
+        });
+        Ok(Encoder { size: (w, h), frames, done })
+    }
+}
+
+impl Default for VideoRecorder {
+    fn default() -> Self {
+        Self {
+            container: Container::Mp4,
+            fps: 30,
+            bitrate_kbps: 4000,
+            overlay: false,
+            path: None,
+            encoder: None,
+            frames: 0,
+            next_capture: 0.0,
+            finishing: None,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use crate::{Colormap, FieldScale, Grid, Grid3};
+
+/// The axis a [`VolumeView`] looks along.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Axis {
+    X,
+    Y,
+    #[default]
+    Z,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum VolumeMode {
+    /// The single layer of cells at `VolumeView::slice`.
+    #[default]
+    Slice,
+    /// The largest value along each line of sight, showing the whole volume at once.
+    MaxProjection,
+}
+
+/// Flattens a [`Grid3`] into a 2D image for display: an axis-aligned slice, or a maximum
+/// intensity projection. Looking along Z the image is the x-y plane; along Y it's x-z
+/// (z down) and along X it's z-y (z across).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeView {
+    pub axis: Axis,
+    pub mode: VolumeMode,
+    /// Position along `axis` of the slice shown; clamped to the volume.
+    pub slice: usize,
+}
+
+impl VolumeView {
+    /// The (width, height) of the image of `grid`.
+    pub fn dimensions<T>(&self, grid: &Grid3<T>) -> (usize, usize) {
+        match self.axis {
+            Axis::X => (grid.depth(), grid.height()),
+            Axis::Y => (grid.width(), grid.depth()),
+            Axis::Z => (grid.width(), grid.height()),
+        }
+    }
+
+    fn depth<T>(&self, grid: &Grid3<T>) -> usize {
+        match self.axis {
+            Axis::X => grid.width(),
+            Axis::Y => grid.height(),
+            Axis::Z => grid.depth(),
+        }
+    }
+
+    /// The volume cell at `along` on the line of sight through image cell (u, v).
+    fn cell(&self, u: usize, v: usize, along: usize) -> (usize, usize, usize) {
+        match self.axis {
+            Axis::X => (along, v, u),
+            Axis::Y => (u, along, v),
+            Axis::Z => (u, v, along),
+        }
+    }
+
+    /// The volume cell image cell (u, v) shows in slice mode, for `probe()`.
+    pub fn cell_at<T>(&self, grid: &Grid3<T>, u: usize, v: usize) -> (usize, usize, usize) {
+        self.cell(u, v, self.slice.min(self.depth(grid).saturating_sub(1)))
+    }
+
+    /// The image as a 2D grid of values.
+    pub fn project<T: Copy + Default + PartialOrd>(&self, grid: &Grid3<T>) -> Grid<T> {
+        let (w, h) = self.dimensions(grid);
+        let depth = self.depth(grid);
+        if self.mode == VolumeMode::Slice && depth > 0 {
+            return grid.slice(self.axis, self.slice.min(depth - 1));
+        }
+        let mut image = Grid::new(w, h);
+        for v in 0..h {
+            for u in 0..w {
+                let value = (0..depth)
+                    .map(|along| {
+                        let (x, y, z) = self.cell(u, v, along);
+                        *grid.get(x, y, z)
+                    })
+                    .reduce(|max, value| if value > max { value } else { max })
+                    .unwrap_or_default();
+                image.set(u, v, value);
+            }
+        }
+        image
+    }
+
+    /// Colors the image of `grid` into an RGB `buffer` sized by [`dimensions`](Self::dimensions),
+    /// as [`Colormap::render_field`] does. Returns the value range the colors span.
+    pub fn render(
+        &self,
+        grid: &Grid3<f32>,
+        colormap: &Colormap,
+        scale: &FieldScale,
+        buffer: &mut [u8],
+    ) -> (f32, f32) {
+        colormap.render_field(&self.project(grid), scale, buffer)
+    }
+
+    /// Axis and mode pickers, and a slider for the slice. Returns whether anything changed.
+    pub fn ui<T>(&mut self, ui: &mut egui::Ui, grid: &Grid3<T>) -> bool {
+        let before = *self;
+        ui.horizontal(|ui| {
+            ui.label("Look along");
+            for (axis, label) in [(Axis::X, "X"), (Axis::Y, "Y"), (Axis::Z, "Z")] {
+                ui.selectable_value(&mut self.axis, axis, label);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.mode, VolumeMode::Slice, "Slice");
+            ui.selectable_value(&mut self.mode, VolumeMode::MaxProjection, "Max projection")
+                .on_hover_text("The largest value along each line of sight");
+        });
+        if self.mode == VolumeMode::Slice {
+            let last = self.depth(grid).saturating_sub(1);
+            self.slice = self.slice.min(last);
+            ui.add(egui::Slider::new(&mut self.slice, 0..=last).text("Slice"));
+        }
+        *self != before
+    }
+}
@@ -0,0 +1,285 @@
+use eframe::egui;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use crate::{
+    sim_params, Boundary, Category, Grid, Metrics, Neighborhood, Params, RenderMode, SimConfig,
+    SimError, SimHelp, SimInfo, Simulation, SpriteAtlas, Tile, TileProjection, TileRenderer, ViewInfo,
+    Viewport,
+};
+
+pub const INFO: SimInfo = SimInfo {
+    description: "Wa-Tor: fish and sharks breeding, hunting and starving on a toroidal or \
+                  walled ocean, drawn with sprites from above or in isometric.",
+    category: Category::Other,
+    tags: &["agents", "predator-prey", "tiles"],
+};
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 60;
+
+const WATER: usize = 0;
+const FISH: usize = 1;
+const SHARK: usize = 2;
+
+sim_params! {
+    pub struct WatorParams {
+        pub fish_breed: u32 = 3 => 1..=20,
+        pub shark_breed: u32 = 10 => 1..=30,
+        pub shark_starve: u32 = 3 => 1..=20,
+        pub fish: f32 = 0.3 => 0.0..=1.0,
+        pub sharks: f32 = 0.05 => 0.0..=1.0,
+        pub wrap: bool = true,
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Cell {
+    #[default]
+    Water,
+    /// Steps since it last bred.
+    Fish { age: u32 },
+    Shark { age: u32, energy: u32 },
+}
+
+/// A.K. Dewdney's Wa-Tor predator-prey model. Every animal acts once per step, in a
+/// random order.
+pub struct WatorSim {
+    ocean: Grid<Cell>,
+    /// The step each cell's animal last acted in, so an animal that moves ahead of the
+    /// scan isn't moved twice.
+    acted: Grid<u64>,
+    order: Vec<usize>,
+    step: u64,
+    rng: StdRng,
+    tiles: TileRenderer,
+    params: WatorParams,
+}
+
+impl WatorSim {
+    pub fn new() -> Self {
+        let mut sim = Self {
+            ocean: Grid::new(WIDTH, HEIGHT).with_boundary(Boundary::Wrap),
+            acted: Grid::new(WIDTH, HEIGHT),
+            order: (0..WIDTH * HEIGHT).collect(),
+            step: 0,
+            rng: StdRng::seed_from_u64(0),
+            tiles: TileRenderer::new(sprites()),
+            params: WatorParams::default(),
+        };
+        sim.reset(0);
+        sim
+    }
+
+    fn count(&self, sprite: usize) -> usize {
+        let matches = |cell: &&Cell| match cell {
+            Cell::Water => sprite == WATER,
+            Cell::Fish { .. } => sprite == FISH,
+            Cell::Shark { .. } => sprite == SHARK,
+        };
+        self.ocean.cells().iter().filter(matches).count()
+    }
+
+    /// The four neighbors of (x, y) whose cell passes `wanted`, in a random
+    /// order.
+    fn neighbors(&mut self, x: usize, y: usize, wanted: impl Fn(&Cell) -> bool) -> Vec<(usize, usize)> {
+        let mut found: Vec<(usize, usize)> = self
+            .ocean
+            .neighbor_positions(x, y, Neighborhood::VonNeumann)
+            .filter(|&(nx, ny)| wanted(self.ocean.get(nx, ny)))
+            .collect();
+        found.shuffle(&mut self.rng);
+        found
+    }
+
+    /// Moves the animal at (x, y) to `to`, leaving `behind` in its place, and marks it as
+    /// having acted.
+    fn move_to(&mut self, (x, y): (usize, usize), to: (usize, usize), animal: Cell, behind: Cell) {
+        self.ocean.set(x, y, behind);
+        self.ocean.set(to.0, to.1, animal);
+        self.acted.set(to.0, to.1, self.step);
+    }
+}
+
+/// Water, a fish and a shark, 16 pixels square.
+fn sprites() -> SpriteAtlas {
+    SpriteAtlas::from_fn(3, [16, 16], |sprite, x, y| {
+        let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+        let in_ellipse = |cx: f32, cy: f32, rx: f32, ry: f32| {
+            ((fx - cx) / rx).powi(2) + ((fy - cy) / ry).powi(2) <= 1.0
+        };
+        match sprite {
+            WATER if (x + 2 * y) % 9 == 0 => egui::Color32::from_rgb(40, 96, 168),
+            WATER => egui::Color32::from_rgb(20, 64, 128),
+            FISH if in_ellipse(9.0, 11.0, 5.0, 3.0) => egui::Color32::from_rgb(255, 150, 40),
+            // Tail
+            FISH if x < 5 && (fy - 11.0).abs() <= 5.0 - fx => egui::Color32::from_rgb(230, 110, 20),
+            SHARK if in_ellipse(8.0, 12.0, 7.0, 2.5) => egui::Color32::from_rgb(150, 160, 170),
+            // Dorsal fin
+            SHARK if (6..10).contains(&x) && y >= 4 && fx - 6.0 <= (fy - 4.0) * 0.6 => {
+                egui::Color32::from_rgb(110, 120, 130)
+            }
+            _ => egui::Color32::TRANSPARENT,
+        }
+    })
+}
+
+impl Simulation for WatorSim {
+    fn name(&self) -> &str {
+        "Wa-Tor"
+    }
+
+    fn info(&self) -> SimInfo {
+        INFO
+    }
+
+    fn help(&self) -> SimHelp {
+        SimHelp {
+            overview: "Fish swim to a random free neighboring cell and, once old enough, leave a \
+                       young fish behind. Sharks eat a neighboring fish if there is one and swim \
+                       otherwise; they breed like fish, and starve if they go too long without \
+                       eating. The ocean wraps around at its edges unless walled in. The \
+                       populations rise and fall out of step, which the Metrics panel plots.",
+            legend: vec![
+                (egui::Color32::from_rgb(255, 150, 40), "Fish"),
+                (egui::Color32::from_rgb(150, 160, 170), "Shark"),
+            ],
+            params: vec![
+                ("fish_breed", "Steps a fish waits between offspring"),
+                ("shark_breed", "Steps a shark waits between offspring"),
+                ("shark_starve", "Steps a shark survives without eating"),
+                ("fish", "Share of the ocean stocked with fish on reset"),
+                ("sharks", "Share of the ocean stocked with sharks on reset"),
+                ("wrap", "Join opposite edges into a torus instead of walling the ocean in"),
+            ],
+            controls: Vec::new(),
+        }
+    }
+
+    fn config(&self) -> SimConfig {
+        SimConfig {
+            max_speed: 120.0,
+            default_speed: 10.0,
+            speed_unit: "chronons/sec",
+            render_mode: RenderMode::Vector,
+            ..SimConfig::default()
+        }
+    }
+
+    fn dimensions(&self) -> (usize, usize) {
+        (WIDTH, HEIGHT)
+    }
+
+    fn update(&mut self) -> Result<(), SimError> {
+        self.step += 1;
+        self.ocean.set_boundary(if self.params.wrap { Boundary::Wrap } else { Boundary::Open });
+        let mut order = std::mem::take(&mut self.order);
+        order.shuffle(&mut self.rng);
+        for &index in &order {
+            let (x, y) = (index % WIDTH, index / WIDTH);
+            if *self.acted.get(x, y) == self.step {
+                continue;
+            }
+            match *self.ocean.get(x, y) {
+                Cell::Water => {}
+                Cell::Fish { age } => {
+                    let breeds = age + 1 >= self.params.fish_breed;
+                    if let Some(&to) = self.neighbors(x, y, |cell| *cell == Cell::Water).first() {
+                        let (age, behind) =
+                            if breeds { (0, Cell::Fish { age: 0 }) } else { (age + 1, Cell::Water) };
+                        self.move_to((x, y), to, Cell::Fish { age }, behind);
+                    } else {
+                        self.ocean.set(x, y, Cell::Fish { age: age + 1 });
+                    }
+                }
+                Cell::Shark { age, energy } => {
+                    let prey = self.neighbors(x, y, |cell| matches!(cell, Cell::Fish { .. }));
+                    let (to, energy) = match prey.first() {
+                        Some(&to) => (Some(to), self.params.shark_starve),
+                        None if energy <= 1 => {
+                            self.ocean.set(x, y, Cell::Water);
+                            continue;
+                        }
+                        None => {
+                            let free = self.neighbors(x, y, |cell| *cell == Cell::Water);
+                            (free.first().copied(), energy - 1)
+                        }
+                    };
+                    let breeds = age + 1 >= self.params.shark_breed;
+                    match to {
+                        Some(to) => {
+                            let young = Cell::Shark { age: 0, energy: self.params.shark_starve };
+                            let (age, behind) = if breeds { (0, young) } else { (age + 1, Cell::Water) };
+                            self.move_to((x, y), to, Cell::Shark { age, energy }, behind);
+                        }
+                        None => self.ocean.set(x, y, Cell::Shark { age: age + 1, energy }),
+                    }
+                }
+            }
+        }
+        self.order = order;
+        Ok(())
+    }
+
+    fn version(&self) -> Option<u64> {
+        Some(self.step)
+    }
+
+    fn render(&self, _buffer: &mut [u8]) {}
+
+    fn render_world(&self, painter: &egui::Painter, viewport: &Viewport) {
+        let ocean = &self.ocean;
+        let tiles = (0..HEIGHT).flat_map(|y| (0..WIDTH).map(move |x| (x, y))).flat_map(|(x, y)| {
+            let animal = match ocean.get(x, y) {
+                Cell::Water => None,
+                Cell::Fish { .. } => Some(Tile::new(x, y, FISH)),
+                Cell::Shark { .. } => Some(Tile::new(x, y, SHARK)),
+            };
+            std::iter::once(Tile::ground(x, y, WATER)).chain(animal)
+        });
+        self.tiles.draw(painter, viewport, tiles);
+    }
+
+    fn views(&self) -> Vec<ViewInfo> {
+        ["Top-down", "Isometric"]
+            .map(|name| ViewInfo { name: name.to_owned(), render_mode: RenderMode::Vector })
+            .to_vec()
+    }
+
+    fn select_view(&mut self, index: usize) {
+        let projection = if index == 1 { TileProjection::Isometric } else { TileProjection::TopDown };
+        self.tiles.set_projection(projection);
+    }
+
+    fn record_metrics(&self, metrics: &mut Metrics) {
+        metrics.record("fish", self.count(FISH) as f64);
+        metrics.record("sharks", self.count(SHARK) as f64);
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.step = 0;
+        self.acted.cells_mut().fill(0);
+        let (fish, sharks) = (self.params.fish, self.params.sharks);
+        for index in 0..WIDTH * HEIGHT {
+            let roll: f32 = self.rng.random();
+            let cell = if roll < sharks {
+                let age = self.rng.random_range(0..self.params.shark_breed);
+                Cell::Shark { age, energy: self.params.shark_starve }
+            } else if roll < sharks + fish {
+                Cell::Fish { age: self.rng.random_range(0..self.params.fish_breed) }
+            } else {
+                Cell::Water
+            };
+            self.ocean.cells_mut()[index] = cell;
+        }
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!("Chronon: {}", self.step));
+        ui.label(format!("Fish: {}", self.count(FISH)));
+        ui.label(format!("Sharks: {}", self.count(SHARK)));
+    }
+
+    fn params(&mut self) -> Option<&mut dyn Params> {
+        Some(&mut self.params)
+    }
+}
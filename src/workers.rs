@@ -0,0 +1,89 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+/// How readily the OS schedules a sim's worker threads next to the UI and other programs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum WorkerPriority {
+    #[default]
+    Normal,
+    /// Gives way to the UI and anything else busy.
+    Low,
+    /// Only runs on otherwise idle cores, for long background searches.
+    Idle,
+}
+
+impl WorkerPriority {
+    pub const ALL: [WorkerPriority; 3] = [Self::Normal, Self::Low, Self::Idle];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "Normal",
+            Self::Low => "Low",
+            Self::Idle => "Idle",
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn nice(self) -> i32 {
+        match self {
+            Self::Normal => 0,
+            Self::Low => 10,
+            Self::Idle => 19,
+        }
+    }
+}
+
+/// How much of the machine an AsyncSim's workers may take, set in the Controls panel for
+/// all sims or per sim.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkerSettings {
+    /// Threads for `WorkerContext::parallel`; 0 leaves it to the sim.
+    pub threads: usize,
+    pub priority: WorkerPriority,
+}
+
+impl WorkerSettings {
+    /// A thread count and priority picker.
+    pub(crate) fn ui(&mut self, ui: &mut egui::Ui, id: &str) {
+        ui.horizontal(|ui| {
+            ui.label("Threads:");
+            ui.add(
+                egui::DragValue::new(&mut self.threads)
+                    .range(0..=256)
+                    .custom_formatter(|n, _| match n {
+                        0.0 => "auto".to_owned(),
+                        n => format!("{n}"),
+                    }),
+            )
+            .on_hover_text("Threads a parallel sim fans out over; auto lets the sim decide");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Priority:").on_hover_text("Only lowered on Linux");
+            egui::ComboBox::from_id_salt(id).selected_text(self.priority.label()).show_ui(ui, |ui| {
+                for priority in WorkerPriority::ALL {
+                    ui.selectable_value(&mut self.priority, priority, priority.label());
+                }
+            });
+        });
+    }
+}
+
+/// Sets the calling thread's priority. Only Linux, where niceness is per thread, is
+/// supported; elsewhere this does nothing.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub(crate) fn set_thread_priority(priority: WorkerPriority) -> std::io::Result<()> {
+    #[cfg(target_os = "linux")]
+    if priority != WorkerPriority::Normal {
+        // SAFETY: plain syscalls on the calling thread, with no pointers involved
+        let result = unsafe {
+            libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, priority.nice())
+        };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = priority;
+    Ok(())
+}
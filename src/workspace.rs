@@ -0,0 +1,34 @@
+use egui_dock::DockState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::dock::Panel;
+
+/// A sim open in a tab, by registry name.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct SlotState {
+    pub(crate) sim: String,
+    pub(crate) params: Option<Value>,
+    pub(crate) view: usize,
+    /// Resolution of sims that can be resized.
+    #[serde(default)]
+    pub(crate) size: Option<(usize, usize)>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct TabState {
+    pub(crate) slots: Vec<SlotState>,
+    pub(crate) focus: usize,
+    pub(crate) speed: f32,
+    pub(crate) time_scale: f32,
+    pub(crate) turbo: bool,
+    pub(crate) paused: bool,
+}
+
+/// A saved setup: the open tabs with their sims, speeds and parameters, and the panel
+/// layout. Sims come back freshly reset rather than mid-run.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct Workspace {
+    pub(crate) tabs: Vec<TabState>,
+    pub(crate) active: usize,
+    pub(crate) layout: DockState<Panel>,
+}